@@ -40,7 +40,13 @@ pub trait RemoveBalanced {
     fn remove_balanced() -> (u8, u8);
 }
 
-struct RemoveBalancedReducer<R: RemoveBalanced>(PhantomData<R>);
+/// A `RemoveRanges` implementation that removes the ranges within `R`'s
+/// balanced bytes.
+///
+/// Wrap this in a `RemoveRangesReducer` to run the same reducer as
+/// `run_balanced::<R>()` without a reducer script.
+#[derive(Clone, Debug)]
+pub struct RemoveBalancedReducer<R: RemoveBalanced>(PhantomData<R>);
 
 impl<R: RemoveBalanced> RemoveRanges for RemoveBalancedReducer<R> {
     fn remove_ranges(seed: PathBuf) -> io::Result<Vec<Range<u64>>> {
@@ -81,6 +87,46 @@ impl<R: RemoveBalanced> RemoveRanges for RemoveBalancedReducer<R> {
     }
 }
 
+/// Remove text within balanced angle brackets: `<` and `>`.
+#[derive(Clone, Debug)]
+pub struct Angles;
+
+impl RemoveBalanced for Angles {
+    fn remove_balanced() -> (u8, u8) {
+        (b'<', b'>')
+    }
+}
+
+/// Remove text within balanced curly braces: `{` and `}`.
+#[derive(Clone, Debug)]
+pub struct Curlies;
+
+impl RemoveBalanced for Curlies {
+    fn remove_balanced() -> (u8, u8) {
+        (b'{', b'}')
+    }
+}
+
+/// Remove text within balanced parentheses: `(` and `)`.
+#[derive(Clone, Debug)]
+pub struct Parens;
+
+impl RemoveBalanced for Parens {
+    fn remove_balanced() -> (u8, u8) {
+        (b'(', b')')
+    }
+}
+
+/// Remove text within balanced square brackets: `[` and `]`.
+#[derive(Clone, Debug)]
+pub struct Squares;
+
+impl RemoveBalanced for Squares {
+    fn remove_balanced() -> (u8, u8) {
+        (b'[', b']')
+    }
+}
+
 /// Run a reducer script that removes text within balanced brackets/parens/etc
 /// from the seed test case.
 ///
@@ -5,7 +5,7 @@ extern crate serde_derive;
 
 use preduce_ranges_reducer::RemoveRanges;
 use std::fs;
-use std::io::{self, Read};
+use std::io::{self, BufRead, Read};
 use std::ops::Range;
 use std::path::PathBuf;
 
@@ -41,3 +41,30 @@ impl RemoveRanges for Chunks {
         Ok(ranges)
     }
 }
+
+/// A `RemoveRanges` implementation that removes blank lines from the seed file.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Blank;
+
+impl RemoveRanges for Blank {
+    fn remove_ranges(seed: PathBuf) -> io::Result<Vec<Range<u64>>> {
+        let seed = fs::File::open(seed)?;
+        let mut seed = io::BufReader::new(seed);
+
+        let mut ranges = vec![];
+
+        let mut offset = 0u64;
+        let mut line = String::new();
+        while {
+            line.clear();
+            seed.read_line(&mut line)? > 0
+        } {
+            if line.trim().is_empty() {
+                ranges.push(offset..offset + line.len() as u64);
+            }
+            offset += line.len() as u64;
+        }
+
+        Ok(ranges)
+    }
+}
@@ -22,8 +22,10 @@ pub trait ClangDelta {
     fn transformation() -> &'static str;
 }
 
+/// The `preduce_reducer_script::Reducer` that `run_clang_delta::<C>()` runs, exposed so
+/// that it can be run without a reducer script.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
-struct ClangDeltaReducer<C: ClangDelta> {
+pub struct ClangDeltaReducer<C: ClangDelta> {
     clang_delta: PhantomData<C>,
     index: usize,
 }
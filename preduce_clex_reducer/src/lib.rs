@@ -18,8 +18,10 @@ pub trait Clex {
     fn clex_command() -> &'static str;
 }
 
+/// The `preduce_reducer_script::Reducer` that `run_clex::<C>()` runs, exposed so
+/// that it can be run without a reducer script.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
-struct ClexReducer<C: Clex> {
+pub struct ClexReducer<C: Clex> {
     clex: PhantomData<C>,
     index: usize,
 }
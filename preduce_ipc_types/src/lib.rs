@@ -44,7 +44,7 @@ pub enum Request {
     /// `{ "FastForward": { "seed": "path/to/seed", "state": <JSON value>, "n": <unsigned integer> } }`
     FastForward(FastForwardRequest),
 
-    /// `{ "Reduce": { "seed": "path/to/seed", "state": <JSON value>, "dest": "path/to/dest" } }`
+    /// `{ "Reduce": { "seed": "path/to/seed", "state": <JSON value>, "dest": "path/to/dest", "member": "optional/path/to/member" } }`
     Reduce(ReduceRequest),
 }
 
@@ -104,6 +104,11 @@ pub struct ReduceRequest {
     pub state: serde_json::Value,
     /// The path where the candidate should be created at.
     pub dest: PathBuf,
+    /// When the test case is a directory and the seed is one of the files
+    /// within it, this is the seed's path relative to the directory. Absent
+    /// otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<PathBuf>,
 }
 
 /// A response from a reducer script to a `Request` sent by `preduce`.
@@ -164,6 +169,97 @@ pub struct ReduceResponse {
     pub reduced: bool,
 }
 
+/// The version of the protocol between a `preduce` coordinator and its remote
+/// `preduce-worker`s described by `WorkerRequest` and `WorkerResponse`.
+///
+/// Version 1 was the original protocol, which had no `Hello` handshake.
+pub const WORKER_PROTOCOL_VERSION: u32 = 2;
+
+/// A request from a `preduce` coordinator to a remote `preduce-worker`.
+///
+/// Every request must be responded to with a `WorkerResponse`. The first
+/// request on each connection is `WorkerRequest::Hello`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum WorkerRequest {
+    /// `{ "Hello": { "protocol_version": <unsigned integer> } }`
+    ///
+    /// Must be responded to with `WorkerResponse::Hello`. If the versions
+    /// differ, both ends close the connection.
+    Hello(HelloRequest),
+
+    /// `{ "Judge": { "predicate": "name", "file_name": "name", "len": <unsigned integer> } }`
+    ///
+    /// The request's line is followed by exactly `len` bytes: the contents of
+    /// the candidate to judge.
+    Judge(JudgeRequest),
+}
+
+/// Check that the worker speaks the same version of the protocol.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct HelloRequest {
+    /// The coordinator's `WORKER_PROTOCOL_VERSION`.
+    pub protocol_version: u32,
+}
+
+/// Judge whether a candidate is interesting.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct JudgeRequest {
+    /// The name of the is-interesting predicate the worker should run.
+    pub predicate: String,
+    /// The file name to give the candidate, which predicates may depend upon,
+    /// for example to pick a compiler's language by the file's extension.
+    pub file_name: String,
+    /// The length of the candidate's contents, which follow the request.
+    pub len: u64,
+}
+
+/// A response from a remote `preduce-worker` to a `WorkerRequest`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub enum WorkerResponse {
+    /// `{ "Hello": { "protocol_version": <unsigned integer> } }`
+    Hello(HelloResponse),
+
+    /// `{ "Judged": { "interesting": <bool> } }`
+    Judged(JudgedResponse),
+
+    /// `{ "TimedOut": { "millis": <unsigned integer> } }`
+    TimedOut(TimedOutResponse),
+
+    /// `{ "Error": { "message": "description of the error" } }`
+    Error(ErrorResponse),
+}
+
+/// A response to `WorkerRequest::Hello`.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct HelloResponse {
+    /// The worker's `WORKER_PROTOCOL_VERSION`.
+    pub protocol_version: u32,
+}
+
+/// A response to `WorkerRequest::Judge` with the predicate's judgement.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct JudgedResponse {
+    /// Whether the candidate is interesting.
+    pub interesting: bool,
+}
+
+/// A response to `WorkerRequest::Judge` when the predicate ran past the
+/// worker's timeout and was killed.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct TimedOutResponse {
+    /// The timeout that the predicate ran past, in milliseconds.
+    pub millis: u64,
+}
+
+/// A response to `WorkerRequest::Judge` when the worker could not judge the
+/// candidate, for example because it has no predicate with the requested
+/// name.
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    /// A description of the error.
+    pub message: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,7 +360,7 @@ fn try_run<R: Reducer>() -> io::Result<()> {
                 };
                 Response::FastForward(FastForwardResponse { next_state })
             }
-            Request::Reduce(ReduceRequest { seed, state, dest }) => {
+            Request::Reduce(ReduceRequest { seed, state, dest, .. }) => {
                 let reducer: R = serde_json::from_value(state)?;
                 Response::Reduce(ReduceResponse {
                     reduced: reducer.infallible_reduce(seed, dest),
@@ -1,3 +1,5 @@
+#[macro_use]
+extern crate lazy_static;
 extern crate preduce_ranges_reducer;
 extern crate regex;
 
@@ -49,7 +51,13 @@ pub trait RemoveRegex {
     fn remove_regex() -> &'static regex::bytes::Regex;
 }
 
-struct RemoveRegexReducer<R: RemoveRegex>(PhantomData<R>);
+/// A `RemoveRanges` implementation that removes the capture groups of `R`'s
+/// regex's matches.
+///
+/// Wrap this in a `RemoveRangesReducer` to run the same reducer as
+/// `run_regex::<R>()` without a reducer script.
+#[derive(Clone, Debug)]
+pub struct RemoveRegexReducer<R: RemoveRegex>(PhantomData<R>);
 
 impl<R: RemoveRegex> RemoveRanges for RemoveRegexReducer<R> {
     fn remove_ranges(seed: PathBuf) -> io::Result<Vec<Range<u64>>> {
@@ -76,6 +84,20 @@ impl<R: RemoveRegex> RemoveRanges for RemoveRegexReducer<R> {
     }
 }
 
+/// Remove `#include` lines.
+#[derive(Clone, Debug)]
+pub struct Includes;
+
+impl RemoveRegex for Includes {
+    fn remove_regex() -> &'static regex::bytes::Regex {
+        lazy_static! {
+            static ref RE: regex::bytes::Regex =
+                regex::bytes::Regex::new(r#"(?m)(^\s*#\s*include.*$)"#).unwrap();
+        }
+        &*RE
+    }
+}
+
 /// Run a reducer script that removes `R`'s regex's matches from the seed test
 /// case.
 ///
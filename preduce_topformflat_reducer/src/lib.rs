@@ -26,14 +26,19 @@ pub trait Topformflat {
     fn flatten() -> u8;
 }
 
+/// The `preduce_reducer_script::Reducer` that `run_topformflat::<T>()` runs,
+/// exposed so that it can be run without a reducer script.
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
-enum TopformflatReducer<T: Topformflat> {
-    // Did not find `topformflat`.
+pub enum TopformflatReducer<T: Topformflat> {
+    /// Did not find `topformflat`.
     NotFound,
-    // Did find `topformflat`.
+    /// Did find `topformflat`.
     Found {
+        /// The `Topformflat` implementation.
         t: PhantomData<T>,
+        /// The path to `topformflat`.
         topformflat: PathBuf,
+        /// Removes chunks of the flattened seed.
         chunks: RemoveRangesReducer<Chunks>,
     },
 }
@@ -2,15 +2,22 @@
 
 use super::{ReducerId, WorkerId};
 use error;
+use event::{self, Event};
 use histo::Histogram;
+use interesting;
+use serde_json;
+use status::Status;
 use std::any::Any;
 use std::collections::BTreeMap;
 use std::fmt;
 use std::io::{self, Write};
+use std::net;
 use std::path;
 use std::sync::mpsc;
 use std::thread;
+use std::time;
 use test_case::{self, TestCaseMethods};
+use LogFormat;
 
 /// The different kinds of log messages that can be sent to the logger actor.
 #[derive(Debug)]
@@ -27,15 +34,59 @@ enum LoggerMessage {
     ReducerErrored(ReducerId, error::Error),
     BackingUpTestCase(String, String),
     StartJudgingInteresting(WorkerId, test_case::Candidate),
-    JudgedInteresting(WorkerId, test_case::Interesting),
-    JudgedNotInteresting(WorkerId, test_case::Candidate),
+    JudgedInteresting(WorkerId, test_case::Interesting, Option<path::PathBuf>),
+    JudgedNotInteresting(WorkerId, test_case::Candidate, Option<path::PathBuf>),
+    JudgedTimedOut(WorkerId, test_case::Candidate, time::Duration, Option<path::PathBuf>),
+    JudgedLimitExceeded(
+        WorkerId,
+        test_case::Candidate,
+        interesting::ResourceLimit,
+        Option<path::PathBuf>,
+    ),
+    JudgedNearThreshold(WorkerId, test_case::Candidate, interesting::Votes),
+    CachedResult(WorkerId, test_case::Candidate, bool),
+    CacheErrored(error::Error),
+    CaptureErrored(error::Error),
+    NotCaching,
+    FlakyPredicate(usize, usize),
+    PreflightPassed(time::Duration),
     NewSmallest(test_case::Interesting, u64),
     IsNotSmaller(test_case::Interesting),
+    Merged(test_case::Candidate),
+    HistoryErrored(error::Error),
+    ResumingFromCheckpoint(String),
+    WroteCheckpoint(String),
+    CheckpointErrored(error::Error),
     StartGeneratingNextCandidate(ReducerId),
     FinishGeneratingNextCandidate(ReducerId, test_case::Candidate),
     NoMoreCandidates(ReducerId),
     FinalReducedSize(u64, u64),
+    QueueOccupancy(String, usize, time::Duration),
     GotSigint,
+    Progress(Progress),
+    Listening(net::SocketAddr),
+    RemoteWorkerConnected(WorkerId, net::SocketAddr),
+    RemoteWorkerLost(WorkerId, net::SocketAddr),
+    StartingPhase(String),
+}
+
+/// A periodic summary of the supervisor's state.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// The size of the current smallest interesting test case.
+    pub smallest_size: u64,
+    /// The size of the original test case.
+    pub orig_size: u64,
+    /// The number of candidates in the queue.
+    pub queued_candidates: usize,
+    /// The number of running workers.
+    pub workers: usize,
+    /// The number of running workers waiting for a candidate.
+    pub idle_workers: usize,
+    /// The number of running reducers.
+    pub reducers: usize,
+    /// The names of the exhausted reducers.
+    pub exhausted_reducers: Vec<String>,
 }
 
 impl fmt::Display for LoggerMessage {
@@ -66,19 +117,97 @@ impl fmt::Display for LoggerMessage {
                 candidate.path().display(),
                 candidate.size()
             ),
-            LoggerMessage::JudgedInteresting(id, ref interesting) => write!(
+            LoggerMessage::JudgedInteresting(id, ref interesting, ref output) => {
+                write!(
+                    f,
+                    "Worker {}: found an interesting test case {} of size {} bytes",
+                    id,
+                    interesting.path().display(),
+                    interesting.size()
+                )?;
+                write_output_file(f, output)
+            }
+            LoggerMessage::JudgedNotInteresting(id, ref candidate, ref output) => {
+                write!(
+                    f,
+                    "Worker {}: found test case {}, generated by {}, not interesting",
+                    id,
+                    candidate.path().display(),
+                    candidate.provenance()
+                )?;
+                write_output_file(f, output)
+            }
+            LoggerMessage::JudgedTimedOut(id, ref candidate, timeout, ref output) => {
+                write!(
+                    f,
+                    "Worker {}: test case {}, generated by {}, timed out after {}.{:03} seconds",
+                    id,
+                    candidate.path().display(),
+                    candidate.provenance(),
+                    timeout.as_secs(),
+                    timeout.subsec_nanos() / 1_000_000
+                )?;
+                write_output_file(f, output)
+            }
+            LoggerMessage::JudgedLimitExceeded(id, ref candidate, limit, ref output) => {
+                write!(
+                    f,
+                    "Worker {}: test case {}, generated by {}, exceeded the {} limit",
+                    id,
+                    candidate.path().display(),
+                    candidate.provenance(),
+                    limit
+                )?;
+                write_output_file(f, output)
+            }
+            LoggerMessage::JudgedNearThreshold(id, ref candidate, votes) => write!(
                 f,
-                "Worker {}: found an interesting test case {} of size {} bytes",
+                "Worker {}: test case {}, generated by {}, is near the threshold: {} of {} \
+                 runs found it interesting, and {} were needed",
                 id,
-                interesting.path().display(),
-                interesting.size()
+                candidate.path().display(),
+                candidate.provenance(),
+                votes.interesting,
+                votes.runs,
+                votes.needed
             ),
-            LoggerMessage::JudgedNotInteresting(id, ref candidate) => write!(
+            LoggerMessage::CachedResult(id, ref candidate, is_interesting) => write!(
                 f,
-                "Worker {}: found test case {}, generated by {}, not interesting",
+                "Worker {}: test case {}, generated by {}, is cached as {}",
                 id,
                 candidate.path().display(),
-                candidate.provenance()
+                candidate.provenance(),
+                if is_interesting {
+                    "interesting"
+                } else {
+                    "not interesting"
+                }
+            ),
+            LoggerMessage::CacheErrored(ref err) => {
+                write!(f, "Worker: error writing to the predicate result cache: {}", err)
+            }
+            LoggerMessage::CaptureErrored(ref err) => {
+                write!(f, "Worker: error writing the predicate's captured output: {}", err)
+            }
+            LoggerMessage::NotCaching => write!(
+                f,
+                "Supervisor: the is-interesting predicate has no fingerprint, so its results \
+                 will not be cached"
+            ),
+            LoggerMessage::PreflightPassed(runtime) => write!(
+                f,
+                "Supervisor: pre-flight checks passed; the is-interesting predicate took \
+                 {}.{:03} seconds on the original test case",
+                runtime.as_secs(),
+                runtime.subsec_nanos() / 1_000_000
+            ),
+            LoggerMessage::FlakyPredicate(interesting, runs) => write!(
+                f,
+                "Supervisor: warning: the is-interesting predicate found the original test \
+                 case interesting in only {} of {} runs ({:.2}% flaky)",
+                interesting,
+                runs,
+                ((runs - interesting) as f64) / (runs as f64) * 100.0
             ),
             LoggerMessage::NewSmallest(ref interesting, orig_size) => {
                 let new_size = interesting.size();
@@ -101,6 +230,28 @@ impl fmt::Display for LoggerMessage {
                 candidate.path().display(),
                 candidate.provenance()
             ),
+            LoggerMessage::Merged(ref candidate) => write!(
+                f,
+                "Supervisor: merged concurrently found interesting test cases into candidate {} \
+                 of size {}",
+                candidate.path().display(),
+                candidate.size()
+            ),
+            LoggerMessage::HistoryErrored(ref err) => write!(
+                f,
+                "Supervisor: error maintaining interesting test case history, \
+                 disabling merging: {}",
+                err
+            ),
+            LoggerMessage::ResumingFromCheckpoint(ref dir) => {
+                write!(f, "Supervisor: resuming from checkpoint {}", dir)
+            }
+            LoggerMessage::WroteCheckpoint(ref dir) => {
+                write!(f, "Supervisor: wrote checkpoint {}", dir)
+            }
+            LoggerMessage::CheckpointErrored(ref err) => {
+                write!(f, "Supervisor: error writing checkpoint: {}", err)
+            }
             LoggerMessage::StartGeneratingNextCandidate(id) => {
                 write!(f, "Reducer {}: generating next candidate...", id)
             }
@@ -126,11 +277,236 @@ impl fmt::Display for LoggerMessage {
                     percent
                 )
             }
+            LoggerMessage::QueueOccupancy(ref reducer, peak_queued, queued_time) => write!(
+                f,
+                "Supervisor: reducer {}'s candidates peaked at {} queued, and waited {}.{:03} \
+                 seconds in total",
+                reducer,
+                peak_queued,
+                queued_time.as_secs(),
+                queued_time.subsec_nanos() / 1_000_000
+            ),
             LoggerMessage::GotSigint => write!(f, "SIGINT actor: received SIGINT"),
+            LoggerMessage::Listening(addr) => {
+                write!(f, "Supervisor: listening for remote workers on {}", addr)
+            }
+            LoggerMessage::RemoteWorkerConnected(id, addr) => write!(
+                f,
+                "Supervisor: remote worker {} connected from {}",
+                id,
+                addr
+            ),
+            LoggerMessage::RemoteWorkerLost(id, addr) => write!(
+                f,
+                "Supervisor: lost remote worker {} at {}; waiting for it to reconnect",
+                id,
+                addr
+            ),
+            LoggerMessage::StartingPhase(ref name) => {
+                write!(f, "Supervisor: starting phase '{}'", name)
+            }
+            LoggerMessage::Progress(ref progress) => write!(
+                f,
+                "Supervisor: smallest is {} bytes, {} candidates queued, {} of {} workers \
+                 idle, {} of {} reducers exhausted",
+                progress.smallest_size,
+                progress.queued_candidates,
+                progress.idle_workers,
+                progress.workers,
+                progress.exhausted_reducers.len(),
+                progress.reducers
+            ),
         }
     }
 }
 
+impl LoggerMessage {
+    /// Get the public event corresponding to this log message.
+    fn to_event(&self) -> Event {
+        match *self {
+            LoggerMessage::SpawningWorker(id) => Event::SpawningWorker { worker: id.into() },
+            LoggerMessage::SpawnedWorker(id) => Event::SpawnedWorker { worker: id.into() },
+            LoggerMessage::SpawningReducer(id) => Event::SpawningReducer { reducer: id.into() },
+            LoggerMessage::SpawnedReducer(id) => Event::SpawnedReducer { reducer: id.into() },
+            LoggerMessage::ShutdownWorker(id) => Event::ShutdownWorker { worker: id.into() },
+            LoggerMessage::ShutdownReducer(id) => Event::ShutdownReducer { reducer: id.into() },
+            LoggerMessage::WorkerPanicked(id, ref panic) => Event::WorkerPanicked {
+                worker: id.into(),
+                message: panic_message(panic),
+            },
+            LoggerMessage::WorkerErrored(id, ref err) => Event::WorkerErrored {
+                worker: id.into(),
+                error: err.to_string(),
+            },
+            LoggerMessage::ReducerPanicked(id, ref panic) => Event::ReducerPanicked {
+                reducer: id.into(),
+                message: panic_message(panic),
+            },
+            LoggerMessage::ReducerErrored(id, ref err) => Event::ReducerErrored {
+                reducer: id.into(),
+                error: err.to_string(),
+            },
+            LoggerMessage::BackingUpTestCase(ref from, ref to) => Event::BackingUpTestCase {
+                from: from.into(),
+                to: to.into(),
+            },
+            LoggerMessage::StartJudgingInteresting(id, ref candidate) => {
+                Event::StartJudgingInteresting {
+                    worker: id.into(),
+                    candidate: test_case_info(candidate),
+                }
+            }
+            LoggerMessage::JudgedInteresting(id, ref interesting, ref output) => {
+                Event::JudgedInteresting {
+                    worker: id.into(),
+                    interesting: test_case_info(interesting),
+                    output: output.clone(),
+                }
+            }
+            LoggerMessage::JudgedNotInteresting(id, ref candidate, ref output) => {
+                Event::JudgedNotInteresting {
+                    worker: id.into(),
+                    candidate: test_case_info(candidate),
+                    output: output.clone(),
+                }
+            }
+            LoggerMessage::JudgedTimedOut(id, ref candidate, timeout, ref output) => {
+                Event::JudgedTimedOut {
+                    worker: id.into(),
+                    candidate: test_case_info(candidate),
+                    timeout: timeout,
+                    output: output.clone(),
+                }
+            }
+            LoggerMessage::JudgedLimitExceeded(id, ref candidate, limit, ref output) => {
+                Event::JudgedLimitExceeded {
+                    worker: id.into(),
+                    candidate: test_case_info(candidate),
+                    limit: limit,
+                    output: output.clone(),
+                }
+            }
+            LoggerMessage::JudgedNearThreshold(id, ref candidate, votes) => {
+                Event::JudgedNearThreshold {
+                    worker: id.into(),
+                    candidate: test_case_info(candidate),
+                    votes: votes,
+                }
+            }
+            LoggerMessage::CachedResult(id, ref candidate, is_interesting) => {
+                Event::CachedResult {
+                    worker: id.into(),
+                    candidate: test_case_info(candidate),
+                    is_interesting: is_interesting,
+                }
+            }
+            LoggerMessage::CacheErrored(ref err) => Event::CacheErrored {
+                error: err.to_string(),
+            },
+            LoggerMessage::CaptureErrored(ref err) => Event::CaptureErrored {
+                error: err.to_string(),
+            },
+            LoggerMessage::NotCaching => Event::NotCaching,
+            LoggerMessage::PreflightPassed(runtime) => Event::PreflightPassed { runtime: runtime },
+            LoggerMessage::FlakyPredicate(interesting, runs) => Event::FlakyPredicate {
+                interesting: interesting,
+                runs: runs,
+            },
+            LoggerMessage::NewSmallest(ref interesting, orig_size) => Event::NewSmallest {
+                interesting: test_case_info(interesting),
+                original_size: orig_size,
+            },
+            LoggerMessage::IsNotSmaller(ref interesting) => Event::IsNotSmaller {
+                interesting: test_case_info(interesting),
+            },
+            LoggerMessage::Merged(ref candidate) => Event::Merged {
+                candidate: test_case_info(candidate),
+            },
+            LoggerMessage::HistoryErrored(ref err) => Event::HistoryErrored {
+                error: err.to_string(),
+            },
+            LoggerMessage::ResumingFromCheckpoint(ref dir) => {
+                Event::ResumingFromCheckpoint { dir: dir.into() }
+            }
+            LoggerMessage::WroteCheckpoint(ref dir) => Event::WroteCheckpoint { dir: dir.into() },
+            LoggerMessage::CheckpointErrored(ref err) => Event::CheckpointErrored {
+                error: err.to_string(),
+            },
+            LoggerMessage::StartGeneratingNextCandidate(id) => {
+                Event::StartGeneratingNextCandidate { reducer: id.into() }
+            }
+            LoggerMessage::FinishGeneratingNextCandidate(id, ref candidate) => {
+                Event::FinishGeneratingNextCandidate {
+                    reducer: id.into(),
+                    candidate: test_case_info(candidate),
+                }
+            }
+            LoggerMessage::NoMoreCandidates(id) => Event::NoMoreCandidates { reducer: id.into() },
+            LoggerMessage::FinalReducedSize(final_size, orig_size) => Event::FinalReducedSize {
+                final_size: final_size,
+                original_size: orig_size,
+            },
+            LoggerMessage::QueueOccupancy(ref reducer, peak_queued, queued_time) => {
+                Event::QueueOccupancy {
+                    reducer: reducer.clone(),
+                    peak_queued: peak_queued,
+                    queued_time: queued_time,
+                }
+            }
+            LoggerMessage::GotSigint => Event::GotSigint,
+            LoggerMessage::Listening(addr) => Event::Listening {
+                addr: addr.to_string(),
+            },
+            LoggerMessage::RemoteWorkerConnected(id, addr) => Event::RemoteWorkerConnected {
+                worker: id.into(),
+                addr: addr.to_string(),
+            },
+            LoggerMessage::RemoteWorkerLost(id, addr) => Event::RemoteWorkerLost {
+                worker: id.into(),
+                addr: addr.to_string(),
+            },
+            LoggerMessage::StartingPhase(ref name) => Event::StartingPhase { name: name.clone() },
+            LoggerMessage::Progress(ref progress) => Event::Progress {
+                smallest_size: progress.smallest_size,
+                original_size: progress.orig_size,
+                queued_candidates: progress.queued_candidates,
+                workers: progress.workers,
+                idle_workers: progress.idle_workers,
+                reducers: progress.reducers,
+                exhausted_reducers: progress.exhausted_reducers.clone(),
+            },
+        }
+    }
+}
+
+/// Point a judgement's log message at the predicate's captured output, if any.
+fn write_output_file(f: &mut fmt::Formatter, output: &Option<path::PathBuf>) -> fmt::Result {
+    match *output {
+        Some(ref output) => write!(f, " (output in {})", output.display()),
+        None => Ok(()),
+    }
+}
+
+fn test_case_info<T>(test_case: &T) -> event::TestCaseInfo
+where
+    T: TestCaseMethods,
+{
+    event::TestCaseInfo {
+        path: test_case.path().to_path_buf(),
+        size: test_case.size(),
+        delta: test_case.delta(),
+        provenance: test_case.provenance().to_string(),
+    }
+}
+
+fn panic_message(panic: &Box<Any + Send + 'static>) -> Option<String> {
+    if let Some(s) = panic.downcast_ref::<&'static str>() {
+        Some(s.to_string())
+    } else {
+        panic.downcast_ref::<String>().cloned()
+    }
+}
+
 /// A client to the logger actor.
 #[derive(Clone, Debug)]
 pub struct Logger {
@@ -139,9 +515,14 @@ pub struct Logger {
 
 /// Logger client implementation.
 impl Logger {
-    /// Spawn a `Logger` actor, writing logs to the given `Write`able.
+    /// Spawn a `Logger` actor, writing logs to the given `Write`able, and
+    /// notifying the given subscribers of each log message's event. If
+    /// `should_show_status` is true, a live status view is drawn on stdout.
     pub fn spawn<W>(
         to: W,
+        format: LogFormat,
+        subscribers: Vec<event::Subscriber>,
+        should_show_status: bool,
         should_print_histograms: bool,
     ) -> error::Result<(Logger, thread::JoinHandle<()>)>
     where
@@ -150,7 +531,14 @@ impl Logger {
         let (sender, receiver) = mpsc::channel();
         let handle = thread::Builder::new()
             .name("preduce-logger".into())
-            .spawn(move || Logger::run(to, receiver, should_print_histograms))?;
+            .spawn(move || {
+                let status = if should_show_status {
+                    Some(Status::new())
+                } else {
+                    None
+                };
+                Logger::run(to, format, subscribers, status, receiver, should_print_histograms)
+            })?;
         Ok((Logger { sender: sender }, handle))
     }
 
@@ -206,16 +594,102 @@ impl Logger {
 
     /// Log that the worker with the given id has discovered a new interesting
     /// test case.
-    pub fn judged_interesting(&self, id: WorkerId, interesting: test_case::Interesting) {
+    pub fn judged_interesting(
+        &self,
+        id: WorkerId,
+        interesting: test_case::Interesting,
+        output: Option<path::PathBuf>,
+    ) {
         let _ = self.sender
-            .send(LoggerMessage::JudgedInteresting(id, interesting));
+            .send(LoggerMessage::JudgedInteresting(id, interesting, output));
     }
 
     /// Log that the worker with the given id has discovered that its test case
     /// is not interesting.
-    pub fn judged_not_interesting(&self, id: WorkerId, candidate: test_case::Candidate) {
+    pub fn judged_not_interesting(
+        &self,
+        id: WorkerId,
+        candidate: test_case::Candidate,
+        output: Option<path::PathBuf>,
+    ) {
+        let _ = self.sender
+            .send(LoggerMessage::JudgedNotInteresting(id, candidate, output));
+    }
+
+    /// Log that the worker with the given id killed the is-interesting
+    /// predicate for its test case after it ran past the given timeout.
+    pub fn judged_timed_out(
+        &self,
+        id: WorkerId,
+        candidate: test_case::Candidate,
+        timeout: time::Duration,
+        output: Option<path::PathBuf>,
+    ) {
+        let _ = self.sender
+            .send(LoggerMessage::JudgedTimedOut(id, candidate, timeout, output));
+    }
+
+    /// Log that the is-interesting predicate for the given worker's test case
+    /// was killed for exceeding the given resource limit.
+    pub fn judged_limit_exceeded(
+        &self,
+        id: WorkerId,
+        candidate: test_case::Candidate,
+        limit: interesting::ResourceLimit,
+        output: Option<path::PathBuf>,
+    ) {
+        let _ = self.sender
+            .send(LoggerMessage::JudgedLimitExceeded(id, candidate, limit, output));
+    }
+
+    /// Log that the runs of the is-interesting predicate for the given worker's
+    /// test case disagreed, and were only just enough, or only just not
+    /// enough, to decide its interesting-ness.
+    pub fn judged_near_threshold(
+        &self,
+        id: WorkerId,
+        candidate: test_case::Candidate,
+        votes: interesting::Votes,
+    ) {
+        let _ = self.sender
+            .send(LoggerMessage::JudgedNearThreshold(id, candidate, votes));
+    }
+
+    /// Log that the worker with the given id found its test case's
+    /// interesting-ness in the predicate result cache.
+    pub fn cached_result(&self, id: WorkerId, candidate: test_case::Candidate, is_interesting: bool) {
         let _ = self.sender
-            .send(LoggerMessage::JudgedNotInteresting(id, candidate));
+            .send(LoggerMessage::CachedResult(id, candidate, is_interesting));
+    }
+
+    /// Log that a worker failed to write a result to the predicate result
+    /// cache.
+    pub fn cache_errored(&self, err: error::Error) {
+        let _ = self.sender.send(LoggerMessage::CacheErrored(err));
+    }
+
+    /// Log that a worker failed to write the predicate's captured output.
+    pub fn capture_errored(&self, err: error::Error) {
+        let _ = self.sender.send(LoggerMessage::CaptureErrored(err));
+    }
+
+    /// Log that a cache directory was given, but the predicate's results can't
+    /// be cached.
+    pub fn not_caching(&self) {
+        let _ = self.sender.send(LoggerMessage::NotCaching);
+    }
+
+    /// Log that the pre-flight checks of the is-interesting predicate passed,
+    /// and that it ran for `runtime` on the original test case.
+    pub fn preflight_passed(&self, runtime: time::Duration) {
+        let _ = self.sender.send(LoggerMessage::PreflightPassed(runtime));
+    }
+
+    /// Log that the is-interesting predicate found the original test case
+    /// interesting in only `interesting` of `runs` runs.
+    pub fn flaky_predicate(&self, interesting: usize, runs: usize) {
+        let _ = self.sender
+            .send(LoggerMessage::FlakyPredicate(interesting, runs));
     }
 
     /// Log that the supervisor has a new globally smallest interesting test
@@ -235,6 +709,43 @@ impl Logger {
         let _ = self.sender.send(LoggerMessage::IsNotSmaller(candidate));
     }
 
+    /// Log that the supervisor merged two interesting test cases that were
+    /// found concurrently into a new candidate.
+    pub fn merged(&self, candidate: test_case::Candidate) {
+        let _ = self.sender.send(LoggerMessage::Merged(candidate));
+    }
+
+    /// Log that the supervisor failed to maintain its history of interesting
+    /// test cases, and will no longer merge them.
+    pub fn history_errored(&self, err: error::Error) {
+        let _ = self.sender.send(LoggerMessage::HistoryErrored(err));
+    }
+
+    /// Log that we are resuming a reduction from the checkpoint in the given
+    /// directory.
+    pub fn resuming_from_checkpoint<P>(&self, dir: P)
+    where
+        P: AsRef<path::Path>,
+    {
+        let dir = dir.as_ref().display().to_string();
+        let _ = self.sender
+            .send(LoggerMessage::ResumingFromCheckpoint(dir));
+    }
+
+    /// Log that the supervisor wrote a checkpoint into the given directory.
+    pub fn wrote_checkpoint<P>(&self, dir: P)
+    where
+        P: AsRef<path::Path>,
+    {
+        let dir = dir.as_ref().display().to_string();
+        let _ = self.sender.send(LoggerMessage::WroteCheckpoint(dir));
+    }
+
+    /// Log that the supervisor failed to write a checkpoint.
+    pub fn checkpoint_errored(&self, err: error::Error) {
+        let _ = self.sender.send(LoggerMessage::CheckpointErrored(err));
+    }
+
     /// Log that this reducer actor has started generating its next candidate.
     pub fn start_generating_next_candidate(&self, id: ReducerId) {
         let _ = self.sender
@@ -261,6 +772,18 @@ impl Logger {
             .send(LoggerMessage::FinalReducedSize(final_size, orig_size));
     }
 
+    /// Log the most candidates from the given reducer that were ever queued at
+    /// once, and how long its candidates waited in the queue in total.
+    pub fn queue_occupancy(
+        &self,
+        reducer: String,
+        peak_queued: usize,
+        queued_time: time::Duration,
+    ) {
+        let _ = self.sender
+            .send(LoggerMessage::QueueOccupancy(reducer, peak_queued, queued_time));
+    }
+
     /// Log that the reducer with the given id is spawning.
     pub fn spawning_reducer(&self, id: ReducerId) {
         let _ = self.sender.send(LoggerMessage::SpawningReducer(id));
@@ -285,6 +808,35 @@ impl Logger {
     pub fn got_sigint(&self) {
         let _ = self.sender.send(LoggerMessage::GotSigint);
     }
+
+    /// Log that the supervisor is listening for remote workers on the given
+    /// address.
+    pub fn listening(&self, addr: net::SocketAddr) {
+        let _ = self.sender.send(LoggerMessage::Listening(addr));
+    }
+
+    /// Log that a remote worker connected from the given address, and will be
+    /// driven by the worker actor with the given id.
+    pub fn remote_worker_connected(&self, id: WorkerId, addr: net::SocketAddr) {
+        let _ = self.sender
+            .send(LoggerMessage::RemoteWorkerConnected(id, addr));
+    }
+
+    /// Log that the remote worker driven by the worker with the given id, which
+    /// connected from the given address, was lost.
+    pub fn remote_worker_lost(&self, id: WorkerId, addr: net::SocketAddr) {
+        let _ = self.sender.send(LoggerMessage::RemoteWorkerLost(id, addr));
+    }
+
+    /// Log that the reduction phase with the given name is starting.
+    pub fn starting_phase(&self, name: String) {
+        let _ = self.sender.send(LoggerMessage::StartingPhase(name));
+    }
+
+    /// Log a periodic summary of the supervisor's state.
+    pub fn progress(&self, progress: Progress) {
+        let _ = self.sender.send(LoggerMessage::Progress(progress));
+    }
 }
 
 const BUCKETS: u64 = 20;
@@ -295,8 +847,14 @@ fn sum(h: &Histogram) -> u64 {
 
 /// Logger actor implementation.
 impl Logger {
-    fn run<W>(to: W, incoming: mpsc::Receiver<LoggerMessage>, should_print_histograms: bool)
-    where
+    fn run<W>(
+        to: W,
+        format: LogFormat,
+        subscribers: Vec<event::Subscriber>,
+        mut status: Option<Status>,
+        incoming: mpsc::Receiver<LoggerMessage>,
+        should_print_histograms: bool,
+    ) where
         W: Write,
     {
         let mut to = io::BufWriter::new(to);
@@ -308,6 +866,18 @@ impl Logger {
         let mut by_provenance: BTreeMap<String, (Histogram, Histogram, Histogram)> =
             BTreeMap::new();
 
+        // Candidate provenance -> number of candidates whose is-interesting
+        // predicate timed out.
+        let mut timeouts_by_provenance: BTreeMap<String, u64> = BTreeMap::new();
+
+        // (Candidate provenance, resource limit) -> number of candidates whose
+        // is-interesting predicate exceeded that limit.
+        let mut limits_by_provenance: BTreeMap<(String, String), u64> = BTreeMap::new();
+
+        // Keep track of how many candidates from each reducer were queued at
+        // most, and how long they waited in total.
+        let mut queue_by_provenance: BTreeMap<String, (usize, time::Duration)> = BTreeMap::new();
+
         // Histograms of various kinds of candidates' sizes.
         let mut all_candidates = Histogram::with_buckets(BUCKETS);
         let mut smallest_candidates = Histogram::with_buckets(BUCKETS);
@@ -321,14 +891,51 @@ impl Logger {
         let mut any_interesting_rate = Histogram::with_buckets(2);
 
         for log_msg in incoming {
-            writeln!(&mut to, "{}", log_msg).expect("Should write to log file");
+            match format {
+                LogFormat::Text => {
+                    writeln!(&mut to, "{}", log_msg).expect("Should write to log file");
+                }
+                LogFormat::JsonLines => {
+                    let mut json = serde_json::to_value(log_msg.to_event())
+                        .expect("Should serialize events");
+                    let timestamp = time::SystemTime::now()
+                        .duration_since(time::UNIX_EPOCH)
+                        .map(|t| t.as_secs() as f64 + t.subsec_nanos() as f64 / 1e9)
+                        .unwrap_or(0.0);
+                    json.as_object_mut()
+                        .expect("Events serialize as objects")
+                        .insert("timestamp".into(), timestamp.into());
+                    writeln!(&mut to, "{}", json).expect("Should write to log file");
+                }
+            }
             to.flush().expect("Should flush log file");
 
+            if !subscribers.is_empty() || status.is_some() {
+                let event = log_msg.to_event();
+                for subscriber in &subscribers {
+                    subscriber.notify(&event);
+                }
+                if let Some(ref mut status) = status {
+                    status.observe(&event);
+                }
+            }
+
             match log_msg {
                 msg @ LoggerMessage::ReducerErrored(..) |
                 msg @ LoggerMessage::WorkerErrored(..) |
+                msg @ LoggerMessage::HistoryErrored(..) |
+                msg @ LoggerMessage::CheckpointErrored(..) |
+                msg @ LoggerMessage::CacheErrored(..) |
+                msg @ LoggerMessage::CaptureErrored(..) |
+                msg @ LoggerMessage::NotCaching |
+                msg @ LoggerMessage::FlakyPredicate(..) |
+                msg @ LoggerMessage::PreflightPassed(..) |
+                msg @ LoggerMessage::Listening(..) |
                 msg @ LoggerMessage::ReducerPanicked(..) |
                 msg @ LoggerMessage::WorkerPanicked(..) => {
+                    if let Some(ref mut status) = status {
+                        status.clear();
+                    }
                     println!("{}", msg);
                 }
 
@@ -337,15 +944,18 @@ impl Logger {
 
                     smallest_size = Some(new_size);
 
-                    println!(
-                        "({:.2}%, {} bytes)",
-                        if orig_size == 0 {
-                            100.0
-                        } else {
-                            ((orig_size - new_size) as f64) / (orig_size as f64) * 100.0
-                        },
-                        new_size
-                    );
+                    // The status view shows the new size itself.
+                    if status.is_none() {
+                        println!(
+                            "({:.2}%, {} bytes)",
+                            if orig_size == 0 {
+                                100.0
+                            } else {
+                                ((orig_size - new_size) as f64) / (orig_size as f64) * 100.0
+                            },
+                            new_size
+                        );
+                    }
 
                     let provenance = interesting.provenance().to_string();
                     by_provenance
@@ -392,7 +1002,7 @@ impl Logger {
                     any_interesting_rate.add(1);
                 }
 
-                LoggerMessage::JudgedNotInteresting(_, candidate) => {
+                LoggerMessage::JudgedNotInteresting(_, candidate, _) => {
                     let provenance = candidate.provenance().to_string();
                     by_provenance
                         .entry(provenance)
@@ -414,10 +1024,28 @@ impl Logger {
                     any_interesting_rate.add(0);
                 }
 
+                LoggerMessage::JudgedTimedOut(_, candidate, ..) => {
+                    let provenance = candidate.provenance().to_string();
+                    *timeouts_by_provenance.entry(provenance).or_insert(0) += 1;
+                }
+
+                LoggerMessage::JudgedLimitExceeded(_, candidate, limit, _) => {
+                    let key = (candidate.provenance().to_string(), limit.to_string());
+                    *limits_by_provenance.entry(key).or_insert(0) += 1;
+                }
+
+                LoggerMessage::QueueOccupancy(reducer, peak_queued, queued_time) => {
+                    queue_by_provenance.insert(reducer, (peak_queued, queued_time));
+                }
+
                 _ => {}
             }
         }
 
+        if let Some(ref mut status) = status {
+            status.draw();
+        }
+
         if let Some(smallest_size) = smallest_size {
             println!("Final size is {}", smallest_size);
         } else {
@@ -425,6 +1053,32 @@ impl Logger {
         }
         println!();
 
+        if !timeouts_by_provenance.is_empty() {
+            println!("Is-interesting predicate timeouts:");
+            for (reducer, count) in &timeouts_by_provenance {
+                println!("    {:<50} {:>10}", reducer, count);
+            }
+            println!();
+        }
+
+        if !limits_by_provenance.is_empty() {
+            println!("Is-interesting predicate resource limits exceeded:");
+            for (&(ref reducer, ref limit), count) in &limits_by_provenance {
+                println!("    {:<35} {:<14} {:>10}", reducer, limit, count);
+            }
+            println!();
+        }
+
+        if !queue_by_provenance.is_empty() {
+            println!("Candidates queued (peak, and total seconds waited):");
+            for (reducer, &(peak_queued, queued_time)) in &queue_by_provenance {
+                let secs =
+                    queued_time.as_secs() as f64 + queued_time.subsec_nanos() as f64 / 1e9;
+                println!("    {:<35} {:>10} {:>14.3}", reducer, peak_queued, secs);
+            }
+            println!();
+        }
+
         if !should_print_histograms {
             return;
         }
@@ -4,6 +4,7 @@
 
 use super::{Logger, Supervisor};
 use error;
+use serde_json;
 use signposts;
 use std::any::Any;
 use std::collections::HashMap;
@@ -24,6 +25,12 @@ impl ReducerId {
     }
 }
 
+impl From<ReducerId> for usize {
+    fn from(id: ReducerId) -> usize {
+        id.0
+    }
+}
+
 impl fmt::Display for ReducerId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -37,6 +44,8 @@ enum ReducerMessage {
     RequestNextCandidate(Option<test_case::Interesting>),
     NotInteresting(test_case::Candidate),
     SetNewSeed(test_case::Interesting),
+    RestoreSeed(test_case::Interesting, serde_json::Value),
+    Snapshot,
 }
 
 /// A client handle to a reducer actor.
@@ -102,6 +111,19 @@ impl Reducer {
     pub fn set_new_seed(&self, new_seed: test_case::Interesting) {
         let _ = self.sender.send(ReducerMessage::SetNewSeed(new_seed));
     }
+
+    /// Reseed this reducer actor with the given test case, and restore the
+    /// state it had for that seed from a checkpoint.
+    pub fn restore_seed(&self, seed: test_case::Interesting, snapshot: serde_json::Value) {
+        let _ = self.sender
+            .send(ReducerMessage::RestoreSeed(seed, snapshot));
+    }
+
+    /// Ask this reducer actor to send the supervisor a snapshot of its current
+    /// state, for checkpointing.
+    pub fn request_snapshot(&self) {
+        let _ = self.sender.send(ReducerMessage::Snapshot);
+    }
 }
 
 // Reducer actor implementation.
@@ -190,6 +212,22 @@ impl ReducerActor {
 
                     current_seed = Some(new_seed);
                 }
+                ReducerMessage::RestoreSeed(seed, snapshot) => {
+                    current_state = self.reducer.restore_state(&seed, snapshot)?;
+                    if current_state.is_none() {
+                        current_state = Some(self.reducer.new_state(&seed)?);
+                    }
+                    current_seed = Some(seed);
+                }
+                ReducerMessage::Snapshot => {
+                    if let Some(ref seed) = current_seed {
+                        let snapshot = current_state
+                            .as_ref()
+                            .and_then(|state| self.reducer.snapshot_state(state));
+                        self.supervisor
+                            .reply_snapshot(self.me.clone(), seed.clone(), snapshot);
+                    }
+                }
                 ReducerMessage::NotInteresting(candidate) => {
                     active_states.remove(&candidate).expect(
                         "Reducer actors should only be informed of their own \
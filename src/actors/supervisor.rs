@@ -1,23 +1,34 @@
 //! The supervisor actor manages workers, and brokers their access to new
 //! candidates.
 
-use super::{Logger, Reducer, ReducerId, Sigint, Worker, WorkerId};
-use super::super::Options;
+use super::{Logger, Progress, Reducer, ReducerId, Sigint, Worker, WorkerId, WorkerOrigin};
+use super::super::{Options, Phase};
+use cache::Cache;
+use capture;
+use checkpoint::Checkpoint;
 use error;
-use oracle;
+use history;
+use preflight;
 use queue::CandidateQueue;
+use remote;
+use report;
+use sandbox;
 use score::Score;
+use serde_json;
 use signposts;
 use std::any::Any;
 use std::cmp;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::{self, Read};
+use std::mem;
+use std::net;
 use std::path;
 use std::sync::mpsc;
 use std::thread;
+use std::time;
 use test_case::{self, TestCaseMethods};
-use traits::{self, Oracle};
+use traits;
 
 /// The messages that can be sent to the supervisor actor.
 #[derive(Debug)]
@@ -25,7 +36,7 @@ enum SupervisorMessage {
     // From workers.
     WorkerPanicked(WorkerId, Box<Any + Send + 'static>),
     WorkerErrored(WorkerId, error::Error),
-    RequestNextCandidate(Worker, Option<test_case::Candidate>),
+    RequestNextCandidate(Worker, Option<NotInteresting>),
     ReportInteresting(Worker, test_case::Interesting),
 
     // From reducers.
@@ -33,9 +44,32 @@ enum SupervisorMessage {
     ReducerErrored(ReducerId, error::Error),
     ReplyNextCandidate(Reducer, test_case::Candidate),
     ReplyExhausted(Reducer, test_case::Interesting),
+    ReplySnapshot(Reducer, test_case::Interesting, Option<serde_json::Value>),
 
     // From the SIGINT actor.
     GotSigint,
+
+    // From the remote worker listener.
+    RemoteWorkerConnected(net::TcpStream),
+}
+
+/// A candidate that a worker found not interesting.
+#[derive(Debug)]
+pub enum NotInteresting {
+    /// The predicate judged the candidate not interesting.
+    Judged(test_case::Candidate),
+
+    /// The predicate timed out while judging the candidate.
+    TimedOut(test_case::Candidate),
+}
+
+impl NotInteresting {
+    /// Get the candidate that was not interesting.
+    pub fn candidate(&self) -> &test_case::Candidate {
+        match *self {
+            NotInteresting::Judged(ref c) | NotInteresting::TimedOut(ref c) => c,
+        }
+    }
 }
 
 /// A client handle to the supervisor actor.
@@ -50,7 +84,7 @@ impl Supervisor {
     /// the test case candidate process.
     pub fn spawn<I>(
         opts: Options<I>,
-    ) -> error::Result<(Supervisor, thread::JoinHandle<error::Result<()>>)>
+    ) -> error::Result<(Supervisor, thread::JoinHandle<error::Result<report::ReductionReport>>)>
     where
         I: 'static + traits::IsInteresting,
     {
@@ -76,7 +110,7 @@ impl Supervisor {
     pub fn request_next_candidate(
         &self,
         who: Worker,
-        not_interesting: Option<test_case::Candidate>,
+        not_interesting: Option<NotInteresting>,
     ) {
         self.sender
             .send(SupervisorMessage::RequestNextCandidate(
@@ -140,15 +174,40 @@ impl Supervisor {
             .unwrap();
     }
 
+    /// Give the supervisor a snapshot of the reducer's state for the given
+    /// seed, or `None` if its state can't be snapshotted.
+    pub fn reply_snapshot(
+        &self,
+        reducer: Reducer,
+        seed: test_case::Interesting,
+        snapshot: Option<serde_json::Value>,
+    ) {
+        self.sender
+            .send(SupervisorMessage::ReplySnapshot(reducer, seed, snapshot))
+            .unwrap();
+    }
+
     // Messages sent to the supervisor from the SIGINT actor.
 
     pub fn got_sigint(&self) {
         self.sender.send(SupervisorMessage::GotSigint).unwrap();
     }
+
+    // Messages sent to the supervisor from the remote worker listener.
+
+    pub fn remote_worker_connected(&self, stream: net::TcpStream) {
+        // The supervisor may have already shut down, in which case the
+        // connection is simply dropped.
+        let _ = self.sender
+            .send(SupervisorMessage::RemoteWorkerConnected(stream));
+    }
 }
 
 // Supervisor actor implementation.
 
+/// How often to log a summary of the supervisor's state.
+const PROGRESS_INTERVAL_MILLIS: u64 = 1000;
+
 struct SupervisorActor<I>
 where
     I: 'static + traits::IsInteresting,
@@ -173,13 +232,60 @@ where
     exhausted_reducers: HashSet<ReducerId>,
     candidate_queue: CandidateQueue,
 
-    oracle: oracle::Join5<
-        oracle::InterestingRate,
-        oracle::CreducePassPriorities,
-        oracle::PercentReduced,
-        oracle::HaveWeSeenIt,
-        oracle::HaveWeTriedIt,
-    >,
+    /// The index of the reduction phase that is currently running.
+    phase: usize,
+
+    /// The history of interesting test cases, used to merge interesting test
+    /// cases that were found concurrently. This is `None` if the history could
+    /// not be created or maintained (for example, `git` is not installed).
+    history: Option<history::History>,
+
+    /// The on-disk cache of is-interesting predicate results that workers
+    /// consult before running the predicate, if enabled.
+    cache: Option<Cache>,
+
+    /// Where workers write the predicate's captured output, if enabled.
+    captures: Option<capture::Captures>,
+
+    /// Accepts connections from remote workers, if enabled.
+    listener: Option<remote::Listener>,
+
+    /// Creates each local worker's predicate sandbox, if enabled.
+    sandboxes: Option<sandbox::Sandboxes>,
+
+    /// When we last wrote a checkpoint.
+    last_checkpoint: time::Instant,
+
+    /// When the reduction started.
+    start: time::Instant,
+
+    /// When we last logged a summary of our state.
+    last_progress: time::Instant,
+
+    /// Whether the status view or any event subscribers want summaries of our
+    /// state. Nobody else does, so they aren't logged otherwise.
+    wants_progress: bool,
+
+    /// Counters for each reducer's candidates, keyed by provenance.
+    stats: BTreeMap<String, report::ReducerStats>,
+
+    /// Whether we have asked the reducers for snapshots since the last
+    /// checkpoint.
+    requested_snapshots: bool,
+
+    /// The most recent snapshot of each reducer's state for the current
+    /// smallest interesting test case, keyed by reducer name.
+    reducer_snapshots: BTreeMap<String, serde_json::Value>,
+
+    /// Reducer states restored from a checkpoint, keyed by reducer name, that
+    /// have yet to be handed back to their reducers.
+    restored_reducer_states: BTreeMap<String, serde_json::Value>,
+
+    /// When each busy worker was sent the candidate it is judging, and that
+    /// candidate's provenance.
+    dispatched: HashMap<WorkerId, (time::Instant, String)>,
+
+    oracle: Box<traits::Oracle>,
 }
 
 impl<I> SupervisorActor<I>
@@ -190,15 +296,78 @@ where
         mut opts: Options<I>,
         me: Supervisor,
         incoming: mpsc::Receiver<SupervisorMessage>,
-    ) -> error::Result<()> {
+    ) -> error::Result<report::ReductionReport> {
         let num_workers = opts.num_workers();
-        let num_reducers = opts.reducers().len();
-        let reducers_without_actors = opts.take_reducers();
-
-        let (logger, logger_handle) =
-            Logger::spawn(fs::File::create("preduce.log")?, opts.print_histograms)?;
+        let num_reducers = opts
+            .reduction_phases()
+            .iter()
+            .map(|p| p.reducers().len())
+            .max()
+            .unwrap_or(0);
+        let phases = opts.take_phases();
+
+        let subscribers = opts.take_subscribers();
+        let wants_progress = opts.status || !subscribers.is_empty();
+        let (logger, logger_handle) = Logger::spawn(
+            fs::File::create(&opts.log_file)?,
+            opts.log_format,
+            subscribers,
+            opts.status,
+            opts.print_histograms,
+        )?;
         let (sigint, sigint_handle) = Sigint::spawn(me.clone(), logger.clone())?;
 
+        let history = match history::History::new() {
+            Ok(history) => Some(history),
+            Err(e) => {
+                logger.history_errored(e);
+                None
+            }
+        };
+
+        let sandboxes = match opts.sandbox {
+            None => None,
+            Some(ref sandbox) => Some(sandbox::Sandboxes::new(sandbox, &opts.test_case)?),
+        };
+
+        // The sandbox's auxiliary files can change the predicate's results, so
+        // they are part of its fingerprint.
+        let fingerprint = opts.predicate()
+            .fingerprint()
+            .and_then(|f| match opts.sandbox {
+                None => Some(f),
+                Some(ref sandbox) => sandbox.fingerprint(&f),
+            });
+        let cache = match opts.cache_dir {
+            None => None,
+            Some(ref dir) => match fingerprint {
+                Some(fingerprint) => Some(Cache::open(dir, &fingerprint)?),
+                None => {
+                    logger.not_caching();
+                    None
+                }
+            },
+        };
+        let captures = match opts.capture_output {
+            None => None,
+            Some((ref dir, retention)) => Some(capture::Captures::new(dir.clone(), retention)?),
+        };
+
+        let listener = match opts.listen {
+            None => None,
+            Some(ref addr) => {
+                let me2 = me.clone();
+                let listener = remote::Listener::spawn(addr, move |stream| {
+                    me2.remote_worker_connected(stream)
+                })?;
+                logger.listening(listener.local_addr());
+                Some(listener)
+            }
+        };
+
+        let oracle = opts.take_oracle();
+        let fairness = opts.fairness();
+
         let mut supervisor = SupervisorActor {
             opts: opts,
             me: me,
@@ -212,35 +381,98 @@ where
             reducer_id_counter: 0,
             reducer_actors: HashMap::with_capacity(num_reducers),
             reducer_id_to_trait_object: HashMap::with_capacity(num_reducers),
-            reducers_without_actors,
+            reducers_without_actors: Vec::with_capacity(num_reducers),
             exhausted_reducers: HashSet::with_capacity(num_reducers),
-            candidate_queue: CandidateQueue::with_capacity(num_reducers),
-            oracle: Default::default(),
+            candidate_queue: CandidateQueue::with_capacity(num_reducers, fairness),
+            phase: 0,
+            history: history,
+            cache: cache,
+            captures: captures,
+            listener: listener,
+            sandboxes: sandboxes,
+            last_checkpoint: time::Instant::now(),
+            start: time::Instant::now(),
+            last_progress: time::Instant::now(),
+            wants_progress: wants_progress,
+            stats: BTreeMap::new(),
+            requested_snapshots: false,
+            reducer_snapshots: BTreeMap::new(),
+            restored_reducer_states: BTreeMap::new(),
+            dispatched: HashMap::with_capacity(num_workers),
+            oracle: oracle,
         };
 
-        supervisor.backup_original_test_case()?;
-        supervisor.spawn_reducers()?;
+        let checkpoint = supervisor.read_checkpoint()?;
+        if checkpoint.is_none() {
+            supervisor.prepare_output()?;
+        }
 
         let mut smallest_interesting = supervisor.verify_initially_interesting()?;
+        supervisor.record_history(&smallest_interesting);
+
+        let first_phase = checkpoint.as_ref().map_or(0, |c| c.phase);
+        let orig_size = match checkpoint {
+            None => smallest_interesting.size(),
+            Some(checkpoint) => supervisor.restore_checkpoint(checkpoint, &smallest_interesting)?,
+        };
 
-        let orig_size = smallest_interesting.size();
+        for (index, phase) in phases.into_iter().enumerate().skip(first_phase) {
+            let repeat_until_fixpoint = phase.repeats_until_fixpoint();
+            supervisor.start_phase(index, phase);
 
-        loop {
-            let last_iter_size = smallest_interesting.size();
+            loop {
+                let last_iter_size = smallest_interesting.size();
 
-            supervisor.reseed_reducers(&smallest_interesting)?;
-            supervisor.spawn_workers()?;
+                supervisor.reseed_reducers(&smallest_interesting)?;
+                supervisor.spawn_workers()?;
 
-            let should_continue = supervisor.candidate_loop_iteration(
-                &incoming,
-                &mut smallest_interesting,
-                orig_size,
-            )?;
+                let should_continue = supervisor.candidate_loop_iteration(
+                    &incoming,
+                    &mut smallest_interesting,
+                    orig_size,
+                )?;
 
-            if !should_continue || smallest_interesting.size() >= last_iter_size {
-                return supervisor.shutdown(smallest_interesting, orig_size, !should_continue);
+                if !should_continue {
+                    return supervisor.shutdown(smallest_interesting, orig_size, true);
+                }
+                if !repeat_until_fixpoint || smallest_interesting.size() >= last_iter_size {
+                    break;
+                }
             }
         }
+
+        supervisor.shutdown(smallest_interesting, orig_size, false)
+    }
+
+    /// Ask the oracle for the given candidate's priority, scaled by the weight
+    /// of the reducer that generated it.
+    fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
+        let weight = self.opts.weight_of_reducer(candidate.provenance());
+        self.oracle.predict(candidate) * weight
+    }
+
+    /// Get how long the given worker took to judge the candidate that we last
+    /// sent it, if we sent it one, and free up that candidate's place in its
+    /// reducer's quota.
+    fn judgement_time(&mut self, who: &Worker) -> Option<time::Duration> {
+        let (dispatched, provenance) = self.dispatched.remove(&who.id())?;
+        self.candidate_queue.judged(&provenance);
+        Some(dispatched.elapsed())
+    }
+
+    /// Shut down the previous phase's reducer actors, if any, and get the
+    /// given phase's reducers ready to be spawned.
+    fn start_phase(&mut self, index: usize, mut phase: Phase) {
+        for (_, r) in self.reducer_actors.drain() {
+            r.shutdown();
+        }
+        self.reducer_id_to_trait_object.clear();
+        self.exhausted_reducers.clear();
+        self.reducer_snapshots.clear();
+
+        self.phase = index;
+        self.reducers_without_actors = phase.take_reducers();
+        self.logger.starting_phase(phase.name().to_string());
     }
 
     /// Run the supervisor's main loop, serving candidates to workers, and
@@ -272,17 +504,37 @@ where
 
                 SupervisorMessage::RequestNextCandidate(who, not_interesting) => {
                     if let Some(not_interesting) = not_interesting {
-                        self.oracle.observe_not_interesting(&not_interesting);
+                        self.stats_for(not_interesting.candidate().provenance())
+                            .tested += 1;
+                        // Without knowing how long the judgement took, there's
+                        // nothing to tell the oracle.
+                        if let Some(elapsed) = self.judgement_time(&who) {
+                            match not_interesting {
+                                NotInteresting::Judged(ref candidate) => {
+                                    self.oracle.observe_not_interesting(candidate, elapsed);
+                                }
+                                NotInteresting::TimedOut(ref candidate) => {
+                                    self.oracle.observe_timed_out(candidate, elapsed);
+                                }
+                            }
+                        }
                     }
                     self.enqueue_worker_for_candidate(who);
                 }
 
                 SupervisorMessage::ReportInteresting(who, interesting) => {
+                    {
+                        let stats = self.stats_for(interesting.provenance());
+                        stats.tested += 1;
+                        stats.interesting += 1;
+                    }
+                    let elapsed = self.judgement_time(&who);
                     self.handle_new_interesting_test_case(
                         who,
                         orig_size,
                         smallest_interesting,
                         interesting,
+                        elapsed,
                     )?;
                 }
 
@@ -373,12 +625,13 @@ where
 
                 SupervisorMessage::ReplyNextCandidate(reducer, candidate) => {
                     assert!(self.reducer_actors.contains_key(&reducer.id()));
+                    self.stats_for(candidate.provenance()).generated += 1;
 
                     if candidate.size() < smallest_interesting.size() {
-                        let priority = self.oracle.predict(&candidate);
+                        let priority = self.predict(&candidate);
                         if let Score::TryIt(_) = priority {
                             self.candidate_queue
-                                .insert(candidate, reducer.id(), priority);
+                                .insert(candidate, Some(reducer.id()), priority);
                             self.drain_queues();
                             continue;
                         }
@@ -388,6 +641,24 @@ where
                     reducer.request_next_candidate(None);
                 }
 
+                SupervisorMessage::ReplySnapshot(reducer, seed, snapshot) => {
+                    // Only keep snapshots for our current smallest, since any
+                    // others are stale and the reducer is being reseeded.
+                    if seed == *smallest_interesting {
+                        if let Some(r) = self.reducer_id_to_trait_object.get(&reducer.id()) {
+                            let name = r.name().to_string();
+                            match snapshot {
+                                Some(snapshot) => {
+                                    self.reducer_snapshots.insert(name, snapshot);
+                                }
+                                None => {
+                                    self.reducer_snapshots.remove(&name);
+                                }
+                            }
+                        }
+                    }
+                }
+
                 SupervisorMessage::GotSigint => {
                     for (_, worker) in self.workers.drain() {
                         worker.shutdown();
@@ -395,6 +666,11 @@ where
                     self.candidate_queue.clear();
                     return Ok(false);
                 }
+
+                // Messages from the remote worker listener...
+                SupervisorMessage::RemoteWorkerConnected(stream) => {
+                    self.spawn_remote_worker(stream);
+                }
             }
 
             // If all of our reducers are exhausted, and we are out of potential
@@ -412,18 +688,66 @@ where
             if self.workers.is_empty() {
                 break;
             }
+
+            if self.wants_progress &&
+                self.last_progress.elapsed() >=
+                    time::Duration::from_millis(PROGRESS_INTERVAL_MILLIS)
+            {
+                self.log_progress(smallest_interesting, orig_size);
+            }
+
+            // Ask the reducers for snapshots of their states halfway between
+            // checkpoints, so that they are saved in the next checkpoint.
+            let since_checkpoint = self.last_checkpoint.elapsed();
+            if since_checkpoint >= self.opts.checkpoint_interval {
+                self.write_checkpoint(smallest_interesting, orig_size);
+            } else if !self.requested_snapshots
+                && since_checkpoint >= self.opts.checkpoint_interval / 2
+            {
+                self.request_reducer_snapshots();
+            }
         }
 
         Ok(true)
     }
 
+    /// Log a summary of our current state.
+    fn log_progress(&mut self, smallest_interesting: &test_case::Interesting, orig_size: u64) {
+        self.last_progress = time::Instant::now();
+
+        let mut exhausted_reducers: Vec<_> = self.exhausted_reducers
+            .iter()
+            .filter_map(|id| self.reducer_id_to_trait_object.get(id))
+            .map(|r| r.name().to_string())
+            .collect();
+        exhausted_reducers.sort();
+
+        self.logger.progress(Progress {
+            smallest_size: smallest_interesting.size(),
+            orig_size: orig_size,
+            queued_candidates: self.candidate_queue.len(),
+            workers: self.workers.len(),
+            idle_workers: self.idle_workers.len(),
+            reducers: self.reducer_actors.len(),
+            exhausted_reducers: exhausted_reducers,
+        });
+    }
+
+    /// Get the counters for candidates with the given provenance.
+    fn stats_for(&mut self, provenance: &str) -> &mut report::ReducerStats {
+        if !self.stats.contains_key(provenance) {
+            self.stats.insert(provenance.to_string(), Default::default());
+        }
+        self.stats.get_mut(provenance).unwrap()
+    }
+
     /// Consume this supervisor actor and perform shutdown.
     fn shutdown(
-        self,
+        mut self,
         smallest_interesting: test_case::Interesting,
         orig_size: u64,
         shutting_down_early: bool
-    ) -> error::Result<()> {
+    ) -> error::Result<report::ReductionReport> {
         assert!(self.workers.is_empty());
         assert!(self.candidate_queue.is_empty());
         if !shutting_down_early {
@@ -432,8 +756,24 @@ where
 
         let _signpost = signposts::SupervisorShutdown::new();
 
+        if let Some(listener) = self.listener.take() {
+            listener.shutdown();
+        }
+
+        self.write_checkpoint(&smallest_interesting, orig_size);
+        if self.wants_progress {
+            self.log_progress(&smallest_interesting, orig_size);
+        }
+
         self.logger
             .final_reduced_size(smallest_interesting.size(), orig_size);
+        for (reducer, occupancy) in self.candidate_queue.occupancy() {
+            self.logger.queue_occupancy(
+                reducer.clone(),
+                occupancy.peak_queued,
+                occupancy.queued_time,
+            );
+        }
 
         self.sigint.shutdown();
         let _ = self.sigint_handle.join();
@@ -455,7 +795,7 @@ where
         // contents are UTF-8, then print it to stdout.
         const TOO_BIG_TO_PRINT: u64 = 4096;
         let final_size = smallest_interesting.size();
-        if final_size < TOO_BIG_TO_PRINT {
+        if final_size < TOO_BIG_TO_PRINT && !smallest_interesting.is_dir() {
             let mut contents = String::with_capacity(final_size as usize);
             let mut file = fs::File::open(smallest_interesting.path())?;
             if let Ok(_) = file.read_to_string(&mut contents) {
@@ -463,16 +803,48 @@ where
             }
         }
 
-        Ok(())
+        for (provenance, occupancy) in self.candidate_queue.occupancy() {
+            let stats = self.stats
+                .entry(provenance.clone())
+                .or_insert_with(Default::default);
+            stats.peak_queued = occupancy.peak_queued as u64;
+            stats.queued_time = occupancy.queued_time;
+        }
+
+        Ok(report::ReductionReport {
+            original_size: orig_size,
+            final_size: final_size,
+            final_path: self.opts.output_path().to_path_buf(),
+            termination: if shutting_down_early {
+                report::Termination::Sigint
+            } else {
+                report::Termination::Fixpoint
+            },
+            wall_time: self.start.elapsed(),
+            reducers: self.stats,
+        })
     }
 
     /// Given that the worker with the given id panicked or errored out, clean
     /// up after it and spawn a replacement for it.
     fn restart_worker(&mut self, id: WorkerId) -> error::Result<()> {
-        let old_worker = self.workers.remove(&id);
-        assert!(old_worker.is_some());
+        let old_worker = self.workers
+            .remove(&id)
+            .expect("should only restart workers we know about");
+        if let Some((_, provenance)) = self.dispatched.remove(&id) {
+            self.candidate_queue.judged(&provenance);
+        }
 
-        self.spawn_workers()
+        match old_worker.origin() {
+            WorkerOrigin::Local => self.spawn_workers(),
+            // Remote workers connect to us, so we can't reconnect to them;
+            // `preduce-worker` connects again by itself, backing off if its
+            // connections keep getting lost.
+            WorkerOrigin::Remote(addr) => {
+                self.logger.remote_worker_lost(id, addr);
+                Ok(())
+            }
+        }
     }
 
     /// Generate the next candidate and send it to the given worker, or shutdown
@@ -499,32 +871,43 @@ where
 
         for (worker, (candidate, reducer_id)) in workers.zip(candidates) {
             assert!(self.workers.contains_key(&worker.id()));
-            assert!(self.reducer_actors.contains_key(&reducer_id));
 
             // Send the worker the next candidate from the queue to test for
             // interestingness.
+            self.dispatched.insert(
+                worker.id(),
+                (time::Instant::now(), candidate.provenance().to_string()),
+            );
             worker.next_candidate(candidate);
 
             // And pipeline the worker's is-interesting test with generating the
-            // next candidate.
-            if !self.exhausted_reducers.contains(&reducer_id) {
-                self.reducer_actors[&reducer_id].request_next_candidate(None);
+            // next candidate. Candidates without a reducer were merged by us,
+            // and there is nothing more to generate for them.
+            if let Some(reducer_id) = reducer_id {
+                assert!(self.reducer_actors.contains_key(&reducer_id));
+                if !self.exhausted_reducers.contains(&reducer_id) {
+                    self.reducer_actors[&reducer_id].request_next_candidate(None);
+                }
             }
         }
     }
 
     /// Given that the `who` worker just found a new interesting test case,
-    /// either update our globally smallest interesting test case, or tell the
-    /// worker to try testing a new candidate.
+    /// after judging it for `elapsed` wall time, if known, either update our
+    /// globally smallest interesting test case, or tell the worker to try
+    /// testing a new candidate.
     fn handle_new_interesting_test_case(
         &mut self,
         who: Worker,
         orig_size: u64,
         smallest_interesting: &mut test_case::Interesting,
         interesting: test_case::Interesting,
+        elapsed: Option<time::Duration>,
     ) -> error::Result<()> {
         let _signpost = signposts::SupervisorHandleInteresting::new();
 
+        self.record_history(&interesting);
+
         let new_size = interesting.size();
         let old_size = smallest_interesting.size();
 
@@ -534,10 +917,14 @@ where
             // candidate. The candidate process can take a LONG time, and if the
             // computation is interrupted for whatever reason, we DO NOT want to
             // lose this incremental progress!
-            *smallest_interesting = interesting;
-            fs::copy(smallest_interesting.path(), &self.opts.test_case)?;
-            self.oracle
-                .observe_smallest_interesting(&smallest_interesting);
+            self.stats_for(interesting.provenance()).bytes_removed += old_size - new_size;
+            let old_smallest = mem::replace(smallest_interesting, interesting);
+            test_case::copy_test_case(smallest_interesting.path(), self.opts.output_path())?;
+            if let Some(elapsed) = elapsed {
+                self.oracle
+                    .observe_smallest_interesting(&smallest_interesting, elapsed);
+            }
+            self.reducer_snapshots.clear();
             self.logger
                 .new_smallest(smallest_interesting.clone(), orig_size);
 
@@ -555,11 +942,18 @@ where
             {
                 let reducers = &self.reducer_actors;
                 self.candidate_queue.retain(|_candidate, reducer_id| {
-                    reducers[&reducer_id].request_next_candidate(None);
+                    if let Some(reducer_id) = reducer_id {
+                        reducers[&reducer_id].request_next_candidate(None);
+                    }
                     false
                 });
             }
 
+            // If the previous smallest interesting test case was not an
+            // ancestor of the new one, then they were found concurrently, and
+            // their reductions might combine into an even smaller test case.
+            self.try_merge(smallest_interesting, &old_smallest);
+
             // Finaly send a new candidate to the worker that reported the new
             // smallest test case.
             self.enqueue_worker_for_candidate(who);
@@ -569,7 +963,11 @@ where
             // the same time, the smaller of the two reports back first, and
             // then the larger finishes and reports back before it is told to
             // abandon its current is-interesting test and move on to new work.
-            self.oracle.observe_not_smallest_interesting(&interesting);
+            if let Some(elapsed) = elapsed {
+                self.oracle
+                    .observe_not_smallest_interesting(&interesting, elapsed);
+            }
+            self.try_merge(smallest_interesting, &interesting);
             self.logger.is_not_smaller(interesting);
             self.enqueue_worker_for_candidate(who);
         }
@@ -577,6 +975,163 @@ where
         Ok(())
     }
 
+    /// Record the given interesting test case in our history, disabling the
+    /// history if that fails.
+    fn record_history(&mut self, interesting: &test_case::Interesting) {
+        let result = match self.history {
+            None => return,
+            Some(ref mut history) => history.record(interesting),
+        };
+        if let Err(e) = result {
+            self.logger.history_errored(e);
+            self.history = None;
+        }
+    }
+
+    /// Attempt to merge the smallest interesting test case with another
+    /// interesting test case, and if the merge is clean and smaller than the
+    /// smallest, queue the merged candidate for testing.
+    ///
+    /// The caller is responsible for draining the queues afterwards.
+    fn try_merge(
+        &mut self,
+        smallest_interesting: &test_case::Interesting,
+        other: &test_case::Interesting,
+    ) {
+        let result = match self.history {
+            None => return,
+            Some(ref mut history) => history.merge(smallest_interesting, other),
+        };
+
+        let candidate = match result {
+            Ok(Some(candidate)) => candidate,
+            Ok(None) => return,
+            Err(e) => {
+                self.logger.history_errored(e);
+                self.history = None;
+                return;
+            }
+        };
+
+        if candidate.size() >= smallest_interesting.size() {
+            return;
+        }
+
+        self.stats_for(candidate.provenance()).generated += 1;
+        let priority = self.predict(&candidate);
+        if let Score::TryIt(_) = priority {
+            self.logger.merged(candidate.clone());
+            self.candidate_queue.insert(candidate, None, priority);
+        }
+    }
+
+    /// If we are resuming, read the checkpoint we are resuming from and copy
+    /// its smallest interesting test case over the test case file.
+    fn read_checkpoint(&self) -> error::Result<Option<Checkpoint>> {
+        if !self.opts.resume {
+            return Ok(None);
+        }
+
+        let dir = self.opts
+            .checkpoint_dir
+            .as_ref()
+            .expect("resuming implies a checkpoint directory");
+        self.logger.resuming_from_checkpoint(dir);
+
+        let checkpoint = Checkpoint::read(dir)?;
+        test_case::copy_test_case(Checkpoint::smallest_path(dir), self.opts.output_path())?;
+        Ok(Some(checkpoint))
+    }
+
+    /// Restore the oracle's observations and the reducers' states from the
+    /// given checkpoint, and return the original test case's size.
+    fn restore_checkpoint(
+        &mut self,
+        checkpoint: Checkpoint,
+        smallest_interesting: &test_case::Interesting,
+    ) -> error::Result<u64> {
+        self.oracle
+            .restore(checkpoint.oracle.clone(), smallest_interesting)?;
+
+        // The reducers' states are only valid for the seed they were generated
+        // from.
+        if checkpoint.matches(smallest_interesting) {
+            self.restored_reducer_states = checkpoint.reducers;
+        }
+
+        Ok(cmp::max(checkpoint.orig_size, smallest_interesting.size()))
+    }
+
+    /// Write a checkpoint, if checkpointing is enabled.
+    ///
+    /// Failing to write a checkpoint is logged, but does not stop the
+    /// reduction.
+    fn write_checkpoint(&mut self, smallest_interesting: &test_case::Interesting, orig_size: u64) {
+        self.last_checkpoint = time::Instant::now();
+        self.requested_snapshots = false;
+
+        let dir = match self.opts.checkpoint_dir {
+            None => return,
+            Some(ref dir) => dir,
+        };
+
+        let checkpoint = Checkpoint {
+            orig_size: orig_size,
+            smallest_hash: test_case::hash_to_hex(&smallest_interesting.full_hash()),
+            oracle: self.oracle.snapshot(),
+            reducers: self.reducer_snapshots.clone(),
+            phase: self.phase,
+        };
+
+        match checkpoint.write(dir, smallest_interesting) {
+            Ok(()) => self.logger.wrote_checkpoint(dir),
+            Err(e) => self.logger.checkpoint_errored(e),
+        }
+    }
+
+    /// Ask the reducers for fresh snapshots of their states, to be saved in
+    /// the next checkpoint.
+    fn request_reducer_snapshots(&mut self) {
+        self.requested_snapshots = true;
+        if self.opts.checkpoint_dir.is_none() {
+            return;
+        }
+
+        for reducer in self.reducer_actors.values() {
+            reducer.request_snapshot();
+        }
+    }
+
+    /// Get the output ready before the reduction starts: either copy the
+    /// initial test case to the separate output, or back up the initial test
+    /// case before we start overwriting it in place.
+    fn prepare_output(&self) -> error::Result<()> {
+        let output = match self.opts.output {
+            None if self.opts.backup => return self.backup_original_test_case(),
+            None => return Ok(()),
+            Some(ref output) => output,
+        };
+
+        test_case::copy_test_case(&self.opts.test_case, output)?;
+
+        // The copy keeps the initial test case's permissions, but we need to
+        // overwrite it with each new smallest interesting test case, even if
+        // the initial test case is read-only.
+        let files = if output.is_dir() {
+            test_case::members(output)?
+                .into_iter()
+                .map(|m| output.join(m))
+                .collect()
+        } else {
+            vec![output.clone()]
+        };
+        for file in files {
+            make_writable(&file)?;
+        }
+
+        Ok(())
+    }
+
     /// Backup the original test case, just in case something goes wrong, or it
     /// is needed again to reduce a different issue from the one we're currently
     /// reducing, or...
@@ -600,31 +1155,77 @@ where
         self.logger
             .backing_up_test_case(&self.opts.test_case, &backup_path);
 
-        fs::copy(&self.opts.test_case, backup_path).map_err(error::Error::TestCaseBackupFailure)?;
+        test_case::copy_test_case(&self.opts.test_case, backup_path).map_err(|e| match e {
+            error::Error::Io(e) => error::Error::TestCaseBackupFailure(e),
+            e => e,
+        })?;
 
         Ok(())
     }
 
-    /// Verify that the initial, unreduced test case is itself interesting.
+    /// Verify that the initial, unreduced test case is itself interesting (and
+    /// run the other pre-flight checks, if enabled), and warn if the predicate
+    /// doesn't find it interesting on every one of the configured flakiness
+    /// check runs.
     fn verify_initially_interesting(&mut self) -> error::Result<test_case::Interesting> {
-        let initial = test_case::Interesting::initial(&self.opts.test_case, self.opts.predicate())?;
+        let predicate = match self.sandboxes {
+            None => self.opts.predicate().clone(),
+            Some(ref sandboxes) => {
+                let id = self.worker_id_counter;
+                self.worker_id_counter += 1;
+                sandboxes.sandbox(id, self.opts.predicate().clone())?
+            }
+        };
+        let initial = if self.opts.preflight {
+            let report = preflight::check(&predicate, self.opts.output_path())?;
+            self.logger.preflight_passed(report.runtime);
+            // The pre-flight checks already found it interesting.
+            let judge = |_: &path::Path| -> error::Result<bool> { Ok(true) };
+            test_case::Interesting::initial(self.opts.output_path(), &judge)?
+        } else {
+            test_case::Interesting::initial(self.opts.output_path(), &predicate)?
+        };
         let initial = initial.ok_or(error::Error::InitialTestCaseNotInteresting)?;
+
+        let runs = self.opts.flakiness_check_runs;
+        if runs > 0 {
+            let mut interesting = 1;
+            for _ in 0..runs {
+                if predicate.is_interesting_once(initial.path())? {
+                    interesting += 1;
+                }
+            }
+            if interesting <= runs {
+                self.logger.flaky_predicate(interesting, runs + 1);
+            }
+        }
+
         Ok(initial)
     }
 
     /// Spawn (or re-spawn) workers until we have the number of active,
     /// concurrent workers originally requested in the `Options`.
     fn spawn_workers(&mut self) -> error::Result<()> {
-        assert!(self.workers.len() <= self.opts.num_workers());
+        // Remote workers come and go as they please, and aren't replaced.
+        let num_local = self.workers.values().filter(|w| !w.is_remote()).count();
+        assert!(num_local <= self.opts.num_workers());
 
-        let new_workers: error::Result<Vec<_>> = (self.workers.len()..self.opts.num_workers())
+        let new_workers: error::Result<Vec<_>> = (num_local..self.opts.num_workers())
             .map(|_| {
                 let id = WorkerId::new(self.worker_id_counter);
                 self.worker_id_counter += 1;
 
+                let predicate = match self.sandboxes {
+                    None => self.opts.predicate().clone(),
+                    Some(ref sandboxes) => {
+                        sandboxes.sandbox(id.into(), self.opts.predicate().clone())?
+                    }
+                };
                 let worker = Worker::spawn(
                     id,
-                    self.opts.predicate().clone(),
+                    predicate,
+                    self.cache.clone(),
+                    self.captures.clone(),
                     self.me.clone(),
                     self.logger.clone(),
                 )?;
@@ -636,6 +1237,37 @@ where
         Ok(())
     }
 
+    /// Spawn a worker actor for a newly connected remote worker.
+    ///
+    /// Failing to do so is logged, but does not stop the reduction; the remote
+    /// worker will see its connection close, and can try again.
+    fn spawn_remote_worker(&mut self, stream: net::TcpStream) {
+        let id = WorkerId::new(self.worker_id_counter);
+        self.worker_id_counter += 1;
+
+        if let Ok(addr) = stream.peer_addr() {
+            self.logger.remote_worker_connected(id, addr);
+        }
+
+        let predicate_name = self.opts
+            .remote_predicate_name
+            .clone()
+            .expect("listening for remote workers implies a remote predicate name");
+        match Worker::spawn_remote(
+            id,
+            stream,
+            predicate_name,
+            self.cache.clone(),
+            self.me.clone(),
+            self.logger.clone(),
+        ) {
+            Ok(worker) => {
+                self.workers.insert(id, worker);
+            }
+            Err(e) => self.logger.worker_errored(id, e),
+        }
+    }
+
     /// Spawn a reducer actor for each reducer given to us in the options.
     fn spawn_reducers(&mut self) -> error::Result<()> {
         for reducer in self.reducers_without_actors.drain(..) {
@@ -662,7 +1294,13 @@ where
         self.spawn_reducers()?;
 
         for (id, reducer_actor) in &self.reducer_actors {
-            reducer_actor.set_new_seed(smallest_interesting.clone());
+            let name = self.reducer_id_to_trait_object[id].name().to_string();
+            match self.restored_reducer_states.remove(&name) {
+                Some(snapshot) => {
+                    reducer_actor.restore_seed(smallest_interesting.clone(), snapshot)
+                }
+                None => reducer_actor.set_new_seed(smallest_interesting.clone()),
+            }
 
             // If the reducer was exhausted, put it back to work again by
             // requesting the next candidate. If it isn't exhausted, then we
@@ -675,6 +1313,28 @@ where
             }
         }
 
+        // Restored states are only good for the first seeding; any that are
+        // left over belong to reducers we aren't running anymore.
+        self.restored_reducer_states.clear();
+
         Ok(())
     }
 }
+
+/// Give the file's owner permission to write to it.
+#[cfg(unix)]
+fn make_writable(file: &path::Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut perms = fs::metadata(file)?.permissions();
+    let mode = perms.mode();
+    perms.set_mode(mode | 0o200);
+    fs::set_permissions(file, perms)
+}
+
+/// Give the file's owner permission to write to it.
+#[cfg(not(unix))]
+fn make_writable(file: &path::Path) -> io::Result<()> {
+    let mut perms = fs::metadata(file)?.permissions();
+    perms.set_readonly(false);
+    fs::set_permissions(file, perms)
+}
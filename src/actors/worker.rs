@@ -1,15 +1,21 @@
 //! The worker actor pulls potentially interesting test cases from the
 //! supervisor and tests them for interestingness.
 
-use super::{Logger, Supervisor};
+use super::{Logger, NotInteresting, Supervisor};
+use cache;
+use capture;
 use either::{Either, Left, Right};
 use error;
+use interesting;
+use remote;
 use signposts;
 use std::fmt;
+use std::net;
 use std::panic;
+use std::path;
 use std::sync::mpsc;
 use std::thread;
-use test_case;
+use test_case::{self, TestCaseMethods};
 use traits;
 
 /// An identifier for a worker actor.
@@ -22,6 +28,12 @@ impl WorkerId {
     }
 }
 
+impl From<WorkerId> for usize {
+    fn from(id: WorkerId) -> usize {
+        id.0
+    }
+}
+
 impl fmt::Display for WorkerId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -35,10 +47,21 @@ enum WorkerMessage {
     Shutdown,
 }
 
+/// Where a worker judges its candidates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkerOrigin {
+    /// On this machine.
+    Local,
+
+    /// On the remote worker that connected from the given address.
+    Remote(net::SocketAddr),
+}
+
 /// A client handle to a worker actor.
 #[derive(Clone, Debug)]
 pub struct Worker {
     id: WorkerId,
+    origin: WorkerOrigin,
     sender: mpsc::Sender<WorkerMessage>,
 }
 
@@ -48,6 +71,44 @@ impl Worker {
     pub fn spawn(
         id: WorkerId,
         predicate: Box<traits::IsInteresting>,
+        cache: Option<cache::Cache>,
+        captures: Option<capture::Captures>,
+        supervisor: Supervisor,
+        logger: Logger,
+    ) -> error::Result<Worker> {
+        Worker::spawn_with(
+            id,
+            WorkerOrigin::Local,
+            predicate,
+            cache,
+            captures,
+            supervisor,
+            logger,
+        )
+    }
+
+    /// Spawn a new worker actor that judges candidates on the remote worker at
+    /// the other end of the given connection, with its predicate of the given
+    /// name.
+    pub fn spawn_remote(
+        id: WorkerId,
+        stream: net::TcpStream,
+        predicate_name: String,
+        cache: Option<cache::Cache>,
+        supervisor: Supervisor,
+        logger: Logger,
+    ) -> error::Result<Worker> {
+        let origin = WorkerOrigin::Remote(stream.peer_addr()?);
+        let predicate = remote::Predicate::new(predicate_name, stream)?;
+        Worker::spawn_with(id, origin, Box::new(predicate), cache, None, supervisor, logger)
+    }
+
+    fn spawn_with(
+        id: WorkerId,
+        origin: WorkerOrigin,
+        predicate: Box<traits::IsInteresting>,
+        cache: Option<cache::Cache>,
+        captures: Option<capture::Captures>,
         supervisor: Supervisor,
         logger: Logger,
     ) -> error::Result<Worker> {
@@ -57,6 +118,7 @@ impl Worker {
 
         let me = Worker {
             id: id,
+            origin: origin,
             sender: sender,
         };
         let me2 = me.clone();
@@ -64,7 +126,16 @@ impl Worker {
         thread::Builder::new()
             .name(format!("preduce-worker-{}", id))
             .spawn(move || {
-                WorkerActor::run(id, me2, predicate, receiver, supervisor, logger);
+                WorkerActor::run(
+                    id,
+                    me2,
+                    predicate,
+                    cache,
+                    captures,
+                    receiver,
+                    supervisor,
+                    logger,
+                );
             })?;
 
         Ok(me)
@@ -75,6 +146,16 @@ impl Worker {
         self.id
     }
 
+    /// Get where this worker judges its candidates.
+    pub fn origin(&self) -> WorkerOrigin {
+        self.origin
+    }
+
+    /// Is this worker judging candidates on a remote worker?
+    pub fn is_remote(&self) -> bool {
+        self.origin != WorkerOrigin::Local
+    }
+
     // For communication with this worker from the supervisor, don't unwrap the
     // mpsc sends. Instead of panicking the supervisor, let the catch_unwind'ing
     // of the worker inform the supervisor of a worker's early, unexpected
@@ -98,6 +179,8 @@ struct WorkerActor {
     id: WorkerId,
     me: Worker,
     predicate: Box<traits::IsInteresting>,
+    cache: Option<cache::Cache>,
+    captures: Option<capture::Captures>,
     incoming: mpsc::Receiver<WorkerMessage>,
     supervisor: Supervisor,
     logger: Logger,
@@ -126,6 +209,8 @@ impl WorkerActor {
         id: WorkerId,
         me: Worker,
         predicate: Box<traits::IsInteresting>,
+        cache: Option<cache::Cache>,
+        captures: Option<capture::Captures>,
         incoming: mpsc::Receiver<WorkerMessage>,
         supervisor: Supervisor,
         logger: Logger,
@@ -134,7 +219,16 @@ impl WorkerActor {
             let supervisor2 = supervisor.clone();
             let logger2 = logger.clone();
             panic::catch_unwind(panic::AssertUnwindSafe(move || {
-                WorkerActor::try_run(id, me, predicate, incoming, supervisor2, logger2)
+                WorkerActor::try_run(
+                    id,
+                    me,
+                    predicate,
+                    cache,
+                    captures,
+                    incoming,
+                    supervisor2,
+                    logger2,
+                )
             }))
         } {
             Err(p) => {
@@ -151,6 +245,8 @@ impl WorkerActor {
         id: WorkerId,
         me: Worker,
         predicate: Box<traits::IsInteresting>,
+        cache: Option<cache::Cache>,
+        captures: Option<capture::Captures>,
         incoming: mpsc::Receiver<WorkerMessage>,
         supervisor: Supervisor,
         logger: Logger,
@@ -161,6 +257,8 @@ impl WorkerActor {
             id: id,
             me: me,
             predicate: predicate,
+            cache: cache,
+            captures: captures,
             incoming: incoming,
             supervisor: supervisor,
             logger: logger,
@@ -195,8 +293,8 @@ impl WorkerActor {
                     }
                 }
                 Right((worker, not_interesting)) => {
-                    // The test case was judged not interesting; grab a new
-                    // candidate to test.
+                    // The test case was not interesting; grab a new candidate
+                    // to test.
                     test = match worker.get_next_candidate(Some(not_interesting)) {
                         Some(test) => test,
                         None => return Ok(()),
@@ -211,7 +309,28 @@ impl WorkerActor {
         None
     }
 
-    fn get_next_candidate(self, not_interesting: Option<test_case::Candidate>) -> Option<Test> {
+    /// Record the predicate's captured output for the given candidate, if we
+    /// are capturing output, and return the file it was kept in.
+    fn capture(
+        &self,
+        candidate: &test_case::Candidate,
+        interesting: bool,
+        output: Option<&interesting::Output>,
+    ) -> Option<path::PathBuf> {
+        let (captures, output) = match (self.captures.as_ref(), output) {
+            (Some(captures), Some(output)) => (captures, output),
+            _ => return None,
+        };
+        match captures.record(candidate, interesting, output) {
+            Ok(path) => path,
+            Err(e) => {
+                self.logger.capture_errored(e);
+                None
+            }
+        }
+    }
+
+    fn get_next_candidate(self, not_interesting: Option<NotInteresting>) -> Option<Test> {
         let _signpost = signposts::WorkerGetNextCandidate::new();
 
         self.supervisor
@@ -227,17 +346,98 @@ impl WorkerActor {
 }
 
 impl Test {
-    fn judge(self) -> error::Result<Either<Interesting, (WorkerActor, test_case::Candidate)>> {
+    fn judge(self) -> error::Result<Either<Interesting, (WorkerActor, NotInteresting)>> {
         let _signpost = signposts::WorkerJudgeInteresting::new();
 
         self.worker
             .logger
             .start_judging_interesting(self.worker.id, self.candidate.clone());
-        match self.candidate.into_interesting(&self.worker.predicate)? {
+        let candidate = self.candidate.clone();
+        let full_hash = candidate.full_hash();
+
+        let cached = self.worker
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(&full_hash));
+        let mut output_file = None;
+        let result = match cached {
+            Some(is_interesting) => {
+                self.worker
+                    .logger
+                    .cached_result(self.worker.id, candidate.clone(), is_interesting);
+                let judge = move |_: &path::Path| Ok(is_interesting);
+                self.candidate.into_interesting(&judge)
+            }
+            None => {
+                let judged = self.worker
+                    .predicate
+                    .judge(candidate.path(), self.worker.captures.is_some());
+                if let Ok(ref judgement) = judged {
+                    if let Some(votes) = judgement.votes {
+                        if votes.is_near_threshold() {
+                            self.worker
+                                .logger
+                                .judged_near_threshold(self.worker.id, candidate.clone(), votes);
+                        }
+                    }
+                    output_file = self.worker.capture(
+                        &candidate,
+                        judgement.interesting,
+                        judgement.output.as_ref(),
+                    );
+                }
+                let result = match judged {
+                    Ok(judgement) => {
+                        let is_interesting = judgement.interesting;
+                        let judge = move |_: &path::Path| Ok(is_interesting);
+                        self.candidate.into_interesting(&judge)
+                    }
+                    Err(e) => Err(e),
+                };
+                if let (Some(cache), &Ok(ref judged)) = (self.worker.cache.as_ref(), &result) {
+                    if let Err(e) = cache.insert(&full_hash, judged.is_left()) {
+                        self.worker.logger.cache_errored(e);
+                    }
+                }
+                result
+            }
+        };
+
+        let judged = match result {
+            // A candidate that hangs the predicate is not interesting, but we
+            // log it separately so that the timeouts can be tracked down, and
+            // tell the supervisor so that the oracle learns which reducers
+            // produce them.
+            Err(error::Error::PredicateTimedOut(timeout, output)) => {
+                let output_file = self.worker.capture(&candidate, false, output.as_ref());
+                self.worker.logger.judged_timed_out(
+                    self.worker.id,
+                    candidate.clone(),
+                    timeout,
+                    output_file,
+                );
+                return Ok(Right((self.worker, NotInteresting::TimedOut(candidate))));
+            }
+            // Likewise for a candidate that makes the predicate exceed one of
+            // its resource limits.
+            Err(error::Error::ResourceLimitExceeded(limit, output)) => {
+                let output_file = self.worker.capture(&candidate, false, output.as_ref());
+                self.worker.logger.judged_limit_exceeded(
+                    self.worker.id,
+                    candidate.clone(),
+                    limit,
+                    output_file,
+                );
+                return Ok(Right((self.worker, NotInteresting::Judged(candidate))));
+            }
+            result => result?,
+        };
+
+        match judged {
             Left(interesting) => {
                 self.worker
                     .logger
-                    .judged_interesting(self.worker.id, interesting.clone());
+                    .judged_interesting(self.worker.id, interesting.clone(), output_file);
                 Ok(Left(Interesting {
                     worker: self.worker,
                     interesting: interesting,
@@ -246,8 +446,8 @@ impl Test {
             Right(not_interesting) => {
                 self.worker
                     .logger
-                    .judged_not_interesting(self.worker.id, not_interesting.clone());
-                Ok(Right((self.worker, not_interesting)))
+                    .judged_not_interesting(self.worker.id, not_interesting.clone(), output_file);
+                Ok(Right((self.worker, NotInteresting::Judged(not_interesting))))
             }
         }
     }
@@ -0,0 +1,146 @@
+//! The `preduce-worker` executable, which judges candidates for a `preduce`
+//! coordinator over TCP.
+#![deny(missing_docs)]
+
+extern crate clap;
+extern crate preduce;
+
+use preduce::{error, interesting, remote};
+use std::cmp;
+use std::io::{self, Write};
+use std::net;
+use std::path;
+use std::process;
+use std::thread;
+use std::time;
+
+/// The longest we will wait between attempts to connect to the coordinator.
+const MAX_RETRY_INTERVAL_MILLIS: u64 = 1000;
+
+/// The longest we will wait before reconnecting to a coordinator that keeps
+/// dropping our connection.
+const MAX_RECONNECT_INTERVAL_MILLIS: u64 = 30_000;
+
+/// A connection that lasts at least this long is considered healthy, and
+/// resets the wait before reconnecting.
+const HEALTHY_CONNECTION_SECS: u64 = 10;
+
+fn main() {
+    if let Err(e) = try_main() {
+        let stderr = io::stderr();
+        let mut stderr = stderr.lock();
+        let _ = writeln!(&mut stderr, "Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args() -> clap::ArgMatches<'static> {
+    clap::App::new("preduce-worker")
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(
+            "Judge candidates for a preduce coordinator that was started with --listen, \
+             running the is-interesting predicates on this machine.",
+        )
+        .arg(
+            clap::Arg::with_name("coordinator")
+                .required(true)
+                .help("The coordinator's address, for example build-01:4000."),
+        )
+        .arg(
+            clap::Arg::with_name("predicate")
+                .required(true)
+                .multiple(true)
+                .min_values(1)
+                .help(
+                    "The is-interesting predicate scripts. The coordinator asks for a \
+                     predicate by its file name.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("predicate-timeout")
+                .long("predicate-timeout")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(|a| {
+                    let secs = a.parse::<f64>().map_err(|e| format!("{}", e))?;
+                    if secs > 0.0 {
+                        Ok(())
+                    } else {
+                        Err("SECONDS must be a number greater than 0".into())
+                    }
+                })
+                .help(
+                    "Kill the is-interesting predicate, and everything it spawned, if it runs \
+                     for longer than SECONDS on a candidate.",
+                ),
+        )
+        .get_matches()
+}
+
+/// Connect to the coordinator, retrying until it is listening.
+fn connect(coordinator: &str) -> net::TcpStream {
+    let mut interval = time::Duration::from_millis(10);
+    loop {
+        if let Ok(stream) = net::TcpStream::connect(coordinator) {
+            return stream;
+        }
+        thread::sleep(interval);
+        interval = cmp::min(
+            interval * 2,
+            time::Duration::from_millis(MAX_RETRY_INTERVAL_MILLIS),
+        );
+    }
+}
+
+fn try_main() -> error::Result<()> {
+    let args = parse_args();
+
+    let timeout = args.value_of("predicate-timeout").map(|secs| {
+        let secs = secs.parse::<f64>().unwrap();
+        time::Duration::new(secs.trunc() as u64, (secs.fract() * 1e9) as u32)
+    });
+
+    let mut worker = remote::RemoteWorker::new();
+    for script in args.values_of("predicate").unwrap() {
+        let script = path::Path::new(script).canonicalize()?;
+        let name = script
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.to_string())
+            .ok_or_else(|| error::Error::DoesNotExist(script.clone()))?;
+        let mut predicate = interesting::Script::new(&script)?;
+        predicate.set_timeout(timeout);
+        worker.add_predicate(name, predicate);
+    }
+
+    // Keep serving coordinators until we're killed: when one finishes its
+    // reduction, or goes away, wait for the next one. A coordinator that
+    // speaks another version of the protocol is never going to work, though.
+    let coordinator = args.value_of("coordinator").unwrap();
+    let mut reconnect_interval = time::Duration::from_millis(MAX_RETRY_INTERVAL_MILLIS);
+    loop {
+        let stream = connect(coordinator);
+        let connected = time::Instant::now();
+        match worker.serve(stream) {
+            Ok(()) => {}
+            Err(e @ error::Error::RemoteProtocolMismatch(..)) => return Err(e),
+            Err(e) => {
+                let stderr = io::stderr();
+                let mut stderr = stderr.lock();
+                let _ = writeln!(&mut stderr, "Lost connection to coordinator: {}", e);
+            }
+        }
+
+        // Don't hammer a coordinator that keeps dropping our connection.
+        if connected.elapsed() >= time::Duration::from_secs(HEALTHY_CONNECTION_SECS) {
+            reconnect_interval = time::Duration::from_millis(MAX_RETRY_INTERVAL_MILLIS);
+        } else {
+            thread::sleep(reconnect_interval);
+            reconnect_interval = cmp::min(
+                reconnect_interval * 2,
+                time::Duration::from_millis(MAX_RECONNECT_INTERVAL_MILLIS),
+            );
+        }
+    }
+}
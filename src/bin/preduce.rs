@@ -2,11 +2,23 @@
 #![deny(missing_docs)]
 
 extern crate clap;
+#[cfg(unix)]
+extern crate libc;
 extern crate preduce;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate tempdir;
+extern crate toml;
 
-use preduce::{error, interesting, reducers, traits};
-use std::io::{self, Write};
+use preduce::{error, interesting, reducers, sandbox, traits};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path;
 use std::process;
+use std::time;
 
 fn main() {
     if let Err(e) = try_main() {
@@ -24,25 +36,121 @@ fn parse_args() -> clap::ArgMatches<'static> {
         .about(env!("CARGO_PKG_DESCRIPTION"))
         .arg(
             clap::Arg::with_name("test-case")
-                .required(true)
-                .help("The initial test case to reduce."),
+                .required_unless_one(&["resume", "config"])
+                .help(
+                    "The initial test case to reduce. May be a file or a directory of files, \
+                     or - to read a file from stdin, in which case --output is required.",
+                ),
         )
         .arg(
             clap::Arg::with_name("predicate")
-                .required(true)
-                .help("The is-interesting predicate script."),
+                .required_unless_one(&["resume", "command", "config"])
+                .help(
+                    "The is-interesting predicate script. Omitted when the predicate is given \
+                     with --command.",
+                ),
         )
         .arg(
             clap::Arg::with_name("reducer")
-                .required(true)
+                .required_unless_one(&["resume", "command", "phase", "config"])
                 .multiple(true)
                 .min_values(1)
-                .help("The candidate generator scripts. There must be at least one."),
+                .help(
+                    "The candidate generator scripts, or the names of built-in reducers, such \
+                     as chunks, which run in-process. There must be at least one, unless the \
+                     reducers are given with --phase.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("phase")
+                .long("phase")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("NAME:REDUCER,...[:fixpoint]")
+                .conflicts_with("resume")
+                .validator(|p| PhaseInvocation::parse(&p).map(|_| ()))
+                .help(
+                    "Run the given reducer scripts in a reduction phase named NAME. Phases run \
+                     in the order they are given, each until its reducers are exhausted, or, \
+                     with the :fixpoint suffix, until it stops making progress. When any \
+                     phase is given, all reducers must be given with --phase.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("config")
+                .long("config")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with("resume")
+                .help(
+                    "Read the reduction's configuration from the TOML file at PATH, for \
+                     example preduce.toml. Command line arguments take precedence over the \
+                     file's values, and reducers given on the command line replace the \
+                     file's reducers.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("command")
+                .long("command")
+                .takes_value(true)
+                .value_name("COMMAND")
+                .conflicts_with("resume")
+                .help(
+                    "Instead of a predicate script, run COMMAND on each candidate, and judge \
+                     it with the --expect-* options. COMMAND is split on whitespace, and {} is \
+                     replaced with the candidate's path. Without any --expect-* options, the \
+                     candidate is interesting if COMMAND exits 0.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("expect-exit-code")
+                .long("expect-exit-code")
+                .takes_value(true)
+                .value_name("CODE")
+                .requires("command")
+                .validator(|a| a.parse::<i32>().map(|_| ()).map_err(|e| format!("{}", e)))
+                .help("With --command, the candidate is interesting only if COMMAND exits CODE."),
+        )
+        .arg(
+            clap::Arg::with_name("expect-signal")
+                .long("expect-signal")
+                .takes_value(true)
+                .value_name("SIGNAL")
+                .requires("command")
+                .validator(|a| a.parse::<i32>().map(|_| ()).map_err(|e| format!("{}", e)))
+                .help(
+                    "With --command, the candidate is interesting only if COMMAND is killed by \
+                     the signal numbered SIGNAL.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("expect-stdout")
+                .long("expect-stdout")
+                .takes_value(true)
+                .value_name("REGEX")
+                .requires("command")
+                .help(
+                    "With --command, the candidate is interesting only if COMMAND's stdout \
+                     matches REGEX.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("expect-stderr")
+                .long("expect-stderr")
+                .takes_value(true)
+                .value_name("REGEX")
+                .requires("command")
+                .help(
+                    "With --command, the candidate is interesting only if COMMAND's stderr \
+                     matches REGEX.",
+                ),
         )
         .arg(
             clap::Arg::with_name("workers")
                 .short("w")
                 .long("workers")
+                .conflicts_with("resume")
                 .takes_value(true)
                 .value_name("NUM_WORKERS")
                 .validator(|a| {
@@ -58,43 +166,1310 @@ fn parse_args() -> clap::ArgMatches<'static> {
                      CPUs.",
                 ),
         )
+        .arg(
+            clap::Arg::with_name("predicate-timeout")
+                .long("predicate-timeout")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(|a| {
+                    let secs = a.parse::<f64>().map_err(|e| format!("{}", e))?;
+                    if secs > 0.0 {
+                        Ok(())
+                    } else {
+                        Err("SECONDS must be a number greater than 0".into())
+                    }
+                })
+                .help(
+                    "Kill the is-interesting predicate, and everything it spawned, if it runs \
+                     for longer than SECONDS on a candidate. Such candidates are treated as \
+                     not interesting.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("retry")
+                .long("retry")
+                .takes_value(true)
+                .value_name("RUNS")
+                .conflicts_with_all(&["resume", "majority"])
+                .validator(|a| {
+                    let runs = a.parse::<usize>().map_err(|e| format!("{}", e))?;
+                    if runs > 0 {
+                        Ok(())
+                    } else {
+                        Err("RUNS must be a number greater than 0".into())
+                    }
+                })
+                .help(
+                    "Run a flaky is-interesting predicate up to RUNS times on each candidate, \
+                     which is interesting if any run finds it interesting.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("majority")
+                .long("majority")
+                .takes_value(true)
+                .value_name("NEEDED/RUNS")
+                .conflicts_with("resume")
+                .validator(|a| parse_majority(&a).map(|_| ()))
+                .help(
+                    "Run a flaky is-interesting predicate up to RUNS times on each candidate, \
+                     which is interesting if NEEDED runs find it interesting.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("no-preflight")
+                .long("no-preflight")
+                .conflicts_with("resume")
+                .help(
+                    "Skip the pre-flight checks that an empty test case and a garbage test \
+                     case are not interesting.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("check-flakiness")
+                .long("check-flakiness")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("RUNS")
+                .validator(|a| a.parse::<usize>().map(|_| ()).map_err(|e| format!("{}", e)))
+                .help(
+                    "Before reducing, run the is-interesting predicate on the original test \
+                     case RUNS more times, and warn if it is flaky.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("sandbox")
+                .long("sandbox")
+                .conflicts_with("resume")
+                .help(
+                    "Run the is-interesting predicate in a private scratch directory for each \
+                     worker, with the candidate under the test case's file name. The \
+                     predicate is given the PREDUCE_WORKER_ID, PREDUCE_SCRATCH_DIR and \
+                     PREDUCE_ORIGINAL environment variables.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("aux-files")
+                .long("aux-files")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PATH")
+                .conflicts_with("resume")
+                .help(
+                    "Copy PATH into each worker's scratch directory. May be given more than \
+                     once. Implies --sandbox.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("symlink-aux-files")
+                .long("symlink-aux-files")
+                .conflicts_with("resume")
+                .help("Symlink the --aux-files into each worker's directory, instead of copying."),
+        )
+        .arg(
+            clap::Arg::with_name("limit-address-space")
+                .long("limit-address-space")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(|a| parse_bytes(&a).map(|_| ()))
+                .help(
+                    "Limit the virtual memory of each of the is-interesting predicate's \
+                     processes to BYTES, which may have a K, M or G suffix.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("limit-cpu")
+                .long("limit-cpu")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(|a| a.parse::<u64>().map(|_| ()).map_err(|e| format!("{}", e)))
+                .help(
+                    "Limit the CPU time of each of the is-interesting predicate's processes to \
+                     SECONDS.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("limit-file-size")
+                .long("limit-file-size")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("BYTES")
+                .validator(|a| parse_bytes(&a).map(|_| ()))
+                .help(
+                    "Limit the size of the files that the is-interesting predicate writes to \
+                     BYTES, which may have a K, M or G suffix.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("limit-processes")
+                .long("limit-processes")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("NUM")
+                .validator(|a| a.parse::<u64>().map(|_| ()).map_err(|e| format!("{}", e)))
+                .help(
+                    "Limit the number of processes that the user may have running while the \
+                     is-interesting predicate runs to NUM.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("oracle")
+                .long("oracle")
+                .takes_value(true)
+                .value_name("STRATEGY")
+                .possible_values(preduce::oracle::Strategy::NAMES)
+                .conflicts_with("resume")
+                .help(
+                    "How to prioritize candidates: 'default' combines several heuristics, \
+                     'bandit' learns which reducers remove the most bytes per second, \
+                     'creduce' follows C-Reduce's pass ordering, 'biggest-first' tries the \
+                     candidates that would remove the most bytes first, and 'cost-aware' tries \
+                     the candidates expected to remove the most bytes per second first.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("cache-dir")
+                .long("cache-dir")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("DIR")
+                .help(
+                    "Cache the is-interesting predicate's results in DIR, and reuse results \
+                     cached there by previous runs with the same predicate.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("capture-output")
+                .long("capture-output")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("DIR")
+                .help(
+                    "Save the is-interesting predicate's stdout and stderr for each candidate \
+                     it judges in DIR, and link to them from the log.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("keep-output")
+                .long("keep-output")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("POLICY")
+                .requires("capture-output")
+                .validator(|a| a.parse::<preduce::capture::Retention>().map(|_| ()))
+                .help(
+                    "Which captured outputs to keep: 'interesting' for only the candidates \
+                     judged interesting, a number N for the last N candidates judged, or \
+                     'all'. Defaults to 'all'.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .value_name("PATH")
+                .conflicts_with("resume")
+                .help(
+                    "Write the reduced test case to PATH, instead of overwriting the initial \
+                     test case in place. With -, the reduced test case is written to stdout \
+                     once the reduction is done, and progress is printed to stderr instead.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("log-file")
+                .long("log-file")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Write the detailed log to PATH. Defaults to preduce.log."),
+        )
+        .arg(
+            clap::Arg::with_name("log-format")
+                .long("log-format")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(&["text", "jsonl"])
+                .help(
+                    "The format of the log file: human-readable text (the default), or one \
+                     JSON object per line.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("no-backup")
+                .long("no-backup")
+                .conflicts_with("resume")
+                .help(
+                    "Do not back up the initial test case to <test case>.orig before \
+                     overwriting it in place.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("listen")
+                .long("listen")
+                .takes_value(true)
+                .value_name("ADDR")
+                .conflicts_with_all(&["command", "resume"])
+                .help(
+                    "Accept remote workers on ADDR, for example 0.0.0.0:4000. Start them with \
+                     `preduce-worker ADDR PREDICATE`, where PREDICATE has the same file name \
+                     as the is-interesting predicate script.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("status")
+                .short("s")
+                .long("status")
+                .help(
+                    "Show a live status view while reducing. Prints a one-line summary every \
+                     few seconds instead when stdout is not a terminal.",
+                ),
+        )
         .arg(
             clap::Arg::with_name("print-histograms")
                 .short("m")
                 .long("print-histograms")
                 .help("Print histograms when finished."),
         )
+        .arg(
+            clap::Arg::with_name("checkpoint-dir")
+                .long("checkpoint-dir")
+                .takes_value(true)
+                .value_name("DIR")
+                .conflicts_with("resume")
+                .help(
+                    "Regularly save checkpoints into DIR, so that an interrupted reduction \
+                     can be continued with --resume.",
+                ),
+        )
+        .arg(
+            clap::Arg::with_name("checkpoint-interval")
+                .long("checkpoint-interval")
+                .conflicts_with("resume")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .validator(|a| {
+                    let secs = a.parse::<u64>().map_err(|e| format!("{}", e))?;
+                    if secs > 0 {
+                        Ok(())
+                    } else {
+                        Err("SECONDS must be a number greater than 0".into())
+                    }
+                })
+                .help("Save a checkpoint every SECONDS. Defaults to 60."),
+        )
+        .arg(
+            clap::Arg::with_name("resume")
+                .long("resume")
+                .takes_value(true)
+                .value_name("DIR")
+                .help(
+                    "Resume the interrupted reduction that was checkpointing into DIR. The \
+                     test case, predicate, reducers, and the settings that affect the \
+                     reduction are taken from the checkpoint.",
+                ),
+        )
         .get_matches()
 }
 
+/// Parse a `NEEDED/RUNS` argument to `--majority`.
+fn parse_majority(arg: &str) -> Result<(usize, usize), String> {
+    let mut parts = arg.splitn(2, '/');
+    let needed = parts.next().unwrap();
+    let runs = parts
+        .next()
+        .ok_or_else(|| format!("'{}' is not of the form NEEDED/RUNS", arg))?;
+    let needed = needed.parse::<usize>().map_err(|e| format!("{}", e))?;
+    let runs = runs.parse::<usize>().map_err(|e| format!("{}", e))?;
+    if needed == 0 || needed > runs {
+        return Err("NEEDED must be greater than 0, and no more than RUNS".into());
+    }
+    Ok((needed, runs))
+}
+
+/// Parse a number of bytes, with an optional `K`, `M` or `G` suffix.
+fn parse_bytes(arg: &str) -> Result<u64, String> {
+    let (digits, multiplier) = match arg.chars().last() {
+        Some('K') | Some('k') => (&arg[..arg.len() - 1], 1 << 10),
+        Some('M') | Some('m') => (&arg[..arg.len() - 1], 1 << 20),
+        Some('G') | Some('g') => (&arg[..arg.len() - 1], 1 << 30),
+        _ => (arg, 1),
+    };
+    let num = digits.parse::<u64>().map_err(|e| format!("{}", e))?;
+    num.checked_mul(multiplier)
+        .ok_or_else(|| format!("{} is too large", arg))
+}
+
+/// The test case argument that means "read the test case from stdin".
+const STDIN_TEST_CASE: &'static str = "-";
+
+/// The output argument that means "write the reduced test case to stdout".
+const STDOUT_OUTPUT: &'static str = "-";
+
+/// The file within a checkpoint directory that a test case read from stdin is
+/// saved to, so that the reduction can be resumed.
+const STDIN_FILE_NAME: &'static str = "stdin";
+
+/// The file that a reduced test case is spooled to before it is written to
+/// stdout.
+const STDOUT_OUTPUT_FILE_NAME: &'static str = "output";
+
+/// The file within a checkpoint directory that the invocation is saved to.
+const INVOCATION_FILE_NAME: &'static str = "invocation.json";
+
+/// The parts of our command line invocation that are needed to resume a
+/// checkpointed reduction, and the settings that it must be resumed with.
+#[derive(Deserialize, Serialize)]
+struct Invocation {
+    test_case: path::PathBuf,
+    predicate: Option<path::PathBuf>,
+    #[serde(default)]
+    command: Option<CommandPredicate>,
+    reducers: Vec<path::PathBuf>,
+    #[serde(default)]
+    phases: Vec<PhaseInvocation>,
+    #[serde(default)]
+    reducer_weights: BTreeMap<path::PathBuf, f64>,
+    #[serde(default)]
+    output: Option<path::PathBuf>,
+    #[serde(default)]
+    sandbox: bool,
+    #[serde(default)]
+    aux_files: Vec<path::PathBuf>,
+    #[serde(default)]
+    symlink_aux_files: bool,
+    #[serde(default)]
+    retry: Option<usize>,
+    #[serde(default)]
+    majority: Option<(usize, usize)>,
+    #[serde(default)]
+    oracle: Option<String>,
+    #[serde(default)]
+    workers: Option<usize>,
+    #[serde(default)]
+    predicate_timeout: Option<f64>,
+    #[serde(default)]
+    limit_address_space: Option<u64>,
+    #[serde(default)]
+    limit_cpu: Option<u64>,
+    #[serde(default)]
+    limit_file_size: Option<u64>,
+    #[serde(default)]
+    limit_processes: Option<u64>,
+    #[serde(default)]
+    check_flakiness: Option<usize>,
+    #[serde(default)]
+    preflight: Option<bool>,
+    #[serde(default)]
+    backup: Option<bool>,
+    #[serde(default)]
+    log_file: Option<path::PathBuf>,
+    #[serde(default)]
+    log_format: Option<String>,
+    #[serde(default)]
+    cache_dir: Option<path::PathBuf>,
+    #[serde(default)]
+    capture_output: Option<path::PathBuf>,
+    #[serde(default)]
+    keep_output: Option<String>,
+    #[serde(default)]
+    checkpoint_interval: Option<u64>,
+    #[serde(default)]
+    listen: Option<String>,
+}
+
+/// A reduction phase given with `--phase`.
+#[derive(Deserialize, Serialize)]
+struct PhaseInvocation {
+    name: String,
+    reducers: Vec<path::PathBuf>,
+    repeat_until_fixpoint: bool,
+}
+
+impl PhaseInvocation {
+    /// Parse a `NAME:REDUCER,...[:fixpoint]` phase argument.
+    fn parse(arg: &str) -> Result<PhaseInvocation, String> {
+        let (arg, repeat_until_fixpoint) = if arg.ends_with(":fixpoint") {
+            (&arg[..arg.len() - ":fixpoint".len()], true)
+        } else {
+            (arg, false)
+        };
+
+        let mut parts = arg.splitn(2, ':');
+        let name = parts.next().unwrap();
+        let reducers = parts.next().unwrap_or("");
+        if name.is_empty() {
+            return Err(format!("the phase '{}' has no name", arg));
+        }
+
+        let reducers: Vec<_> = reducers
+            .split(',')
+            .filter(|r| !r.is_empty())
+            .map(path::PathBuf::from)
+            .collect();
+        if reducers.is_empty() {
+            return Err(format!("the phase '{}' has no reducers", name));
+        }
+
+        Ok(PhaseInvocation {
+            name: name.to_string(),
+            reducers: reducers,
+            repeat_until_fixpoint: repeat_until_fixpoint,
+        })
+    }
+}
+
+/// A reduction's configuration, read from the TOML file given with `--config`.
+///
+/// Relative paths within the file are relative to the file's directory.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    test_case: Option<path::PathBuf>,
+    predicate: Option<ConfigPredicate>,
+    #[serde(default)]
+    reducers: Vec<ConfigReducer>,
+    #[serde(default)]
+    phases: Vec<ConfigPhase>,
+    workers: Option<usize>,
+    predicate_timeout: Option<f64>,
+    output: Option<path::PathBuf>,
+    log_file: Option<path::PathBuf>,
+    log_format: Option<String>,
+    cache_dir: Option<path::PathBuf>,
+    capture_output: Option<path::PathBuf>,
+    keep_output: Option<String>,
+    checkpoint_dir: Option<path::PathBuf>,
+    checkpoint_interval: Option<u64>,
+    #[serde(default)]
+    sandbox: bool,
+    #[serde(default)]
+    aux_files: Vec<path::PathBuf>,
+    #[serde(default)]
+    symlink_aux_files: bool,
+    limit_address_space: Option<u64>,
+    limit_cpu: Option<u64>,
+    limit_file_size: Option<u64>,
+    limit_processes: Option<u64>,
+    retry: Option<usize>,
+    majority: Option<String>,
+    check_flakiness: Option<usize>,
+    preflight: Option<bool>,
+    oracle: Option<String>,
+}
+
+/// The `[predicate]` table: either a predicate script, or a command with the
+/// same expectations as the `--expect-*` options.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigPredicate {
+    script: Option<path::PathBuf>,
+    command: Option<String>,
+    expect_exit_code: Option<i32>,
+    expect_signal: Option<i32>,
+    expect_stdout: Option<String>,
+    expect_stderr: Option<String>,
+}
+
+/// A `[[reducers]]` entry.
+///
+/// There are deliberately no per-reducer arguments or timeouts. A reducer is
+/// known by its script's file name: that is what its candidates' provenance,
+/// weight, statistics, and checkpointed state are keyed by, so one script run
+/// with different arguments would be indistinguishable. Wrap the script in
+/// another script instead. And a reducer script is a long-lived process that
+/// generates candidates on demand, so a timeout would have to kill it and lose
+/// its place; it is the is-interesting predicate that `predicate_timeout`
+/// bounds.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigReducer {
+    path: path::PathBuf,
+    weight: Option<f64>,
+    phase: Option<String>,
+}
+
+/// A `[[phases]]` entry. Phases run in the order they are declared.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigPhase {
+    name: String,
+    #[serde(default)]
+    repeat_until_fixpoint: bool,
+}
+
+impl Config {
+    fn read(config_path: &path::Path) -> error::Result<Config> {
+        let invalid = |details: String| {
+            error::Error::InvalidConfig(format!("{}: {}", config_path.display(), details))
+        };
+
+        let mut contents = String::new();
+        fs::File::open(config_path)?.read_to_string(&mut contents)?;
+        let mut config: Config = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+
+        // Make relative paths relative to the configuration file, rather than
+        // wherever we happen to be running from.
+        let dir = config_path.parent().unwrap_or(path::Path::new("."));
+        {
+            let mut paths: Vec<&mut path::PathBuf> = vec![];
+            paths.extend(config.test_case.as_mut());
+            paths.extend(config.output.as_mut());
+            paths.extend(config.log_file.as_mut());
+            paths.extend(config.cache_dir.as_mut());
+            paths.extend(config.capture_output.as_mut());
+            paths.extend(config.checkpoint_dir.as_mut());
+            paths.extend(config.aux_files.iter_mut());
+            paths.extend(config.predicate.as_mut().and_then(|p| p.script.as_mut()));
+            paths.extend(
+                config
+                    .reducers
+                    .iter_mut()
+                    .map(|r| &mut r.path)
+                    .filter(|p| !is_builtin_reducer(p) || dir.join(&**p).exists()),
+            );
+            for p in paths {
+                *p = dir.join(&*p);
+            }
+        }
+
+        if let Some(ref predicate) = config.predicate {
+            if predicate.script.is_some() == predicate.command.is_some() {
+                return Err(invalid(
+                    "the predicate must have exactly one of `script` or `command`".into(),
+                ));
+            }
+            if predicate.script.is_some() &&
+                (predicate.expect_exit_code.is_some() || predicate.expect_signal.is_some() ||
+                    predicate.expect_stdout.is_some() ||
+                    predicate.expect_stderr.is_some())
+            {
+                return Err(invalid(
+                    "the `expect_*` keys only apply to `command` predicates".into(),
+                ));
+            }
+        }
+        if config.workers == Some(0) {
+            return Err(invalid("`workers` must be greater than 0".into()));
+        }
+        if config.checkpoint_interval == Some(0) {
+            return Err(invalid("`checkpoint_interval` must be greater than 0".into()));
+        }
+        if config.predicate_timeout.map_or(false, |t| !(t > 0.0)) {
+            return Err(invalid("`predicate_timeout` must be greater than 0".into()));
+        }
+        match config.log_format.as_ref().map(|f| &f[..]) {
+            None | Some("text") | Some("jsonl") => {}
+            Some(f) => return Err(invalid(format!("unknown log format '{}'", f))),
+        }
+        if let Some(ref oracle) = config.oracle {
+            oracle.parse::<preduce::oracle::Strategy>().map_err(&invalid)?;
+        }
+
+        for reducer in &config.reducers {
+            if reducer.weight.map_or(false, |w| !(w >= 0.0 && w.is_finite())) {
+                return Err(invalid(format!(
+                    "the weight of '{}' must be a non-negative number",
+                    reducer.path.display()
+                )));
+            }
+            match reducer.phase {
+                None if !config.phases.is_empty() => {
+                    return Err(invalid(format!(
+                        "'{}' must have a `phase` when phases are declared",
+                        reducer.path.display()
+                    )))
+                }
+                Some(ref phase) if !config.phases.iter().any(|p| p.name == *phase) => {
+                    return Err(invalid(format!("there is no phase named '{}'", phase)))
+                }
+                _ => {}
+            }
+        }
+        for phase in &config.phases {
+            if !config.reducers.iter().any(|r| r.phase.as_ref() == Some(&phase.name)) {
+                return Err(invalid(format!("the phase '{}' has no reducers", phase.name)));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The reducers, when they are not divided into phases.
+    fn reducers(&self) -> Vec<path::PathBuf> {
+        if !self.phases.is_empty() {
+            return vec![];
+        }
+        self.reducers.iter().map(|r| r.path.clone()).collect()
+    }
+
+    fn phases(&self) -> Vec<PhaseInvocation> {
+        self.phases
+            .iter()
+            .map(|phase| {
+                PhaseInvocation {
+                    name: phase.name.clone(),
+                    reducers: self.reducers
+                        .iter()
+                        .filter(|r| r.phase.as_ref() == Some(&phase.name))
+                        .map(|r| r.path.clone())
+                        .collect(),
+                    repeat_until_fixpoint: phase.repeat_until_fixpoint,
+                }
+            })
+            .collect()
+    }
+}
+
+impl ConfigPredicate {
+    fn command(&self) -> Option<CommandPredicate> {
+        self.command.as_ref().map(|command| {
+            CommandPredicate {
+                argv: command.split_whitespace().map(|s| s.to_string()).collect(),
+                exit_code: self.expect_exit_code,
+                signal: self.expect_signal,
+                stdout: self.expect_stdout.clone(),
+                stderr: self.expect_stderr.clone(),
+            }
+        })
+    }
+}
+
+/// A predicate given with `--command` and the `--expect-*` options.
+#[derive(Deserialize, Serialize)]
+struct CommandPredicate {
+    argv: Vec<String>,
+    exit_code: Option<i32>,
+    signal: Option<i32>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+}
+
+impl Invocation {
+    fn from_args(args: &clap::ArgMatches, config: &Config) -> error::Result<Invocation> {
+        let test_case = match args.value_of("test-case") {
+            Some(test_case) => path::PathBuf::from(test_case),
+            None => match config.test_case {
+                Some(ref test_case) => test_case.clone(),
+                None => clap::Error::with_description(
+                    "The test case must be given on the command line or in the configuration \
+                     file",
+                    clap::ErrorKind::MissingRequiredArgument,
+                ).exit(),
+            },
+        };
+        let output = args.value_of("output")
+            .map(path::PathBuf::from)
+            .or_else(|| config.output.clone());
+        if test_case == path::Path::new(STDIN_TEST_CASE) && output.is_none() {
+            clap::Error::with_description(
+                "--output is required when reading the test case from stdin",
+                clap::ErrorKind::MissingRequiredArgument,
+            ).exit();
+        }
+
+        // The test case read from stdin is written to a temporary file later.
+        let test_case = if test_case == path::Path::new(STDIN_TEST_CASE) {
+            test_case
+        } else {
+            test_case.canonicalize()?
+        };
+
+        // The output need not exist yet, so it can't be canonicalized, but it
+        // must still be absolute to be resumed from another directory.
+        let output = match output {
+            None => None,
+            Some(ref o) if o == path::Path::new(STDOUT_OUTPUT) => Some(o.clone()),
+            Some(o) => Some(env::current_dir()?.join(o)),
+        };
+
+        let command = args.value_of("command").map(|command| {
+            CommandPredicate {
+                argv: command.split_whitespace().map(|s| s.to_string()).collect(),
+                exit_code: args.value_of("expect-exit-code").map(|c| c.parse().unwrap()),
+                signal: args.value_of("expect-signal").map(|s| s.parse().unwrap()),
+                stdout: args.value_of("expect-stdout").map(|s| s.to_string()),
+                stderr: args.value_of("expect-stderr").map(|s| s.to_string()),
+            }
+        });
+
+        // With `--command`, there is no predicate script, so every positional
+        // argument after the test case is a reducer. Without either, the
+        // predicate comes from the configuration file.
+        let (predicate, command, reducers): (_, _, Vec<_>) = match command {
+            Some(command) => (
+                None,
+                Some(command),
+                args.values_of("predicate")
+                    .into_iter()
+                    .flat_map(|p| p)
+                    .chain(args.values_of("reducer").into_iter().flat_map(|r| r))
+                    .map(path::PathBuf::from)
+                    .collect(),
+            ),
+            None => match args.value_of("predicate") {
+                Some(predicate) => (
+                    Some(path::PathBuf::from(predicate)),
+                    None,
+                    args.values_of("reducer")
+                        .into_iter()
+                        .flat_map(|r| r)
+                        .map(path::PathBuf::from)
+                        .collect(),
+                ),
+                None => match config.predicate {
+                    Some(ref p) => (p.script.clone(), p.command(), vec![]),
+                    None => clap::Error::with_description(
+                        "The predicate must be given on the command line or in the \
+                         configuration file",
+                        clap::ErrorKind::MissingRequiredArgument,
+                    ).exit(),
+                },
+            },
+        };
+
+        if command.as_ref().map_or(false, |c| c.argv.is_empty()) {
+            clap::Error::with_description(
+                "--command must not be empty",
+                clap::ErrorKind::EmptyValue,
+            ).exit();
+        }
+        let phases = args.values_of("phase")
+            .into_iter()
+            .flat_map(|p| p)
+            .map(|p| PhaseInvocation::parse(p).expect("clap validates phases"))
+            .collect::<Vec<_>>();
+        if !phases.is_empty() && !reducers.is_empty() {
+            clap::Error::with_description(
+                "When reduction phases are given with --phase, all reducers must be given \
+                 with --phase",
+                clap::ErrorKind::ArgumentConflict,
+            ).exit();
+        }
+
+        // Reducers given on the command line replace all of the configuration
+        // file's reducers.
+        let (reducers, phases) = if reducers.is_empty() && phases.is_empty() {
+            (config.reducers(), config.phases())
+        } else {
+            (reducers, phases)
+        };
+        if reducers.is_empty() && phases.is_empty() {
+            clap::Error::with_description(
+                "There must be at least one reducer",
+                clap::ErrorKind::MissingRequiredArgument,
+            ).exit();
+        }
+
+        let predicate = match predicate {
+            None => None,
+            Some(p) => Some(p.canonicalize()?),
+        };
+        let reducers = reducers
+            .into_iter()
+            .map(resolve_reducer)
+            .collect::<error::Result<Vec<_>>>()?;
+        let phases = phases
+            .into_iter()
+            .map(|p| {
+                Ok(PhaseInvocation {
+                    reducers: p.reducers
+                        .into_iter()
+                        .map(resolve_reducer)
+                        .collect::<error::Result<Vec<_>>>()?,
+                    ..p
+                })
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        let aux_files = match args.values_of("aux-files") {
+            Some(files) => files.map(path::PathBuf::from).collect(),
+            None => config.aux_files.clone(),
+        };
+        let aux_files = aux_files
+            .into_iter()
+            .map(|f| Ok(f.canonicalize()?))
+            .collect::<error::Result<Vec<_>>>()?;
+        let retry = match args.value_of("retry") {
+            Some(runs) => Some(runs.parse().unwrap()),
+            None if args.is_present("majority") => None,
+            None => config.retry,
+        };
+        let majority = match args.value_of("majority") {
+            Some(arg) => Some(parse_majority(arg).unwrap()),
+            None if retry.is_some() => None,
+            None => match config.majority {
+                Some(ref arg) => Some(parse_majority(arg).map_err(error::Error::InvalidConfig)?),
+                None => None,
+            },
+        };
+        if retry.is_some() && majority.is_some() {
+            clap::Error::with_description(
+                "Only one of retry and majority may be given",
+                clap::ErrorKind::ArgumentConflict,
+            ).exit();
+        }
+        let reducer_weights = config
+            .reducers
+            .iter()
+            .filter_map(|r| r.weight.map(|w| (r.path.clone(), w)))
+            .map(|(r, w)| Ok((resolve_reducer(r)?, w)))
+            .collect::<error::Result<BTreeMap<_, _>>>()?;
+        Ok(Invocation {
+            test_case: test_case,
+            predicate: predicate,
+            command: command,
+            reducers: reducers,
+            phases: phases,
+            reducer_weights: reducer_weights,
+            output: output,
+            sandbox: args.is_present("sandbox") || config.sandbox || !aux_files.is_empty(),
+            aux_files: aux_files,
+            symlink_aux_files: args.is_present("symlink-aux-files") || config.symlink_aux_files,
+            retry: retry,
+            majority: majority,
+            oracle: args.value_of("oracle")
+                .map(String::from)
+                .or_else(|| config.oracle.clone()),
+            workers: args.value_of("workers")
+                .map(|n| n.parse().unwrap())
+                .or(config.workers),
+            predicate_timeout: args.value_of("predicate-timeout")
+                .map(|secs| secs.parse().unwrap())
+                .or(config.predicate_timeout),
+            limit_address_space: args.value_of("limit-address-space")
+                .map(|b| parse_bytes(b).unwrap())
+                .or(config.limit_address_space),
+            limit_cpu: args.value_of("limit-cpu")
+                .map(|s| s.parse().unwrap())
+                .or(config.limit_cpu),
+            limit_file_size: args.value_of("limit-file-size")
+                .map(|b| parse_bytes(b).unwrap())
+                .or(config.limit_file_size),
+            limit_processes: args.value_of("limit-processes")
+                .map(|n| n.parse().unwrap())
+                .or(config.limit_processes),
+            check_flakiness: args.value_of("check-flakiness")
+                .map(|n| n.parse().unwrap())
+                .or(config.check_flakiness),
+            preflight: if args.is_present("no-preflight") {
+                Some(false)
+            } else {
+                config.preflight
+            },
+            backup: if args.is_present("no-backup") {
+                Some(false)
+            } else {
+                None
+            },
+            log_file: absolute(
+                args.value_of("log-file")
+                    .map(path::PathBuf::from)
+                    .or_else(|| config.log_file.clone()),
+            )?,
+            log_format: args.value_of("log-format")
+                .map(String::from)
+                .or_else(|| config.log_format.clone()),
+            cache_dir: absolute(
+                args.value_of("cache-dir")
+                    .map(path::PathBuf::from)
+                    .or_else(|| config.cache_dir.clone()),
+            )?,
+            capture_output: absolute(
+                args.value_of("capture-output")
+                    .map(path::PathBuf::from)
+                    .or_else(|| config.capture_output.clone()),
+            )?,
+            keep_output: args.value_of("keep-output")
+                .map(String::from)
+                .or_else(|| config.keep_output.clone()),
+            checkpoint_interval: args.value_of("checkpoint-interval")
+                .map(|secs| secs.parse().unwrap())
+                .or(config.checkpoint_interval),
+            listen: args.value_of("listen").map(String::from),
+        })
+    }
+
+    /// Get the predicate timeout.
+    fn timeout(&self) -> Option<time::Duration> {
+        self.predicate_timeout
+            .map(|secs| time::Duration::new(secs.trunc() as u64, (secs.fract() * 1e9) as u32))
+    }
+
+    /// Get the predicate's resource limits.
+    fn limits(&self) -> interesting::ResourceLimits {
+        interesting::ResourceLimits {
+            address_space: self.limit_address_space,
+            cpu_seconds: self.limit_cpu,
+            file_size: self.limit_file_size,
+            processes: self.limit_processes,
+        }
+    }
+
+    fn predicate(&self, timeout: Option<time::Duration>) -> error::Result<Box<traits::IsInteresting>> {
+        let predicate = self.single_run_predicate(timeout)?;
+        Ok(match (self.retry, self.majority) {
+            (Some(runs), _) => Box::new(interesting::Retry::new(predicate, runs)),
+            (None, Some((needed, runs))) => {
+                Box::new(interesting::Majority::new(predicate, needed, runs))
+            }
+            (None, None) => predicate,
+        })
+    }
+
+    fn single_run_predicate(
+        &self,
+        timeout: Option<time::Duration>,
+    ) -> error::Result<Box<traits::IsInteresting>> {
+        if let Some(ref command) = self.command {
+            let mut predicate = interesting::Command::new(command.argv.iter().cloned());
+            predicate.set_expected_exit_code(command.exit_code);
+            predicate.set_expected_signal(command.signal);
+            if let Some(ref stdout) = command.stdout {
+                predicate.set_expected_stdout(stdout)?;
+            }
+            if let Some(ref stderr) = command.stderr {
+                predicate.set_expected_stderr(stderr)?;
+            }
+            predicate.set_timeout(timeout);
+            return Ok(Box::new(predicate));
+        }
+
+        let script = self.predicate
+            .as_ref()
+            .expect("there is a predicate script when there is no command");
+        let mut predicate = interesting::Script::new(script)?;
+        predicate.set_timeout(timeout);
+        Ok(Box::new(predicate))
+    }
+
+    fn read(dir: &path::Path) -> error::Result<Invocation> {
+        let file = fs::File::open(dir.join(INVOCATION_FILE_NAME))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    fn write(&self, dir: &path::Path) -> error::Result<()> {
+        fs::create_dir_all(dir)?;
+        let mut file = fs::File::create(dir.join(INVOCATION_FILE_NAME))?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        Ok(())
+    }
+}
+
+/// Make the given path absolute, so that it can be resumed from another
+/// directory.
+fn absolute(path: Option<path::PathBuf>) -> error::Result<Option<path::PathBuf>> {
+    Ok(match path {
+        None => None,
+        Some(path) => Some(env::current_dir()?.join(path)),
+    })
+}
+
+/// Is the given reducer argument the name of a built-in reducer?
+fn is_builtin_reducer(reducer: &path::Path) -> bool {
+    reducer
+        .to_str()
+        .map_or(false, |r| reducers::BUILTINS.contains(&r))
+}
+
+/// Resolve a reducer argument to either the canonical path of a reducer script,
+/// or the name of a built-in reducer. A reducer script takes precedence over a
+/// built-in reducer of the same name.
+fn resolve_reducer(reducer: path::PathBuf) -> error::Result<path::PathBuf> {
+    if is_builtin_reducer(&reducer) && !reducer.exists() {
+        return Ok(reducer);
+    }
+    Ok(reducer.canonicalize()?)
+}
+
 fn try_main() -> error::Result<()> {
     let args = parse_args();
 
-    let predicate = args.value_of("predicate").unwrap();
-    let predicate = interesting::Script::new(predicate)?;
+    let config = match args.value_of("config") {
+        Some(config) => Config::read(path::Path::new(config))?,
+        None => Config::default(),
+    };
 
-    let reducers = args.values_of("reducer")
-        .unwrap()
-        .map(|script| {
-            let reducer = reducers::Script::new(script)?;
-            let reducer = reducers::Fuse::new(reducer);
-            let reducer = Box::new(reducer) as Box<traits::Reducer>;
-            Ok(reducer)
-        })
-        .collect::<error::Result<Vec<_>>>()?;
+    let mut invocation = match args.value_of("resume") {
+        Some(dir) => Invocation::read(path::Path::new(dir))?,
+        None => Invocation::from_args(&args, &config)?,
+    };
+
+    let checkpoint_dir = args.value_of("checkpoint-dir")
+        .map(path::PathBuf::from)
+        .or_else(|| config.checkpoint_dir.clone());
+
+    // Save the test case read from stdin into the checkpoint directory, so
+    // that it is still there to resume from. Without one, keep it alive in a
+    // temporary directory until we're done.
+    let _stdin_dir = if invocation.test_case == path::Path::new(STDIN_TEST_CASE) {
+        let (dir, temp_dir) = match checkpoint_dir {
+            Some(ref dir) => {
+                fs::create_dir_all(dir)?;
+                (dir.clone(), None)
+            }
+            None => {
+                let temp_dir = tempdir::TempDir::new("preduce-stdin")?;
+                (temp_dir.path().to_path_buf(), Some(temp_dir))
+            }
+        };
+        // Like the output, it must be absolute to be resumed from another
+        // directory.
+        invocation.test_case = env::current_dir()?.join(dir).join(STDIN_FILE_NAME);
+        let stdin = io::stdin();
+        let mut file = fs::File::create(&invocation.test_case)?;
+        io::copy(&mut stdin.lock(), &mut file)?;
+        temp_dir
+    } else {
+        None
+    };
+
+    let predicate = invocation.predicate(invocation.timeout())?;
 
-    let test_case = args.value_of("test-case").unwrap();
+    // Reducer scripts and built-in reducers only know how to reduce a single
+    // file, so when the test case is a directory, apply each of them to its
+    // files one at a time, and also try removing whole files.
+    let is_dir = invocation.test_case.is_dir();
+    let mut weights = vec![];
+    let mut make_reducers = |scripts: &[path::PathBuf]| -> error::Result<Vec<_>> {
+        let mut reducers = scripts
+            .iter()
+            .map(|script| {
+                let reducer = match script.to_str().and_then(reducers::builtin) {
+                    Some(builtin) if !script.is_absolute() => builtin,
+                    _ => Box::new(reducers::Script::new(script)?) as Box<traits::Reducer>,
+                };
+                if let Some(&weight) = invocation.reducer_weights.get(script) {
+                    weights.push((traits::Reducer::name(&reducer).to_string(), weight));
+                }
+                let reducer = if is_dir {
+                    Box::new(reducers::Fuse::new(reducers::Members::new(reducer)))
+                        as Box<traits::Reducer>
+                } else {
+                    Box::new(reducers::Fuse::new(reducer)) as Box<traits::Reducer>
+                };
+                Ok(reducer)
+            })
+            .collect::<error::Result<Vec<_>>>()?;
 
-    let mut options = preduce::Options::new(predicate, reducers, test_case);
+        if is_dir {
+            reducers.push(Box::new(reducers::Fuse::new(reducers::RemoveFiles::new())));
+        }
+        Ok(reducers)
+    };
 
-    if let Some(num_workers) = args.value_of("workers") {
-        let num_workers = num_workers.parse::<usize>().unwrap();
+    let mut options = if invocation.phases.is_empty() {
+        let reducers = make_reducers(&invocation.reducers)?;
+        preduce::Options::new(predicate, reducers, &invocation.test_case)
+    } else {
+        let phases = invocation
+            .phases
+            .iter()
+            .map(|p| {
+                Ok(preduce::Phase::new(p.name.clone(), make_reducers(&p.reducers)?)
+                    .repeat_until_fixpoint(p.repeat_until_fixpoint))
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        preduce::Options::with_phases(predicate, phases, &invocation.test_case)
+    };
+
+    for (name, weight) in weights {
+        options = options.reducer_weight(name, weight);
+    }
+
+    if let Some(ref oracle) = invocation.oracle {
+        let strategy = oracle.parse().map_err(error::Error::InvalidConfig)?;
+        options = options.oracle_strategy(strategy);
+    }
+
+    if let Some(num_workers) = invocation.workers {
         options = options.workers(num_workers);
     }
 
+    if let Some(runs) = invocation.check_flakiness {
+        options = options.flakiness_check(runs);
+    }
+
+    // A reduced test case that goes to stdout is spooled into a temporary
+    // file, and only written out once the reduction is done.
+    let stdout_output = match invocation.output {
+        Some(ref output) if output == path::Path::new(STDOUT_OUTPUT) => {
+            if invocation.test_case.is_dir() {
+                clap::Error::with_description(
+                    "--output - requires a test case that is a single file",
+                    clap::ErrorKind::InvalidValue,
+                ).exit();
+            }
+            if args.is_present("status") {
+                clap::Error::with_description(
+                    "--output - can't be used with --status, which draws on stdout",
+                    clap::ErrorKind::ArgumentConflict,
+                ).exit();
+            }
+            let dir = tempdir::TempDir::new("preduce-output")?;
+            options = options.output(dir.path().join(STDOUT_OUTPUT_FILE_NAME));
+            Some(dir)
+        }
+        Some(ref output) => {
+            options = options.output(output);
+            None
+        }
+        None => None,
+    };
+
+    if let Some(ref log_file) = invocation.log_file {
+        options = options.log_file(log_file);
+    }
+
+    match invocation.log_format.as_ref().map(|f| &f[..]) {
+        Some("jsonl") => options = options.log_format(preduce::LogFormat::JsonLines),
+        Some("text") | None => {}
+        Some(_) => unreachable!("the log format is validated when it is parsed"),
+    }
+
+    let limits = invocation.limits();
+    if limits != interesting::ResourceLimits::default() {
+        options = options.resource_limits(limits);
+    }
+
+    if invocation.sandbox {
+        let mode = if invocation.symlink_aux_files {
+            sandbox::AuxFileMode::Symlink
+        } else {
+            sandbox::AuxFileMode::Copy
+        };
+        let sandbox = invocation
+            .aux_files
+            .iter()
+            .fold(sandbox::Sandbox::new().aux_file_mode(mode), |s, f| s.aux_file(f));
+        options = options.sandbox(sandbox);
+    }
+
+    options = options.preflight(invocation.preflight.unwrap_or(true));
+
+    if let Some(backup) = invocation.backup {
+        options = options.backup(backup);
+    }
+
+    if let Some(ref addr) = invocation.listen {
+        let name = invocation
+            .predicate
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .map(|n| n.to_string());
+        match name {
+            Some(name) => options = options.listen(addr, name),
+            None => clap::Error::with_description(
+                "--listen requires a predicate script",
+                clap::ErrorKind::ArgumentConflict,
+            ).exit(),
+        }
+    }
+
+    if args.is_present("status") {
+        options = options.status(true);
+    }
+
     if args.is_present("print-histograms") {
         options = options.print_histograms(true);
     }
 
-    options.run()
+    if let Some(dir) = checkpoint_dir {
+        invocation.write(&dir)?;
+        options = options.checkpoint_dir(dir);
+    }
+
+    if let Some(secs) = invocation.checkpoint_interval {
+        options = options.checkpoint_interval(time::Duration::from_secs(secs));
+    }
+
+    if let Some(ref dir) = invocation.cache_dir {
+        options = options.cache_dir(dir);
+    }
+
+    if let Some(ref dir) = invocation.capture_output {
+        let retention = match invocation.keep_output {
+            Some(ref arg) => arg.parse().map_err(error::Error::InvalidConfig)?,
+            None => preduce::capture::Retention::default(),
+        };
+        options = options.capture_output(dir, retention);
+    }
+
+    if let Some(dir) = args.value_of("resume") {
+        options = options.resume(dir);
+    }
+
+    match stdout_output {
+        None => {
+            options.run()?;
+        }
+        Some(dir) => {
+            {
+                let _redirect = StdoutToStderr::new()?;
+                options.run()?;
+            }
+            let mut reduced = fs::File::open(dir.path().join(STDOUT_OUTPUT_FILE_NAME))?;
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            io::copy(&mut reduced, &mut stdout)?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Sends everything written to stdout to stderr instead, until dropped, so
+/// that our progress doesn't get mixed up with a reduced test case that is
+/// written to stdout.
+#[cfg(unix)]
+struct StdoutToStderr {
+    saved_stdout: libc::c_int,
+}
+
+#[cfg(unix)]
+impl StdoutToStderr {
+    fn new() -> io::Result<StdoutToStderr> {
+        io::stdout().flush()?;
+        unsafe {
+            let saved_stdout = libc::dup(libc::STDOUT_FILENO);
+            if saved_stdout < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+                let error = io::Error::last_os_error();
+                libc::close(saved_stdout);
+                return Err(error);
+            }
+            Ok(StdoutToStderr {
+                saved_stdout: saved_stdout,
+            })
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StdoutToStderr {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        unsafe {
+            libc::dup2(self.saved_stdout, libc::STDOUT_FILENO);
+            libc::close(self.saved_stdout);
+        }
+    }
+}
+
+/// Elsewhere, our progress is mixed in with the reduced test case.
+#[cfg(not(unix))]
+struct StdoutToStderr;
+
+#[cfg(not(unix))]
+impl StdoutToStderr {
+    fn new() -> io::Result<StdoutToStderr> {
+        Ok(StdoutToStderr)
+    }
 }
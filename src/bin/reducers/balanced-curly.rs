@@ -1,13 +1,5 @@
 extern crate preduce_balanced_reducer;
-use preduce_balanced_reducer::{run_balanced, RemoveBalanced};
-
-struct Curlies;
-
-impl RemoveBalanced for Curlies {
-    fn remove_balanced() -> (u8, u8) {
-        (b'{', b'}')
-    }
-}
+use preduce_balanced_reducer::{run_balanced, Curlies};
 
 fn main() {
     run_balanced::<Curlies>()
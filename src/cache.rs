@@ -0,0 +1,106 @@
+//! An on-disk cache of is-interesting predicate results, which persists across
+//! runs.
+//!
+//! Results are keyed by the predicate's fingerprint and the candidate's full
+//! content hash. Each result is a tiny file at
+//! `<cache dir>/<fingerprint hash>/<hh>/<rest of content hash>`, whose contents
+//! are either `1` (interesting) or `0` (not interesting). Results are written
+//! to a temporary file and then renamed into place, so concurrent workers (and
+//! concurrent runs of `preduce`) can safely share a cache directory.
+
+use error;
+use std::fs;
+use std::io::{Read, Write};
+use std::path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use test_case;
+
+/// Used to give each temporary file a unique name within this process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A cache of is-interesting predicate results for a single predicate.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: path::PathBuf,
+}
+
+impl Cache {
+    /// Open (creating if necessary) the cache within the given directory for
+    /// the predicate with the given fingerprint.
+    pub fn open<P>(dir: P, fingerprint: &str) -> error::Result<Cache>
+    where
+        P: AsRef<path::Path>,
+    {
+        let fingerprint = test_case::hash(fingerprint.as_bytes())?;
+        let dir = dir.as_ref()
+            .join(&test_case::hash_to_hex(&fingerprint)[..32]);
+        fs::create_dir_all(&dir)?;
+        Ok(Cache { dir: dir })
+    }
+
+    fn entry_path(&self, hash: &test_case::Blake2Hash) -> path::PathBuf {
+        let hex = test_case::hash_to_hex(hash);
+        self.dir.join(&hex[..2]).join(&hex[2..])
+    }
+
+    /// Get the cached result for the test case with the given full hash, if
+    /// there is one.
+    pub fn get(&self, hash: &test_case::Blake2Hash) -> Option<bool> {
+        let mut contents = String::new();
+        fs::File::open(self.entry_path(hash))
+            .and_then(|mut f| f.read_to_string(&mut contents))
+            .ok()?;
+        match contents.as_str() {
+            "1" => Some(true),
+            "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Record the result for the test case with the given full hash.
+    pub fn insert(&self, hash: &test_case::Blake2Hash, is_interesting: bool) -> error::Result<()> {
+        let path = self.entry_path(hash);
+        let parent = path.parent().expect("entries are always within a directory");
+        fs::create_dir_all(parent)?;
+
+        let tmp = parent.join(format!(
+            ".tmp-{}-{}",
+            ::std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(if is_interesting { b"1" } else { b"0" })?;
+        }
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+    use test_case;
+
+    #[test]
+    fn insert_then_get() {
+        let dir = TempDir::new("cache_insert_then_get").unwrap();
+        let cache = Cache::open(dir.path(), "some-predicate").unwrap();
+
+        let a = test_case::hash(&b"a"[..]).unwrap();
+        let b = test_case::hash(&b"b"[..]).unwrap();
+        assert_eq!(cache.get(&a), None);
+
+        cache.insert(&a, true).unwrap();
+        cache.insert(&b, false).unwrap();
+        assert_eq!(cache.get(&a), Some(true));
+        assert_eq!(cache.get(&b), Some(false));
+
+        // Results persist, but only for the same predicate.
+        let reopened = Cache::open(dir.path(), "some-predicate").unwrap();
+        assert_eq!(reopened.get(&a), Some(true));
+        let other = Cache::open(dir.path(), "other-predicate").unwrap();
+        assert_eq!(other.get(&a), None);
+    }
+}
@@ -0,0 +1,235 @@
+//! Capturing the is-interesting predicate's output for each candidate it
+//! judges, for debugging a reduction that went off the rails.
+//!
+//! Each judged candidate's output is written to its own file in the capture
+//! directory, named in the order the candidates were judged. The file starts
+//! with the candidate's path, provenance, size, and whether it was judged
+//! interesting, followed by the predicate's stdout and stderr. The log links
+//! each judgement to its output file.
+
+use error;
+use interesting;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path;
+use std::str;
+use std::sync::{Arc, Mutex};
+use test_case::{self, TestCaseMethods};
+
+/// Which candidates' captured output to keep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Retention {
+    /// Only keep the output for candidates that were judged interesting.
+    Interesting,
+
+    /// Keep the output for the last `n` candidates judged.
+    Last(usize),
+
+    /// Keep the output for every candidate judged.
+    All,
+}
+
+impl Default for Retention {
+    fn default() -> Retention {
+        Retention::All
+    }
+}
+
+impl str::FromStr for Retention {
+    type Err = String;
+
+    /// Parse `interesting`, `all`, or a number `N` of the last candidates to
+    /// keep the output of.
+    fn from_str(s: &str) -> Result<Retention, String> {
+        match s {
+            "interesting" => Ok(Retention::Interesting),
+            "all" => Ok(Retention::All),
+            n => n.parse::<usize>().map(Retention::Last).map_err(|_| {
+                format!(
+                    "'{}' is not 'interesting', 'all', or a number of outputs to keep",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    /// The number of outputs written so far.
+    written: usize,
+
+    /// The outputs that are being kept, oldest first. Only tracked for
+    /// `Retention::Last`.
+    kept: VecDeque<path::PathBuf>,
+}
+
+/// The directory that captured outputs are written to, shared by all of the
+/// workers.
+#[derive(Clone, Debug)]
+pub(crate) struct Captures {
+    dir: path::PathBuf,
+    retention: Retention,
+    state: Arc<Mutex<State>>,
+}
+
+impl Captures {
+    /// Write captured outputs into the given directory, creating it if
+    /// necessary.
+    pub fn new<P>(dir: P, retention: Retention) -> error::Result<Captures>
+    where
+        P: Into<path::PathBuf>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Captures {
+            dir: dir,
+            retention: retention,
+            state: Arc::new(Mutex::new(State {
+                written: 0,
+                kept: VecDeque::new(),
+            })),
+        })
+    }
+
+    /// Write the captured output of the predicate's judgement of the given
+    /// candidate, if the retention policy keeps it, and return the path it was
+    /// written to.
+    pub fn record(
+        &self,
+        candidate: &test_case::Candidate,
+        interesting: bool,
+        output: &interesting::Output,
+    ) -> error::Result<Option<path::PathBuf>> {
+        if self.retention == Retention::Interesting && !interesting {
+            return Ok(None);
+        }
+        if self.retention == Retention::Last(0) {
+            return Ok(None);
+        }
+
+        // Only hold the lock while numbering the output and updating the kept
+        // outputs, so that workers don't wait on each other's file writes.
+        let path = {
+            let mut state = self.state.lock().unwrap();
+            state.written += 1;
+            self.dir.join(format!("candidate-{:06}.txt", state.written))
+        };
+
+        let mut file = fs::File::create(&path)?;
+        writeln!(&mut file, "candidate: {}", candidate.path().display())?;
+        writeln!(&mut file, "provenance: {}", candidate.provenance())?;
+        writeln!(&mut file, "size: {}", candidate.size())?;
+        writeln!(&mut file, "interesting: {}", interesting)?;
+        writeln!(&mut file, "{}", output)?;
+
+        if let Retention::Last(n) = self.retention {
+            let evicted: Vec<_> = {
+                let mut state = self.state.lock().unwrap();
+                state.kept.push_back(path.clone());
+                let excess = state.kept.len().saturating_sub(n);
+                state.kept.drain(..excess).collect()
+            };
+            for oldest in evicted {
+                fs::remove_file(oldest)?;
+            }
+        }
+
+        Ok(Some(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use tempdir;
+    use test_case;
+
+    fn output() -> interesting::Output {
+        interesting::Output {
+            stdout: "out\n".into(),
+            stderr: "err\n".into(),
+        }
+    }
+
+    fn files(dir: &path::Path) -> Vec<String> {
+        let mut files: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn parse_retention() {
+        assert_eq!("interesting".parse(), Ok(Retention::Interesting));
+        assert_eq!("all".parse(), Ok(Retention::All));
+        assert_eq!("3".parse(), Ok(Retention::Last(3)));
+        assert!("most".parse::<Retention>().is_err());
+    }
+
+    #[test]
+    fn keep_all() {
+        let dir = tempdir::TempDir::new("capture").unwrap();
+        let captures = Captures::new(dir.path().join("out"), Retention::All).unwrap();
+        let candidate = test_case::Candidate::testing_only_new();
+
+        let path = captures
+            .record(&candidate, false, &output())
+            .unwrap()
+            .expect("should keep uninteresting output");
+        captures.record(&candidate, true, &output()).unwrap();
+
+        let mut contents = String::new();
+        fs::File::open(&path)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert!(contents.contains("interesting: false"));
+        assert!(contents.contains("out"));
+        assert!(contents.contains("err"));
+        assert_eq!(
+            files(&dir.path().join("out")),
+            vec!["candidate-000001.txt", "candidate-000002.txt"]
+        );
+    }
+
+    #[test]
+    fn keep_interesting() {
+        let dir = tempdir::TempDir::new("capture").unwrap();
+        let captures = Captures::new(dir.path().join("out"), Retention::Interesting).unwrap();
+        let candidate = test_case::Candidate::testing_only_new();
+
+        assert!(
+            captures
+                .record(&candidate, false, &output())
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            captures
+                .record(&candidate, true, &output())
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(files(&dir.path().join("out")), vec!["candidate-000001.txt"]);
+    }
+
+    #[test]
+    fn keep_last() {
+        let dir = tempdir::TempDir::new("capture").unwrap();
+        let captures = Captures::new(dir.path().join("out"), Retention::Last(2)).unwrap();
+        let candidate = test_case::Candidate::testing_only_new();
+
+        for _ in 0..4 {
+            captures.record(&candidate, false, &output()).unwrap();
+        }
+        assert_eq!(
+            files(&dir.path().join("out")),
+            vec!["candidate-000003.txt", "candidate-000004.txt"]
+        );
+    }
+}
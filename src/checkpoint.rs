@@ -0,0 +1,177 @@
+//! Saving and restoring checkpoints of a reduction in progress, so that long
+//! reductions can be resumed after they are interrupted.
+//!
+//! A checkpoint directory contains a copy of the smallest interesting test case
+//! and a JSON file with everything else we need to pick up where we left off:
+//! the original test case's size, the oracle's observations, the running
+//! reduction phase, and each reducer's state.
+
+use error;
+use serde_json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path;
+use test_case::{self, TestCaseMethods};
+
+/// The name of the checkpoint's JSON file within the checkpoint directory.
+const CHECKPOINT_FILE_NAME: &'static str = "checkpoint.json";
+
+/// The name of the copy of the smallest interesting test case within the
+/// checkpoint directory.
+const SMALLEST_FILE_NAME: &'static str = "smallest";
+
+/// Everything besides the smallest interesting test case itself that is saved
+/// in a checkpoint.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Checkpoint {
+    /// The size of the original, unreduced test case.
+    pub orig_size: u64,
+
+    /// The hex-formatted hash of the smallest interesting test case that this
+    /// checkpoint was written with.
+    pub smallest_hash: String,
+
+    /// The oracle's observations.
+    pub oracle: serde_json::Value,
+
+    /// A map from reducer name to the reducer's state for the smallest
+    /// interesting test case.
+    pub reducers: BTreeMap<String, serde_json::Value>,
+
+    /// The index of the reduction phase that was running.
+    #[serde(default)]
+    pub phase: usize,
+}
+
+impl Checkpoint {
+    /// Get the path to the copy of the smallest interesting test case within
+    /// the given checkpoint directory.
+    pub fn smallest_path<P>(dir: P) -> path::PathBuf
+    where
+        P: AsRef<path::Path>,
+    {
+        dir.as_ref().join(SMALLEST_FILE_NAME)
+    }
+
+    /// Read a checkpoint from the given checkpoint directory.
+    pub fn read<P>(dir: P) -> error::Result<Checkpoint>
+    where
+        P: AsRef<path::Path>,
+    {
+        let smallest = Checkpoint::smallest_path(&dir);
+        if !smallest.exists() {
+            return Err(error::Error::DoesNotExist(smallest));
+        }
+
+        let file = fs::File::open(dir.as_ref().join(CHECKPOINT_FILE_NAME))?;
+        let checkpoint = serde_json::from_reader(file)?;
+        Ok(checkpoint)
+    }
+
+    /// Write this checkpoint, along with a copy of the given smallest
+    /// interesting test case, into the given checkpoint directory.
+    ///
+    /// Each file is written to a temporary file first and then renamed into
+    /// place, so that being killed mid-write never leaves a truncated
+    /// checkpoint behind.
+    pub fn write<P>(&self, dir: P, smallest: &test_case::Interesting) -> error::Result<()>
+    where
+        P: AsRef<path::Path>,
+    {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let smallest_path = Checkpoint::smallest_path(dir);
+        let smallest_tmp = dir.join(format!("{}.tmp", SMALLEST_FILE_NAME));
+        test_case::copy_test_case(smallest.path(), &smallest_tmp)?;
+        if smallest_tmp.is_dir() && smallest_path.is_dir() {
+            // Directories can't be renamed over non-empty directories.
+            fs::remove_dir_all(&smallest_path)?;
+        }
+        fs::rename(&smallest_tmp, &smallest_path)?;
+
+        let checkpoint_path = dir.join(CHECKPOINT_FILE_NAME);
+        let checkpoint_tmp = dir.join(format!("{}.tmp", CHECKPOINT_FILE_NAME));
+        {
+            let mut file = fs::File::create(&checkpoint_tmp)?;
+            serde_json::to_writer(&mut file, self)?;
+            file.flush()?;
+        }
+        fs::rename(&checkpoint_tmp, &checkpoint_path)?;
+
+        Ok(())
+    }
+
+    /// Does this checkpoint's saved reducer state belong to the given smallest
+    /// interesting test case?
+    ///
+    /// This may not be the case if we were killed between writing the copy of
+    /// the smallest test case and writing the checkpoint's JSON file.
+    pub fn matches(&self, smallest: &test_case::Interesting) -> bool {
+        test_case::hash_from_hex(&self.smallest_hash) == Some(smallest.full_hash())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::path;
+    use tempdir::TempDir;
+    use test_case::{self, Interesting, TestCaseMethods};
+
+    #[test]
+    fn write_then_read() {
+        let dir = TempDir::new("checkpoint_write_then_read").unwrap();
+        let initial_path = dir.path().join("initial");
+        {
+            let mut file = fs::File::create(&initial_path).unwrap();
+            writeln!(&mut file, "la la la").unwrap();
+        }
+
+        let judge = |_: &path::Path| Ok(true);
+        let smallest = Interesting::initial(&initial_path, &judge)
+            .unwrap()
+            .unwrap();
+
+        let mut reducers = BTreeMap::new();
+        reducers.insert("some-reducer".to_string(), serde_json::Value::from(42));
+
+        let checkpoint = Checkpoint {
+            orig_size: 100,
+            smallest_hash: test_case::hash_to_hex(&smallest.full_hash()),
+            oracle: serde_json::Value::Null,
+            reducers: reducers,
+            phase: 1,
+        };
+
+        let checkpoint_dir = dir.path().join("checkpoint");
+        checkpoint.write(&checkpoint_dir, &smallest).unwrap();
+
+        let restored = Checkpoint::read(&checkpoint_dir).unwrap();
+        assert_eq!(restored.orig_size, 100);
+        assert_eq!(restored.phase, 1);
+        assert!(restored.matches(&smallest));
+        assert_eq!(
+            restored.reducers.get("some-reducer"),
+            Some(&serde_json::Value::from(42))
+        );
+
+        let mut contents = String::new();
+        fs::File::open(Checkpoint::smallest_path(&checkpoint_dir))
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "la la la\n");
+    }
+
+    #[test]
+    fn read_missing() {
+        let dir = TempDir::new("checkpoint_read_missing").unwrap();
+        assert!(Checkpoint::read(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! An in-process line diff, using the linear space variant of Myers' O(ND)
+//! algorithm.
+//!
+//! We only need to know *what* changed between a seed and a candidate, so that
+//! we can hash the edit and recognize it when it is made again. Unchanged lines
+//! and line numbers are not reported.
+
+use std::cmp;
+
+/// Give up on finding a minimal diff once it would take more than this many
+/// inserted and deleted lines. The search's time grows with the edit's size
+/// times the texts' lengths, and an edit this big is not going to be tried
+/// twice anyway.
+const MAX_EDIT_COST: usize = 2000;
+
+/// A changed line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Edit<'a> {
+    /// The line was removed from the old text.
+    Delete(&'a [u8]),
+
+    /// The line was added to the new text.
+    Insert(&'a [u8]),
+}
+
+/// Split the given text into lines, each including its trailing newline, if
+/// it has one.
+pub(crate) fn lines(text: &[u8]) -> Vec<&[u8]> {
+    let mut lines = vec![];
+    let mut start = 0;
+    for (i, &byte) in text.iter().enumerate() {
+        if byte == b'\n' {
+            lines.push(&text[start..i + 1]);
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        lines.push(&text[start..]);
+    }
+    lines
+}
+
+/// Diff the `old` lines against the `new` lines, returning the lines that were
+/// deleted and inserted, in order.
+///
+/// The diff is minimal, unless it would be larger than `MAX_EDIT_COST`, in
+/// which case everything between the common prefix and suffix is replaced.
+pub(crate) fn diff<'a>(old: &[&'a [u8]], new: &[&'a [u8]]) -> Vec<Edit<'a>> {
+    let prefix = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old.iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|&(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+
+    match middle_snake(old, new, MAX_EDIT_COST) {
+        Some(snake) => {
+            let mut edits = vec![];
+            edit_around(old, new, &snake, &mut edits);
+            edits
+        }
+        None => old.iter()
+            .map(|line| Edit::Delete(line))
+            .chain(new.iter().map(|line| Edit::Insert(line)))
+            .collect(),
+    }
+}
+
+/// The middle snake of a shortest edit script: the run of unchanged lines
+/// from `(x, y)` to `(u, v)` that the script passes through halfway, and the
+/// script's total cost `d`.
+#[derive(Debug)]
+struct Snake {
+    x: usize,
+    y: usize,
+    u: usize,
+    v: usize,
+    d: usize,
+}
+
+/// Find the middle snake of the shortest edit script from `old` to `new`, by
+/// searching forwards from the start and backwards from the end at the same
+/// time until the two searches meet. Only the furthest reaching paths are
+/// kept, so this takes space linear in the texts' lengths.
+///
+/// Returns `None` if the script costs more than `max_cost`.
+fn middle_snake(old: &[&[u8]], new: &[&[u8]], max_cost: usize) -> Option<Snake> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = cmp::min((n + m + 1) / 2, (max_cost as isize + 1) / 2);
+    let offset = max + 1;
+
+    // The furthest reaching x on each diagonal k = x - y, searching forwards,
+    // and the furthest reaching distance back from the end on each diagonal of
+    // the reversed texts, searching backwards.
+    let mut forward = vec![0; 2 * max as usize + 3];
+    let mut backward = vec![0; 2 * max as usize + 3];
+
+    for d in 0..max + 1 {
+        for k in (-d..d + 1).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && forward[i - 1] < forward[i + 1]) {
+                forward[i + 1]
+            } else {
+                forward[i - 1] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[i] = x;
+
+            // Diagonal k going forwards is diagonal delta - k going backwards.
+            let back_k = delta - k;
+            if odd && back_k > -d && back_k < d && x + backward[(back_k + offset) as usize] >= n {
+                return snake(start_x, start_y, x, y, 2 * d - 1, max_cost);
+            }
+        }
+
+        for k in (-d..d + 1).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && backward[i - 1] < backward[i + 1]) {
+                backward[i + 1]
+            } else {
+                backward[i - 1] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && old[(n - 1 - x) as usize] == new[(m - 1 - y) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[i] = x;
+
+            let forward_k = delta - k;
+            if !odd && forward_k >= -d && forward_k <= d &&
+                forward[(forward_k + offset) as usize] + x >= n
+            {
+                return snake(n - x, m - y, n - start_x, m - start_y, 2 * d, max_cost);
+            }
+        }
+    }
+
+    None
+}
+
+fn snake(x: isize, y: isize, u: isize, v: isize, d: isize, max_cost: usize) -> Option<Snake> {
+    if d as usize > max_cost {
+        return None;
+    }
+    Some(Snake {
+        x: x as usize,
+        y: y as usize,
+        u: u as usize,
+        v: v as usize,
+        d: d as usize,
+    })
+}
+
+/// Push the edits that turn `old` into `new` onto `edits`.
+fn edit<'a>(old: &[&'a [u8]], new: &[&'a [u8]], edits: &mut Vec<Edit<'a>>) {
+    if old.is_empty() {
+        edits.extend(new.iter().map(|line| Edit::Insert(line)));
+    } else if new.is_empty() {
+        edits.extend(old.iter().map(|line| Edit::Delete(line)));
+    } else {
+        let snake = middle_snake(old, new, old.len() + new.len())
+            .expect("no edit costs more than deleting and inserting everything");
+        edit_around(old, new, &snake, edits);
+    }
+}
+
+/// Push the edits that turn `old` into `new` onto `edits`, given the middle
+/// snake of the shortest edit script: the edits before it, and then the edits
+/// after it.
+fn edit_around<'a>(old: &[&'a [u8]], new: &[&'a [u8]], snake: &Snake, edits: &mut Vec<Edit<'a>>) {
+    match snake.d {
+        0 => {}
+        // Splitting around the snake wouldn't make any progress here, but a
+        // single edit can only be at the first line that differs.
+        1 => {
+            let same = old.iter().zip(new).take_while(|&(a, b)| a == b).count();
+            if old.len() > new.len() {
+                edits.push(Edit::Delete(old[same]));
+            } else {
+                edits.push(Edit::Insert(new[same]));
+            }
+        }
+        _ => {
+            edit(&old[..snake.x], &new[..snake.y], edits);
+            edit(&old[snake.u..], &new[snake.v..], edits);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff_str<'a>(old: &'a str, new: &'a str) -> Vec<Edit<'a>> {
+        diff(&lines(old.as_bytes()), &lines(new.as_bytes()))
+    }
+
+    #[test]
+    fn split_lines() {
+        assert!(lines(b"").is_empty());
+        assert_eq!(lines(b"a\nb\n"), vec![&b"a\n"[..], &b"b\n"[..]]);
+        assert_eq!(lines(b"a\nb"), vec![&b"a\n"[..], &b"b"[..]]);
+        assert_eq!(lines(b"\n\n"), vec![&b"\n"[..], &b"\n"[..]]);
+    }
+
+    #[test]
+    fn identical() {
+        assert!(diff_str("", "").is_empty());
+        assert!(diff_str("a\nb\nc\n", "a\nb\nc\n").is_empty());
+    }
+
+    #[test]
+    fn delete_and_insert() {
+        assert_eq!(
+            diff_str("a\nb\nc\n", "a\nc\n"),
+            vec![Edit::Delete(b"b\n")]
+        );
+        assert_eq!(
+            diff_str("a\nc\n", "a\nb\nc\n"),
+            vec![Edit::Insert(b"b\n")]
+        );
+        assert_eq!(
+            diff_str("a\nb\nc\n", ""),
+            vec![Edit::Delete(b"a\n"), Edit::Delete(b"b\n"), Edit::Delete(b"c\n")]
+        );
+        assert_eq!(diff_str("", "a\n"), vec![Edit::Insert(b"a\n")]);
+    }
+
+    #[test]
+    fn minimal() {
+        // The classic example from Myers' paper: ABCABBA -> CBABAC has a
+        // shortest edit script of length 5.
+        let old = "A\nB\nC\nA\nB\nB\nA\n";
+        let new = "C\nB\nA\nB\nA\nC\n";
+        let edits = diff_str(old, new);
+        assert_eq!(edits.len(), 5);
+
+        let deleted = edits
+            .iter()
+            .filter(|e| match **e {
+                Edit::Delete(_) => true,
+                _ => false,
+            })
+            .count();
+        assert_eq!(deleted, 3);
+    }
+
+    #[test]
+    fn scattered_edits() {
+        assert_eq!(
+            diff_str("a\nb\nc\nd\ne\n", "a\nx\nc\ne\ny\n"),
+            vec![
+                Edit::Delete(b"b\n"),
+                Edit::Insert(b"x\n"),
+                Edit::Delete(b"d\n"),
+                Edit::Insert(b"y\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn large_but_affordable() {
+        let old: Vec<String> = (0..MAX_EDIT_COST * 3 / 2).map(|i| format!("{}\n", i)).collect();
+        let old: Vec<&[u8]> = old.iter().map(|l| l.as_bytes()).collect();
+        let new: Vec<&[u8]> = old.iter().cloned().step_by(2).collect();
+
+        let edits = diff(&old, &new);
+        let expected: Vec<_> = old.iter().skip(1).step_by(2).map(|l| Edit::Delete(l)).collect();
+        assert_eq!(edits, expected);
+    }
+
+    #[test]
+    fn too_expensive() {
+        let old: Vec<String> = (0..MAX_EDIT_COST).map(|i| format!("old {}\n", i)).collect();
+        let new: Vec<String> = (0..MAX_EDIT_COST).map(|i| format!("new {}\n", i)).collect();
+        let old: Vec<&[u8]> = old.iter().map(|l| l.as_bytes()).collect();
+        let new: Vec<&[u8]> = new.iter().map(|l| l.as_bytes()).collect();
+
+        let edits = diff(&old, &new);
+        assert_eq!(edits.len(), 2 * MAX_EDIT_COST);
+        assert_eq!(edits[0], Edit::Delete(old[0]));
+        assert_eq!(edits[MAX_EDIT_COST], Edit::Insert(new[0]));
+    }
+}
@@ -1,11 +1,15 @@
 //! Custom errors and results.
 
+use interesting;
+use preflight;
+use regex;
 use serde_json;
 use std::any::Any;
 use std::error;
 use std::fmt;
 use std::io;
 use std::path;
+use std::time;
 
 /// The kinds of errors that can happen when running `preduce`.
 #[derive(Debug)]
@@ -16,6 +20,9 @@ pub enum Error {
     /// A JSON encoding/decoding error.
     Json(serde_json::Error),
 
+    /// An invalid regular expression.
+    Regex(regex::Error),
+
     /// A panicked thread's failure value.
     Thread(Box<Any + Send + 'static>),
 
@@ -33,6 +40,42 @@ pub enum Error {
 
     /// There is no file at the given path, when we expected one.
     DoesNotExist(path::PathBuf),
+
+    /// A `git` command failed while maintaining the history of interesting
+    /// test cases.
+    Git(String),
+
+    /// A checkpoint could not be restored because it is malformed.
+    InvalidCheckpoint(String),
+
+    /// An is-interesting predicate ran for longer than its timeout, and was
+    /// killed. Also holds everything it printed until then, if it was asked
+    /// to capture its output.
+    PredicateTimedOut(time::Duration, Option<interesting::Output>),
+
+    /// A remote worker could not judge a candidate.
+    RemoteWorker(String),
+
+    /// The other end of a remote worker connection speaks a different version
+    /// of the protocol: `(ours, theirs)`.
+    RemoteProtocolMismatch(u32, u32),
+
+    /// A configuration file is malformed.
+    InvalidConfig(String),
+
+    /// An in-process reducer failed.
+    Reducer(String),
+
+    /// A predicate sandbox could not be set up.
+    Sandbox(String),
+
+    /// An is-interesting predicate was killed for exceeding one of its resource
+    /// limits. Also holds everything it printed until then, if it was asked to
+    /// capture its output.
+    ResourceLimitExceeded(interesting::ResourceLimit, Option<interesting::Output>),
+
+    /// One of the pre-flight checks of the is-interesting predicate failed.
+    PreflightFailed(preflight::Failure),
 }
 
 impl fmt::Display for Error {
@@ -40,6 +83,7 @@ impl fmt::Display for Error {
         match *self {
             Error::Io(ref e) => fmt::Display::fmt(e, f),
             Error::Json(ref e) => fmt::Display::fmt(e, f),
+            Error::Regex(ref e) => fmt::Display::fmt(e, f),
             Error::Thread(ref e) => write!(f, "Thread panicked: {:?}", e),
             Error::MisbehavingReducerScript(ref details) => {
                 write!(f, "Misbehaving reducer script: {}", details)
@@ -57,6 +101,31 @@ impl fmt::Display for Error {
             Error::DoesNotExist(ref file_path) => {
                 write!(f, "The file does not exist: {}", file_path.display())
             }
+            Error::Git(ref details) => write!(f, "Git error: {}", details),
+            Error::InvalidCheckpoint(ref details) => write!(f, "Invalid checkpoint: {}", details),
+            Error::PredicateTimedOut(timeout, _) => write!(
+                f,
+                "The is-interesting predicate timed out after {}.{:03} seconds",
+                timeout.as_secs(),
+                timeout.subsec_nanos() / 1_000_000
+            ),
+            Error::RemoteWorker(ref details) => write!(f, "Remote worker error: {}", details),
+            Error::RemoteProtocolMismatch(ours, theirs) => write!(
+                f,
+                "Remote worker protocol mismatch: this end speaks version {}, but the other \
+                 end speaks version {}; upgrade the older of preduce and preduce-worker",
+                ours,
+                theirs
+            ),
+            Error::InvalidConfig(ref details) => write!(f, "Invalid configuration: {}", details),
+            Error::Reducer(ref details) => write!(f, "Reducer error: {}", details),
+            Error::Sandbox(ref details) => write!(f, "Sandbox error: {}", details),
+            Error::ResourceLimitExceeded(limit, _) => write!(
+                f,
+                "The is-interesting predicate exceeded its {} limit",
+                limit
+            ),
+            Error::PreflightFailed(ref failure) => write!(f, "Pre-flight check failed: {}", failure),
         }
     }
 }
@@ -66,6 +135,7 @@ impl error::Error for Error {
         match *self {
             Error::Io(ref e) => error::Error::description(e),
             Error::Json(ref e) => error::Error::description(e),
+            Error::Regex(ref e) => error::Error::description(e),
             Error::Thread(_) => "A panicked thread",
             Error::MisbehavingReducerScript(_) => "Misbehaving reducer script",
             Error::TestCaseBackupFailure(_) => "Could not backup initial test case",
@@ -74,6 +144,20 @@ impl error::Error for Error {
             }
             Error::IsNotExecutable(_) => "The script is not executable",
             Error::DoesNotExist(_) => "There is no file at the given path, but we expected one",
+            Error::Git(_) => "A git command failed",
+            Error::InvalidCheckpoint(_) => "Invalid checkpoint",
+            Error::PredicateTimedOut(..) => "The is-interesting predicate timed out",
+            Error::RemoteWorker(_) => "A remote worker could not judge a candidate",
+            Error::RemoteProtocolMismatch(..) => "Remote worker protocol mismatch",
+            Error::InvalidConfig(_) => "Invalid configuration",
+            Error::Reducer(_) => "An in-process reducer failed",
+            Error::Sandbox(_) => "A predicate sandbox could not be set up",
+            Error::ResourceLimitExceeded(..) => {
+                "The is-interesting predicate exceeded a resource limit"
+            }
+            Error::PreflightFailed(_) => {
+                "A pre-flight check of the is-interesting predicate failed"
+            }
         }
     }
 
@@ -81,6 +165,7 @@ impl error::Error for Error {
         match *self {
             Error::Io(ref e) => Some(e),
             Error::Json(ref e) => Some(e),
+            Error::Regex(ref e) => Some(e),
             _ => None,
         }
     }
@@ -98,6 +183,12 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<regex::Error> for Error {
+    fn from(e: regex::Error) -> Self {
+        Error::Regex(e)
+    }
+}
+
 impl From<Box<Any + Send + 'static>> for Error {
     fn from(e: Box<Any + Send + 'static>) -> Self {
         Error::Thread(e)
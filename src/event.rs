@@ -0,0 +1,384 @@
+//! Events describing a reduction's progress, for embedding applications that
+//! want to drive their own UIs or collect their own metrics.
+//!
+//! Subscribe to events with `Options::on_event`. Every event that `preduce`
+//! writes to its log is also delivered to subscribers, in the same order.
+
+use interesting;
+use std::fmt;
+use std::path;
+use std::time;
+
+/// A test case or candidate that an event is about.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TestCaseInfo {
+    /// The path to the test case's contents. This is generally a temporary
+    /// file that only lives as long as the reduction needs it.
+    pub path: path::PathBuf,
+
+    /// The size of the test case, in bytes.
+    pub size: u64,
+
+    /// The difference in size between this test case and the seed it was
+    /// generated from, in bytes.
+    pub delta: u64,
+
+    /// The name of the reducer that generated this test case.
+    pub provenance: String,
+}
+
+/// Something that happened during a reduction.
+///
+/// Workers and reducers are identified by the ids that `preduce` assigns to
+/// them; a worker or reducer that is restarted after panicking gets a new id.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A worker is being spawned.
+    SpawningWorker {
+        /// The worker's id.
+        worker: usize,
+    },
+
+    /// A worker finished spawning.
+    SpawnedWorker {
+        /// The worker's id.
+        worker: usize,
+    },
+
+    /// A reducer is being spawned.
+    SpawningReducer {
+        /// The reducer's id.
+        reducer: usize,
+    },
+
+    /// A reducer finished spawning.
+    SpawnedReducer {
+        /// The reducer's id.
+        reducer: usize,
+    },
+
+    /// A worker is shutting down.
+    ShutdownWorker {
+        /// The worker's id.
+        worker: usize,
+    },
+
+    /// A reducer is shutting down.
+    ShutdownReducer {
+        /// The reducer's id.
+        reducer: usize,
+    },
+
+    /// A worker panicked, and will be replaced.
+    WorkerPanicked {
+        /// The worker's id.
+        worker: usize,
+        /// The panic's message, if it had one.
+        message: Option<String>,
+    },
+
+    /// A worker failed with an error, and will be replaced.
+    WorkerErrored {
+        /// The worker's id.
+        worker: usize,
+        /// A description of the error.
+        error: String,
+    },
+
+    /// A reducer panicked.
+    ReducerPanicked {
+        /// The reducer's id.
+        reducer: usize,
+        /// The panic's message, if it had one.
+        message: Option<String>,
+    },
+
+    /// A reducer failed with an error.
+    ReducerErrored {
+        /// The reducer's id.
+        reducer: usize,
+        /// A description of the error.
+        error: String,
+    },
+
+    /// The initial test case is being backed up before it is overwritten.
+    BackingUpTestCase {
+        /// The initial test case.
+        from: path::PathBuf,
+        /// Where the backup is written.
+        to: path::PathBuf,
+    },
+
+    /// A worker started running the is-interesting predicate on a candidate.
+    StartJudgingInteresting {
+        /// The worker's id.
+        worker: usize,
+        /// The candidate being judged.
+        candidate: TestCaseInfo,
+    },
+
+    /// A worker found that a candidate is interesting.
+    JudgedInteresting {
+        /// The worker's id.
+        worker: usize,
+        /// The interesting candidate.
+        interesting: TestCaseInfo,
+        /// The file holding the predicate's captured output, if it was
+        /// captured and kept.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<path::PathBuf>,
+    },
+
+    /// A worker found that a candidate is not interesting.
+    JudgedNotInteresting {
+        /// The worker's id.
+        worker: usize,
+        /// The candidate that is not interesting.
+        candidate: TestCaseInfo,
+        /// The file holding the predicate's captured output, if it was
+        /// captured and kept.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<path::PathBuf>,
+    },
+
+    /// The is-interesting predicate was killed after running past its
+    /// timeout, and the candidate is treated as not interesting.
+    JudgedTimedOut {
+        /// The worker's id.
+        worker: usize,
+        /// The candidate being judged.
+        candidate: TestCaseInfo,
+        /// The timeout that the predicate ran past.
+        timeout: time::Duration,
+        /// The file holding the predicate's captured output, if it was
+        /// captured and kept.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<path::PathBuf>,
+    },
+
+    /// The is-interesting predicate was killed for exceeding one of its
+    /// resource limits, and the candidate is treated as not interesting.
+    JudgedLimitExceeded {
+        /// The worker's id.
+        worker: usize,
+        /// The candidate being judged.
+        candidate: TestCaseInfo,
+        /// The limit that the predicate exceeded.
+        limit: interesting::ResourceLimit,
+        /// The file holding the predicate's captured output, if it was
+        /// captured and kept.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<path::PathBuf>,
+    },
+
+    /// The runs of a flakiness-tolerant is-interesting predicate disagreed
+    /// about a candidate, and only just decided its interesting-ness.
+    JudgedNearThreshold {
+        /// The worker's id.
+        worker: usize,
+        /// The candidate being judged.
+        candidate: TestCaseInfo,
+        /// How the predicate's runs voted.
+        votes: interesting::Votes,
+    },
+
+    /// A candidate's interesting-ness was found in the predicate result cache.
+    CachedResult {
+        /// The worker's id.
+        worker: usize,
+        /// The candidate.
+        candidate: TestCaseInfo,
+        /// Whether the cached result is interesting.
+        is_interesting: bool,
+    },
+
+    /// Writing a result to the predicate result cache failed.
+    CacheErrored {
+        /// A description of the error.
+        error: String,
+    },
+
+    /// A worker failed to write the predicate's captured output.
+    CaptureErrored {
+        /// A description of the error.
+        error: String,
+    },
+
+    /// A cache directory was configured, but the predicate has no fingerprint,
+    /// so its results are not cached.
+    NotCaching,
+
+    /// The pre-flight checks of the is-interesting predicate passed.
+    PreflightPassed {
+        /// How long the predicate ran for on the original test case.
+        runtime: time::Duration,
+    },
+
+    /// The is-interesting predicate did not find the original test case
+    /// interesting every time it was run.
+    FlakyPredicate {
+        /// The number of runs that found it interesting.
+        interesting: usize,
+        /// The number of runs.
+        runs: usize,
+    },
+
+    /// There is a new smallest interesting test case.
+    NewSmallest {
+        /// The new smallest interesting test case.
+        interesting: TestCaseInfo,
+        /// The size of the original test case, in bytes.
+        original_size: u64,
+    },
+
+    /// An interesting candidate is not smaller than the current smallest
+    /// interesting test case.
+    IsNotSmaller {
+        /// The interesting candidate.
+        interesting: TestCaseInfo,
+    },
+
+    /// Interesting test cases that were found concurrently were merged into a
+    /// new candidate.
+    Merged {
+        /// The merged candidate.
+        candidate: TestCaseInfo,
+    },
+
+    /// Maintaining the history of interesting test cases failed, so merging
+    /// is disabled.
+    HistoryErrored {
+        /// A description of the error.
+        error: String,
+    },
+
+    /// The reduction is resuming from a checkpoint.
+    ResumingFromCheckpoint {
+        /// The checkpoint directory.
+        dir: path::PathBuf,
+    },
+
+    /// A checkpoint was written.
+    WroteCheckpoint {
+        /// The checkpoint directory.
+        dir: path::PathBuf,
+    },
+
+    /// Writing a checkpoint failed.
+    CheckpointErrored {
+        /// A description of the error.
+        error: String,
+    },
+
+    /// A reducer started generating its next candidate.
+    StartGeneratingNextCandidate {
+        /// The reducer's id.
+        reducer: usize,
+    },
+
+    /// A reducer finished generating its next candidate.
+    FinishGeneratingNextCandidate {
+        /// The reducer's id.
+        reducer: usize,
+        /// The generated candidate.
+        candidate: TestCaseInfo,
+    },
+
+    /// A reducer has no more candidates for the current smallest interesting
+    /// test case.
+    NoMoreCandidates {
+        /// The reducer's id.
+        reducer: usize,
+    },
+
+    /// The reduction finished.
+    FinalReducedSize {
+        /// The size of the final, smallest interesting test case, in bytes.
+        final_size: u64,
+        /// The size of the original test case, in bytes.
+        original_size: u64,
+    },
+
+    /// A summary of a reducer's candidates' time in the queue, sent once for
+    /// each reducer when the reduction finishes.
+    QueueOccupancy {
+        /// The reducer's name.
+        reducer: String,
+        /// The most candidates from this reducer that were queued at once.
+        peak_queued: usize,
+        /// The total time this reducer's candidates spent waiting in the queue.
+        queued_time: time::Duration,
+    },
+
+    /// SIGINT was received, and the reduction is stopping early.
+    GotSigint,
+
+    /// The supervisor is listening for remote workers.
+    Listening {
+        /// The address being listened on.
+        addr: String,
+    },
+
+    /// A remote worker connected.
+    RemoteWorkerConnected {
+        /// The id of the worker that drives the remote worker.
+        worker: usize,
+        /// The remote worker's address.
+        addr: String,
+    },
+
+    /// The connection to a remote worker was lost. The remote worker connects
+    /// again by itself.
+    RemoteWorkerLost {
+        /// The id of the worker that drove the remote worker.
+        worker: usize,
+        /// The remote worker's address.
+        addr: String,
+    },
+
+    /// A reduction phase is starting.
+    StartingPhase {
+        /// The phase's name.
+        name: String,
+    },
+
+    /// A periodic summary of the supervisor's state. Only sent when the
+    /// status view is shown or there are event subscribers.
+    Progress {
+        /// The size of the current smallest interesting test case, in bytes.
+        smallest_size: u64,
+        /// The size of the original test case, in bytes.
+        original_size: u64,
+        /// The number of candidates waiting for a worker to judge them.
+        queued_candidates: usize,
+        /// The number of running workers.
+        workers: usize,
+        /// The number of running workers that are waiting for a candidate.
+        idle_workers: usize,
+        /// The number of running reducers.
+        reducers: usize,
+        /// The names of the reducers that have no more candidates for the
+        /// current smallest interesting test case.
+        exhausted_reducers: Vec<String>,
+    },
+}
+
+/// A subscriber to a reduction's events.
+pub(crate) struct Subscriber(Box<Fn(&Event) + Send + 'static>);
+
+impl Subscriber {
+    pub(crate) fn new(f: Box<Fn(&Event) + Send + 'static>) -> Subscriber {
+        Subscriber(f)
+    }
+
+    pub(crate) fn notify(&self, event: &Event) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for Subscriber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Subscriber")
+    }
+}
@@ -0,0 +1,329 @@
+//! A `git` history of every interesting test case we discover, which lets us
+//! merge interesting test cases that were found concurrently.
+//!
+//! Every interesting test case becomes a commit whose parent is the commit of
+//! the seed it was generated from. When two interesting test cases of the same
+//! seed are found at the same time, we ask `git` for their merge base and do a
+//! three-way merge of their contents. If the merge is clean, the result is a
+//! new candidate that can be tested for interesting-ness like any other.
+//!
+//! Directory test cases are not recorded, so they are never merged.
+
+use error;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path;
+use std::process;
+use std::sync::Arc;
+use tempdir;
+use test_case::{self, TestCaseMethods};
+
+/// The provenance given to candidates that are the result of a merge.
+pub const MERGE_PROVENANCE: &'static str = "merge";
+
+/// The name of the test case file within each commit's tree.
+const TEST_CASE_FILE_NAME: &'static str = "test-case";
+
+/// A `git` history of interesting test cases.
+#[derive(Debug)]
+pub struct History {
+    /// The bare `git` repository holding the history.
+    repo: tempdir::TempDir,
+
+    /// The directory that merged candidates are written into.
+    out_dir: Arc<tempdir::TempDir>,
+
+    /// A map from an interesting test case's full hash to its commit.
+    commits: HashMap<test_case::Blake2Hash, String>,
+
+    /// A map from a merged candidate's full hash to the commit of the "theirs"
+    /// side of the merge, so that it can be given as a second parent if the
+    /// merged candidate turns out to be interesting.
+    merges: HashMap<test_case::Blake2Hash, String>,
+
+    /// A counter for generating unique merged candidate file names.
+    counter: usize,
+}
+
+impl History {
+    /// Create a new, empty history.
+    pub fn new() -> error::Result<History> {
+        let repo = tempdir::TempDir::new("preduce-history")?;
+        let out_dir = Arc::new(tempdir::TempDir::new("preduce-merges")?);
+
+        let history = History {
+            repo: repo,
+            out_dir: out_dir,
+            commits: HashMap::new(),
+            merges: HashMap::new(),
+            counter: 0,
+        };
+        history.git(&["init", "--quiet", "--bare"], None)?;
+        Ok(history)
+    }
+
+    /// Record the given interesting test case in the history.
+    ///
+    /// If the interesting test case was generated from a seed that is already
+    /// in the history, then the seed's commit becomes its parent. If it is the
+    /// result of a merge, then the other side of the merge becomes its second
+    /// parent.
+    ///
+    /// Directory test cases are ignored.
+    pub fn record(&mut self, interesting: &test_case::Interesting) -> error::Result<()> {
+        if interesting.is_dir() || self.commits.contains_key(&interesting.full_hash()) {
+            return Ok(());
+        }
+
+        let path = interesting.path().display().to_string();
+        let blob = self.git(&["hash-object", "-w", &path], None)?;
+
+        let entry = format!("100644 blob {}\t{}\n", blob, TEST_CASE_FILE_NAME);
+        let tree = self.git(&["mktree"], Some(entry.as_bytes()))?;
+
+        let parent = interesting
+            .as_candidate()
+            .and_then(|c| self.commits.get(&c.seed_hash()))
+            .cloned();
+
+        let merged_from = self.merges.remove(&interesting.full_hash());
+
+        let commit = {
+            let mut args = vec!["commit-tree", &tree, "-m", interesting.provenance()];
+            for parent in parent.iter().chain(merged_from.iter()) {
+                args.push("-p");
+                args.push(parent);
+            }
+            self.git(&args, None)?
+        };
+
+        self.commits.insert(interesting.full_hash(), commit);
+        Ok(())
+    }
+
+    /// Attempt a three-way merge of two interesting test cases that are both
+    /// in the history.
+    ///
+    /// Returns `None` if either test case is not in the history, if they have
+    /// no common ancestor, if one is an ancestor of the other (in which case
+    /// there is nothing to merge), or if the merge has conflicts. Otherwise,
+    /// returns the merged candidate, seeded by `ours`.
+    pub fn merge(
+        &mut self,
+        ours: &test_case::Interesting,
+        theirs: &test_case::Interesting,
+    ) -> error::Result<Option<test_case::Candidate>> {
+        let (our_commit, their_commit) = match (
+            self.commits.get(&ours.full_hash()),
+            self.commits.get(&theirs.full_hash()),
+        ) {
+            (Some(o), Some(t)) if o != t => (o.clone(), t.clone()),
+            _ => return Ok(None),
+        };
+
+        let base = match self.git(&["merge-base", &our_commit, &their_commit], None) {
+            Ok(base) => base,
+            // No common ancestor.
+            Err(_) => return Ok(None),
+        };
+        if base == our_commit || base == their_commit {
+            return Ok(None);
+        }
+
+        let base_contents = self.git_raw(
+            &["cat-file", "blob", &format!("{}:{}", base, TEST_CASE_FILE_NAME)],
+            None,
+        )?;
+        let base_file = self.next_temp_file()?;
+        fs::File::create(base_file.path())?.write_all(&base_contents)?;
+
+        let merged = self.next_temp_file()?;
+        let output = process::Command::new("git")
+            .current_dir(self.repo.path())
+            .args(&["merge-file", "-p", "--quiet"])
+            .arg(ours.path())
+            .arg(base_file.path())
+            .arg(theirs.path())
+            .stdin(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .output()?;
+
+        // `git merge-file` exits with the number of conflicts, or a negative
+        // status on error.
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        fs::File::create(merged.path())?.write_all(&output.stdout)?;
+        let candidate = test_case::Candidate::new(ours.clone(), MERGE_PROVENANCE, merged)?;
+        self.merges.insert(candidate.full_hash(), their_commit);
+        Ok(Some(candidate))
+    }
+
+    fn next_temp_file(&mut self) -> error::Result<test_case::TempFile> {
+        let file_name = format!("merge{}", self.counter);
+        self.counter += 1;
+        test_case::TempFile::new(self.out_dir.clone(), path::PathBuf::from(file_name))
+    }
+
+    /// Run a `git` command within the history's repository and return its
+    /// trimmed stdout.
+    fn git(&self, args: &[&str], stdin: Option<&[u8]>) -> error::Result<String> {
+        let stdout = self.git_raw(args, stdin)?;
+        Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+    }
+
+    /// Run a `git` command within the history's repository and return its raw
+    /// stdout.
+    fn git_raw(&self, args: &[&str], stdin: Option<&[u8]>) -> error::Result<Vec<u8>> {
+        let mut cmd = process::Command::new("git");
+        cmd.current_dir(self.repo.path())
+            .env("GIT_DIR", self.repo.path())
+            .env("GIT_AUTHOR_NAME", "preduce")
+            .env("GIT_AUTHOR_EMAIL", "preduce@localhost")
+            .env("GIT_COMMITTER_NAME", "preduce")
+            .env("GIT_COMMITTER_EMAIL", "preduce@localhost")
+            .args(args)
+            .stdin(if stdin.is_some() {
+                process::Stdio::piped()
+            } else {
+                process::Stdio::null()
+            })
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped());
+
+        let mut child = cmd.spawn()?;
+        if let Some(stdin) = stdin {
+            child.stdin.take().unwrap().write_all(stdin)?;
+        }
+
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(error::Error::Git(format!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::path;
+    use tempdir::TempDir;
+    use test_case::{Candidate, Interesting, TempFile, TestCaseMethods};
+
+    fn candidate_of(seed: &Interesting, contents: &str) -> Interesting {
+        let temp_file = TempFile::anonymous().unwrap();
+        {
+            let mut file = fs::File::create(temp_file.path()).unwrap();
+            write!(&mut file, "{}", contents).unwrap();
+        }
+
+        let judge = |_: &path::Path| Ok(true);
+        Candidate::new(seed.clone(), "test", temp_file)
+            .unwrap()
+            .into_interesting(&judge)
+            .unwrap()
+            .left()
+            .unwrap()
+    }
+
+    fn initial(contents: &str) -> (TempDir, Interesting) {
+        let dir = TempDir::new("history_initial").unwrap();
+        let path = dir.path().join("initial");
+        {
+            let mut file = fs::File::create(&path).unwrap();
+            write!(&mut file, "{}", contents).unwrap();
+        }
+
+        let judge = |_: &path::Path| Ok(true);
+        let initial = Interesting::initial(&path, &judge).unwrap().unwrap();
+        (dir, initial)
+    }
+
+    #[test]
+    fn merge_disjoint_line_removals() {
+        let (_dir, initial) = initial("one\ntwo\nthree\nfour\nfive\n");
+        let without_one = candidate_of(&initial, "two\nthree\nfour\nfive\n");
+        let without_five = candidate_of(&initial, "one\ntwo\nthree\nfour\n");
+
+        let mut history = History::new().unwrap();
+        history.record(&initial).unwrap();
+        history.record(&without_one).unwrap();
+        history.record(&without_five).unwrap();
+
+        let merged = history
+            .merge(&without_one, &without_five)
+            .expect("should merge OK")
+            .expect("should not have conflicts");
+
+        assert_eq!(merged.provenance(), MERGE_PROVENANCE);
+        assert_eq!(merged.seed_hash(), without_one.full_hash());
+
+        let mut contents = String::new();
+        fs::File::open(merged.path())
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "two\nthree\nfour\n");
+        assert_eq!(merged.size(), contents.len() as u64);
+    }
+
+    #[test]
+    fn merged_test_case_has_both_parents() {
+        let (_dir, initial) = initial("one\ntwo\nthree\nfour\nfive\n");
+        let without_one = candidate_of(&initial, "two\nthree\nfour\nfive\n");
+        let without_five = candidate_of(&initial, "one\ntwo\nthree\nfour\n");
+
+        let mut history = History::new().unwrap();
+        history.record(&initial).unwrap();
+        history.record(&without_one).unwrap();
+        history.record(&without_five).unwrap();
+
+        let merged = history.merge(&without_one, &without_five).unwrap().unwrap();
+        let judge = |_: &path::Path| Ok(true);
+        let merged = merged.into_interesting(&judge).unwrap().left().unwrap();
+        history.record(&merged).unwrap();
+
+        // Both sides of the merge are now ancestors of the merged test case,
+        // so there is nothing left to merge.
+        assert!(history.merge(&merged, &without_one).unwrap().is_none());
+        assert!(history.merge(&merged, &without_five).unwrap().is_none());
+    }
+
+    #[test]
+    fn merge_with_ancestor_is_none() {
+        let (_dir, initial) = initial("one\ntwo\nthree\n");
+        let without_one = candidate_of(&initial, "two\nthree\n");
+        let without_two = candidate_of(&without_one, "three\n");
+
+        let mut history = History::new().unwrap();
+        history.record(&initial).unwrap();
+        history.record(&without_one).unwrap();
+        history.record(&without_two).unwrap();
+
+        assert!(history.merge(&without_two, &without_one).unwrap().is_none());
+    }
+
+    #[test]
+    fn merge_conflict_is_none() {
+        let (_dir, initial) = initial("one\ntwo\nthree\n");
+        let a = candidate_of(&initial, "one\nthree\n");
+        let b = candidate_of(&initial, "one\n2\nthree\n");
+
+        let mut history = History::new().unwrap();
+        history.record(&initial).unwrap();
+        history.record(&a).unwrap();
+        history.record(&b).unwrap();
+
+        assert!(history.merge(&a, &b).unwrap().is_none());
+    }
+}
@@ -2,10 +2,19 @@
 
 use error;
 use is_executable::IsExecutable;
+use regex;
+use std::env;
+use std::ffi;
+use std::fmt;
 use std::fs;
+use std::io::{self, Read};
 use std::panic::UnwindSafe;
 use std::path;
 use std::process;
+use std::thread;
+use std::time;
+use subprocess;
+use test_case;
 use traits::IsInteresting;
 
 impl IsInteresting for Box<IsInteresting> {
@@ -13,12 +22,46 @@ impl IsInteresting for Box<IsInteresting> {
         (**self).is_interesting(candidate)
     }
 
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        (**self).is_interesting_once(candidate)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        (**self).judge(candidate, capture_output)
+    }
+
+    fn is_interesting_with_votes(
+        &self,
+        candidate: &path::Path,
+    ) -> error::Result<(bool, Option<Votes>)> {
+        (**self).is_interesting_with_votes(candidate)
+    }
+
+    fn is_interesting_with_output(
+        &self,
+        candidate: &path::Path,
+    ) -> error::Result<(bool, Option<Output>)> {
+        (**self).is_interesting_with_output(candidate)
+    }
+
     fn clone(&self) -> Box<IsInteresting>
     where
         Self: 'static,
     {
         (**self).clone()
     }
+
+    fn fingerprint(&self) -> Option<String> {
+        (**self).fingerprint()
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        (**self).set_env(vars)
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        (**self).set_resource_limits(limits)
+    }
 }
 
 /// An `IsInteresting` implementation that rejects empty test cases, and accepts
@@ -38,6 +81,133 @@ impl IsInteresting for NonEmpty {
     {
         Box::new(NonEmpty) as _
     }
+
+    fn fingerprint(&self) -> Option<String> {
+        Some("non-empty".into())
+    }
+}
+
+/// Limits on the resources that each of a predicate's processes may use,
+/// enforced with `setrlimit`. The processes that they spawn inherit the same
+/// limits. Limits are only enforced on Unix.
+///
+/// ```
+/// let limits = preduce::interesting::ResourceLimits {
+///     address_space: Some(4 << 30),
+///     cpu_seconds: Some(60),
+///     ..Default::default()
+/// };
+/// # let _ = limits;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ResourceLimits {
+    /// The maximum size of a process's virtual memory, in bytes
+    /// (`RLIMIT_AS`).
+    pub address_space: Option<u64>,
+
+    /// The maximum CPU time a process may use, in seconds (`RLIMIT_CPU`).
+    pub cpu_seconds: Option<u64>,
+
+    /// The maximum size of a file that a process may write, in bytes
+    /// (`RLIMIT_FSIZE`).
+    pub file_size: Option<u64>,
+
+    /// The maximum number of processes that the user may have running
+    /// (`RLIMIT_NPROC`). Note that this counts all of the user's processes,
+    /// not just the predicate's.
+    pub processes: Option<u64>,
+}
+
+/// One of the limits in `ResourceLimits`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceLimit {
+    /// The address space limit.
+    AddressSpace,
+
+    /// The CPU time limit.
+    CpuTime,
+
+    /// The file size limit.
+    FileSize,
+
+    /// The process limit.
+    Processes,
+}
+
+impl fmt::Display for ResourceLimit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ResourceLimit::AddressSpace => "address space",
+            ResourceLimit::CpuTime => "CPU time",
+            ResourceLimit::FileSize => "file size",
+            ResourceLimit::Processes => "process",
+        })
+    }
+}
+
+/// How a predicate judged a candidate, as returned by `IsInteresting::judge`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Judgement {
+    /// Whether the candidate is interesting.
+    pub interesting: bool,
+
+    /// How the predicate's runs voted, if it ran more than once.
+    pub votes: Option<Votes>,
+
+    /// Everything that the predicate's processes printed, if it was asked to
+    /// capture its output and runs any processes.
+    pub output: Option<Output>,
+}
+
+impl Judgement {
+    /// Combine this judgement with that of a predicate that ran after it: the
+    /// later one decides the outcome, and their outputs are kept one after the
+    /// other.
+    fn then(self, next: Judgement) -> Judgement {
+        let output = match (self.output, next.output) {
+            (Some(mut output), Some(next_output)) => {
+                output.stdout.push_str(&next_output.stdout);
+                output.stderr.push_str(&next_output.stderr);
+                Some(output)
+            }
+            (output, next_output) => output.or(next_output),
+        };
+        Judgement {
+            interesting: next.interesting,
+            votes: next.votes.or(self.votes),
+            output: output,
+        }
+    }
+}
+
+impl From<bool> for Judgement {
+    fn from(interesting: bool) -> Judgement {
+        Judgement {
+            interesting: interesting,
+            votes: None,
+            output: None,
+        }
+    }
+}
+
+/// Everything that a predicate's processes printed while judging a candidate.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Output {
+    /// The captured stdout.
+    pub stdout: String,
+
+    /// The captured stderr.
+    pub stderr: String,
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "--- stdout ---")?;
+        writeln!(f, "{}", self.stdout.trim_right())?;
+        writeln!(f, "--- stderr ---")?;
+        write!(f, "{}", self.stderr.trim_right())
+    }
 }
 
 /// Spawn a subprocess that runs a user-provided script to determine whether a
@@ -49,6 +219,18 @@ impl IsInteresting for NonEmpty {
 /// The user-provided script is given a single argument: a relative path to the
 /// test case file it should test.
 ///
+/// If a timeout is set with `set_timeout`, then a script that runs for longer
+/// than that is killed, along with any processes it spawned, and
+/// `is_interesting` returns `Err(Error::PredicateTimedOut(..))`. When capturing
+/// its output with `judge`, the error holds what the script printed before it
+/// was killed.
+///
+/// If resource limits are set with `set_resource_limits`, then they are applied
+/// to the script and everything it spawns. When the script appears to have been
+/// killed for exceeding one of them, `is_interesting` returns
+/// `Err(Error::ResourceLimitExceeded(..))`, rather than judging the candidate
+/// not interesting.
+///
 /// ### Example
 ///
 /// Suppose we have some custom test script, `my_test.sh`:
@@ -89,6 +271,9 @@ impl IsInteresting for NonEmpty {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Script {
     program: path::PathBuf,
+    timeout: Option<time::Duration>,
+    limits: ResourceLimits,
+    env: Vec<(String, ffi::OsString)>,
 }
 
 impl Script {
@@ -106,19 +291,32 @@ impl Script {
         }
 
         let program = program.as_ref().canonicalize()?;
-        Ok(Script { program: program })
+        Ok(Script {
+            program: program,
+            timeout: None,
+            limits: ResourceLimits::default(),
+            env: vec![],
+        })
     }
-}
 
-impl IsInteresting for Script {
-    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
-        assert!(candidate.is_file());
+    /// Set the maximum wall-clock time the script may run for on a single test
+    /// case, or `None` to let it run for as long as it likes. Defaults to
+    /// `None`.
+    pub fn set_timeout(&mut self, timeout: Option<time::Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Run the script on the candidate, capturing its output if `capture` is
+    /// true.
+    fn run(&self, candidate: &path::Path, capture: bool) -> error::Result<(bool, Option<Output>)> {
+        assert!(candidate.exists());
         assert!(self.program.is_file());
 
         let mut cmd = process::Command::new(&self.program);
-        cmd.stdout(process::Stdio::null())
-            .stderr(process::Stdio::null())
-            .stdin(process::Stdio::null());
+        cmd.stdout(piped(capture))
+            .stderr(piped(capture))
+            .stdin(process::Stdio::null())
+            .envs(self.env.iter().map(|&(ref k, ref v)| (k, v)));
 
         match (candidate.parent(), candidate.file_name()) {
             (Some(dir), Some(file)) => {
@@ -129,12 +327,431 @@ impl IsInteresting for Script {
             }
         }
 
-        Ok(cmd.spawn()?.wait()?.success())
+        let mut child = subprocess::spawn(&mut cmd, &self.limits)?;
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        // Even a killed script's output is captured, since it is the best clue
+        // as to why it was killed.
+        let exit = subprocess::wait_timeout(&mut child, self.timeout)?;
+        let output = if capture {
+            Some(Output {
+                stdout: join_pipe(stdout)?.unwrap_or_default(),
+                stderr: join_pipe(stderr)?.unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+
+        let (status, usage) = match exit {
+            Some(exit) => exit,
+            None => {
+                return Err(error::Error::PredicateTimedOut(
+                    self.timeout.expect("can only time out if there is a timeout"),
+                    output,
+                ))
+            }
+        };
+
+        if status.success() {
+            return Ok((true, output));
+        }
+        match subprocess::exceeded_limit(status, &usage, &self.limits) {
+            Some(limit) => Err(error::Error::ResourceLimitExceeded(limit, output)),
+            None => Ok((false, output)),
+        }
+    }
+}
+
+impl IsInteresting for Script {
+    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.run(candidate, false)
+            .map(|(interesting, _)| interesting)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        let (interesting, output) = self.run(candidate, capture_output)?;
+        Ok(Judgement {
+            interesting: interesting,
+            votes: None,
+            output: output,
+        })
+    }
+
+    fn clone(&self) -> Box<IsInteresting> {
+        Box::new(Clone::clone(self)) as _
+    }
+
+    /// A script's fingerprint is derived from its path, its modification time,
+    /// its contents, and its resource limits.
+    fn fingerprint(&self) -> Option<String> {
+        let metadata = fs::metadata(&self.program).ok()?;
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(time::UNIX_EPOCH)
+            .ok()?;
+
+        let mut data = vec![];
+        data.extend(self.program.to_string_lossy().as_bytes());
+        data.push(0);
+        data.extend(format!("{}.{:09}", mtime.as_secs(), mtime.subsec_nanos()).as_bytes());
+        data.push(0);
+        fs::File::open(&self.program)
+            .ok()?
+            .read_to_end(&mut data)
+            .ok()?;
+        if self.limits != ResourceLimits::default() {
+            data.push(0);
+            data.extend(format!("{:?}", self.limits).as_bytes());
+        }
+
+        let hash = test_case::hash(&data[..]).ok()?;
+        Some(format!("script-{}", test_case::hash_to_hex(&hash)))
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.env = vars.to_vec();
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.limits = *limits;
+    }
+}
+
+/// Run a command on the candidate and decide whether it is interesting by
+/// matching on how the command exited and what it printed, without needing a
+/// predicate script.
+///
+/// The command is given as an argv template. Each occurrence of `{}` in its
+/// arguments is replaced with a relative path to the candidate test case, and
+/// the command is run from the candidate's directory. If no argument contains
+/// `{}`, then the path is appended as the last argument.
+///
+/// A candidate is interesting if all of the configured expectations hold:
+///
+/// * `set_expected_exit_code`: the command exited with the given code.
+///
+/// * `set_expected_signal`: the command was killed by the given signal.
+///
+/// * `set_expected_stdout` / `set_expected_stderr`: the command's stdout /
+///   stderr matches the given regular expression.
+///
+/// If no expectations are configured, then a candidate is interesting when the
+/// command exits with `0`, just like `Script`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate preduce;
+/// use preduce::traits::IsInteresting;
+/// # fn main() { fn _foo() -> preduce::error::Result<()> {
+///
+/// // Is the candidate still making the compiler crash?
+/// let mut test = preduce::interesting::Command::new(vec!["cc", "-c", "-O2", "{}"]);
+/// test.set_expected_stderr("internal compiler error")?;
+///
+/// # fn get_some_random_test_case() -> &'static ::std::path::Path { unimplemented!() }
+/// let test_case = get_some_random_test_case();
+/// if test.is_interesting(test_case)? {
+///     println!("Still crashes the compiler!");
+/// }
+/// # Ok(()) } }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Command {
+    argv: Vec<String>,
+    exit_code: Option<i32>,
+    signal: Option<i32>,
+    stdout: Option<regex::Regex>,
+    stderr: Option<regex::Regex>,
+    timeout: Option<time::Duration>,
+    limits: ResourceLimits,
+    env: Vec<(String, ffi::OsString)>,
+}
+
+impl Command {
+    /// Construct a new `Command` is-interesting test from the given argv
+    /// template.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `argv` is empty.
+    pub fn new<I, S>(argv: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let argv: Vec<String> = argv.into_iter().map(Into::into).collect();
+        assert!(!argv.is_empty(), "the command must have a program to run");
+        Command {
+            argv: argv,
+            exit_code: None,
+            signal: None,
+            stdout: None,
+            stderr: None,
+            timeout: None,
+            limits: ResourceLimits::default(),
+            env: vec![],
+        }
+    }
+
+    /// Only consider candidates interesting when the command exits with the
+    /// given code.
+    pub fn set_expected_exit_code(&mut self, code: Option<i32>) {
+        self.exit_code = code;
+    }
+
+    /// Only consider candidates interesting when the command is killed by the
+    /// given signal.
+    pub fn set_expected_signal(&mut self, signal: Option<i32>) {
+        self.signal = signal;
+    }
+
+    /// Only consider candidates interesting when the command's stdout matches
+    /// the given regular expression.
+    pub fn set_expected_stdout(&mut self, pattern: &str) -> error::Result<()> {
+        self.stdout = Some(regex::Regex::new(pattern)?);
+        Ok(())
+    }
+
+    /// Only consider candidates interesting when the command's stderr matches
+    /// the given regular expression.
+    pub fn set_expected_stderr(&mut self, pattern: &str) -> error::Result<()> {
+        self.stderr = Some(regex::Regex::new(pattern)?);
+        Ok(())
+    }
+
+    /// Set the maximum wall-clock time the command may run for on a single
+    /// test case, or `None` to let it run for as long as it likes. Defaults to
+    /// `None`.
+    pub fn set_timeout(&mut self, timeout: Option<time::Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn has_expectations(&self) -> bool {
+        self.exit_code.is_some() || self.signal.is_some() || self.stdout.is_some() ||
+            self.stderr.is_some()
+    }
+
+    fn status_matches(&self, status: process::ExitStatus) -> bool {
+        if !self.has_expectations() {
+            return status.success();
+        }
+
+        if let Some(code) = self.exit_code {
+            if status.code() != Some(code) {
+                return false;
+            }
+        }
+
+        if let Some(signal) = self.signal {
+            if exit_signal(status) != Some(signal) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Run the command on the candidate, capturing its output if `capture` is
+    /// true.
+    fn run(&self, candidate: &path::Path, capture: bool) -> error::Result<(bool, Option<Output>)> {
+        assert!(candidate.exists());
+
+        let (dir, file) = match (candidate.parent(), candidate.file_name()) {
+            (Some(dir), Some(file)) => (Some(dir), path::Path::new(file)),
+            _ => (None, candidate),
+        };
+        let file = file.to_string_lossy();
+
+        let mut cmd = process::Command::new(&self.argv[0]);
+        let mut substituted = false;
+        for arg in &self.argv[1..] {
+            if arg.contains("{}") {
+                substituted = true;
+                cmd.arg(arg.replace("{}", &file));
+            } else {
+                cmd.arg(arg);
+            }
+        }
+        if !substituted {
+            cmd.arg(&*file);
+        }
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+
+        cmd.stdin(process::Stdio::null())
+            .stdout(piped(capture || self.stdout.is_some()))
+            .stderr(piped(capture || self.stderr.is_some()))
+            .envs(self.env.iter().map(|&(ref k, ref v)| (k, v)));
+
+        let mut child = subprocess::spawn(&mut cmd, &self.limits)?;
+        let stdout = read_pipe(child.stdout.take());
+        let stderr = read_pipe(child.stderr.take());
+
+        let exit = subprocess::wait_timeout(&mut child, self.timeout)?;
+        let stdout = join_pipe(stdout)?;
+        let stderr = join_pipe(stderr)?;
+        let output = |stdout: Option<String>, stderr: Option<String>| if capture {
+            Some(Output {
+                stdout: stdout.unwrap_or_default(),
+                stderr: stderr.unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+
+        let (status, usage) = match exit {
+            Some(exit) => exit,
+            None => {
+                return Err(error::Error::PredicateTimedOut(
+                    self.timeout.expect("can only time out if there is a timeout"),
+                    output(stdout, stderr),
+                ))
+            }
+        };
+
+        if !self.status_matches(status) {
+            if let Some(limit) = subprocess::exceeded_limit(status, &usage, &self.limits) {
+                return Err(error::Error::ResourceLimitExceeded(limit, output(stdout, stderr)));
+            }
+        }
+
+        let interesting = self.status_matches(status) &&
+            self.stdout
+                .as_ref()
+                .map_or(true, |re| re.is_match(stdout.as_ref().unwrap())) &&
+            self.stderr
+                .as_ref()
+                .map_or(true, |re| re.is_match(stderr.as_ref().unwrap()));
+        Ok((interesting, output(stdout, stderr)))
+    }
+}
+
+/// Find the binary that running `program` would execute: the program itself
+/// if it is a path, or otherwise the first executable with its name in the
+/// `$PATH` the command is run with.
+fn resolve_program(program: &str, env: &[(String, ffi::OsString)]) -> Option<path::PathBuf> {
+    let program = path::Path::new(program);
+    if program.components().count() > 1 {
+        return fs::canonicalize(program).ok();
+    }
+
+    let search_path = env.iter()
+        .rev()
+        .find(|&&(ref var, _)| var == "PATH")
+        .map(|&(_, ref value)| value.clone())
+        .or_else(|| env::var_os("PATH"))?;
+    env::split_paths(&search_path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_executable())
+        .and_then(|binary| fs::canonicalize(binary).ok())
+}
+
+#[cfg(unix)]
+fn exit_signal(status: process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/// Pipe a child's output if it should be captured, and discard it otherwise.
+fn piped(capture: bool) -> process::Stdio {
+    if capture {
+        process::Stdio::piped()
+    } else {
+        process::Stdio::null()
+    }
+}
+
+/// Read everything from the given pipe on a new thread, so that the child
+/// never blocks on a full pipe while we wait for it.
+fn read_pipe<R>(pipe: Option<R>) -> Option<thread::JoinHandle<io::Result<String>>>
+where
+    R: 'static + Read + Send,
+{
+    pipe.map(|mut pipe| {
+        thread::spawn(move || {
+            let mut output = vec![];
+            pipe.read_to_end(&mut output)?;
+            Ok(String::from_utf8_lossy(&output).into_owned())
+        })
+    })
+}
+
+/// Wait for a pipe that is being read by `read_pipe` to finish.
+fn join_pipe(
+    handle: Option<thread::JoinHandle<io::Result<String>>>,
+) -> error::Result<Option<String>> {
+    match handle {
+        Some(handle) => Ok(Some(handle.join()??)),
+        None => Ok(None),
+    }
+}
+
+impl IsInteresting for Command {
+    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.run(candidate, false)
+            .map(|(interesting, _)| interesting)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        let (interesting, output) = self.run(candidate, capture_output)?;
+        Ok(Judgement {
+            interesting: interesting,
+            votes: None,
+            output: output,
+        })
     }
 
     fn clone(&self) -> Box<IsInteresting> {
         Box::new(Clone::clone(self)) as _
     }
+
+    /// A command's fingerprint is derived from the path, size, and
+    /// modification time of the binary that it runs, its arguments, and its
+    /// expectations and resource limits.
+    fn fingerprint(&self) -> Option<String> {
+        let program = resolve_program(&self.argv[0], &self.env)?;
+        let metadata = fs::metadata(&program).ok()?;
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(time::UNIX_EPOCH)
+            .ok()?;
+
+        Some(format!(
+            "command({:?},{:?},{}.{:09},{:?},{:?},{:?},{:?},{:?}{})",
+            program,
+            metadata.len(),
+            mtime.as_secs(),
+            mtime.subsec_nanos(),
+            &self.argv[1..],
+            self.exit_code,
+            self.signal,
+            self.stdout.as_ref().map(|re| re.as_str()),
+            self.stderr.as_ref().map(|re| re.as_str()),
+            if self.limits == ResourceLimits::default() {
+                String::new()
+            } else {
+                format!(",{:?}", self.limits)
+            }
+        ))
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.env = vars.to_vec();
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.limits = *limits;
+    }
 }
 
 /// Given two is-interesting tests, combine them into a single is-interesting
@@ -193,12 +810,43 @@ where
         Ok(self.first.is_interesting(candidate)? && self.second.is_interesting(candidate)?)
     }
 
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        Ok(self.first.is_interesting_once(candidate)? &&
+            self.second.is_interesting_once(candidate)?)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        let first = self.first.judge(candidate, capture_output)?;
+        if !first.interesting {
+            return Ok(first);
+        }
+        Ok(first.then(self.second.judge(candidate, capture_output)?))
+    }
+
     fn clone(&self) -> Box<IsInteresting>
     where
         Self: 'static,
     {
         Box::new(And::new(self.first.clone(), self.second.clone())) as _
     }
+
+    fn fingerprint(&self) -> Option<String> {
+        Some(format!(
+            "and({},{})",
+            self.first.fingerprint()?,
+            self.second.fingerprint()?
+        ))
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.first.set_env(vars);
+        self.second.set_env(vars);
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.first.set_resource_limits(limits);
+        self.second.set_resource_limits(limits);
+    }
 }
 
 /// Given two is-interesting tests, combine them into a single is-interesting
@@ -253,12 +901,278 @@ where
         Ok(self.first.is_interesting(candidate)? || self.second.is_interesting(candidate)?)
     }
 
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        Ok(self.first.is_interesting_once(candidate)? ||
+            self.second.is_interesting_once(candidate)?)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        let first = self.first.judge(candidate, capture_output)?;
+        if first.interesting {
+            return Ok(first);
+        }
+        Ok(first.then(self.second.judge(candidate, capture_output)?))
+    }
+
     fn clone(&self) -> Box<IsInteresting>
     where
         Self: 'static,
     {
         Box::new(And::new(self.first.clone(), self.second.clone())) as _
     }
+
+    fn fingerprint(&self) -> Option<String> {
+        Some(format!(
+            "or({},{})",
+            self.first.fingerprint()?,
+            self.second.fingerprint()?
+        ))
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.first.set_env(vars);
+        self.second.set_env(vars);
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.first.set_resource_limits(limits);
+        self.second.set_resource_limits(limits);
+    }
+}
+
+/// How many runs of a predicate that runs more than once, like `Retry` or
+/// `Majority`, found a candidate interesting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct Votes {
+    /// The number of runs that found the candidate interesting.
+    pub interesting: usize,
+
+    /// The number of runs it took to decide the outcome.
+    pub runs: usize,
+
+    /// The number of runs that must find a candidate interesting for it to
+    /// be judged interesting.
+    pub needed: usize,
+
+    /// The largest number of runs that could have been made.
+    pub max_runs: usize,
+}
+
+impl Votes {
+    /// Is this outcome near the threshold? That is, did the runs disagree,
+    /// with the outcome undecided until the last possible run?
+    pub fn is_near_threshold(&self) -> bool {
+        self.runs == self.max_runs && self.interesting != 0 && self.interesting != self.runs
+    }
+
+    /// Run `predicate` on `candidate` until either `needed` runs have found it
+    /// interesting, or enough have found it not interesting that `needed` can
+    /// no longer be reached. When capturing output, every run's output is
+    /// kept, one after the other.
+    fn tally<I>(
+        predicate: &I,
+        candidate: &path::Path,
+        needed: usize,
+        max_runs: usize,
+        capture_output: bool,
+    ) -> error::Result<Judgement>
+    where
+        I: ?Sized + IsInteresting,
+    {
+        let mut votes = Votes {
+            interesting: 0,
+            runs: 0,
+            needed: needed,
+            max_runs: max_runs,
+        };
+        let mut output: Option<Output> = None;
+        while votes.interesting < needed && votes.runs - votes.interesting <= max_runs - needed {
+            let judgement = match predicate.judge(candidate, capture_output) {
+                Ok(judgement) => judgement,
+                // A run that times out or exceeds a resource limit didn't find
+                // the candidate interesting, and shouldn't stop the others.
+                Err(error::Error::PredicateTimedOut(_, output)) |
+                Err(error::Error::ResourceLimitExceeded(_, output)) => Judgement {
+                    interesting: false,
+                    votes: None,
+                    output: output,
+                },
+                Err(e) => return Err(e),
+            };
+            if judgement.interesting {
+                votes.interesting += 1;
+            }
+            votes.runs += 1;
+            if let Some(run_output) = judgement.output {
+                let output = output.get_or_insert_with(Default::default);
+                output.stdout.push_str(&run_output.stdout);
+                output.stderr.push_str(&run_output.stderr);
+            }
+        }
+        Ok(Judgement {
+            interesting: votes.interesting >= needed,
+            votes: Some(votes),
+            output: output,
+        })
+    }
+}
+
+/// Given a flaky is-interesting test, make a test that runs it up to `runs`
+/// times, and returns `true` as soon as any run returns `true`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate preduce;
+/// # fn main() { fn _foo() {
+///
+/// // The crash only reproduces some of the time, so give it five chances.
+/// let test = preduce::interesting::Retry::new(
+///     preduce::interesting::Script::new("/path/to/flaky/script").unwrap(),
+///     5
+/// );
+/// # let _ = test;
+/// # } }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retry<I> {
+    inner: I,
+    runs: usize,
+}
+
+impl<I> Retry<I> {
+    /// Retry `inner` up to `runs` times.
+    ///
+    /// Panics if `runs` is zero.
+    pub fn new(inner: I, runs: usize) -> Retry<I> {
+        assert!(runs > 0, "must run the predicate at least once");
+        Retry {
+            inner: inner,
+            runs: runs,
+        }
+    }
+}
+
+impl<I> IsInteresting for Retry<I>
+where
+    I: IsInteresting,
+{
+    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.judge(candidate, false)
+            .map(|judgement| judgement.interesting)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        Votes::tally(&self.inner, candidate, 1, self.runs, capture_output)
+    }
+
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.inner.is_interesting_once(candidate)
+    }
+
+    fn clone(&self) -> Box<IsInteresting>
+    where
+        Self: 'static,
+    {
+        Box::new(Retry::new(self.inner.clone(), self.runs)) as _
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        Some(format!("retry({},{})", self.runs, self.inner.fingerprint()?))
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.inner.set_env(vars);
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.inner.set_resource_limits(limits);
+    }
+}
+
+/// Given a flaky is-interesting test, make a test that returns `true` if at
+/// least `needed` of `runs` runs of it return `true`.
+///
+/// Runs stop as soon as the outcome is decided, so an interesting candidate
+/// may only need `needed` runs.
+///
+/// ### Example
+///
+/// ```
+/// extern crate preduce;
+/// # fn main() { fn _foo() {
+///
+/// // Only trust a candidate when at least three of five runs agree.
+/// let test = preduce::interesting::Majority::new(
+///     preduce::interesting::Script::new("/path/to/flaky/script").unwrap(),
+///     3,
+///     5
+/// );
+/// # let _ = test;
+/// # } }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Majority<I> {
+    inner: I,
+    needed: usize,
+    runs: usize,
+}
+
+impl<I> Majority<I> {
+    /// Require `needed` of `runs` runs of `inner` to be interesting.
+    ///
+    /// Panics if `needed` is zero or greater than `runs`.
+    pub fn new(inner: I, needed: usize, runs: usize) -> Majority<I> {
+        assert!(needed > 0, "must need at least one interesting run");
+        assert!(needed <= runs, "cannot need more interesting runs than there are runs");
+        Majority {
+            inner: inner,
+            needed: needed,
+            runs: runs,
+        }
+    }
+}
+
+impl<I> IsInteresting for Majority<I>
+where
+    I: IsInteresting,
+{
+    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.judge(candidate, false)
+            .map(|judgement| judgement.interesting)
+    }
+
+    fn judge(&self, candidate: &path::Path, capture_output: bool) -> error::Result<Judgement> {
+        Votes::tally(&self.inner, candidate, self.needed, self.runs, capture_output)
+    }
+
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.inner.is_interesting_once(candidate)
+    }
+
+    fn clone(&self) -> Box<IsInteresting>
+    where
+        Self: 'static,
+    {
+        Box::new(Majority::new(self.inner.clone(), self.needed, self.runs)) as _
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        Some(format!(
+            "majority({},{},{})",
+            self.needed,
+            self.runs,
+            self.inner.fingerprint()?
+        ))
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.inner.set_env(vars);
+    }
+
+    fn set_resource_limits(&mut self, limits: &ResourceLimits) {
+        self.inner.set_resource_limits(limits);
+    }
 }
 
 impl<T> IsInteresting for T
@@ -283,7 +1197,12 @@ mod tests {
     use error;
     use std::fs;
     use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
     use std::path;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time;
+    use tempdir;
     use test_case;
     use test_utils::*;
 
@@ -325,6 +1244,127 @@ mod tests {
         assert!(!test.is_interesting(test_case.path()).unwrap());
     }
 
+    #[test]
+    fn timeout() {
+        let mut test = Script::new(get_sleep_30()).unwrap();
+        test.set_timeout(Some(time::Duration::from_millis(100)));
+        let test_case = temp_file();
+        match test.is_interesting(test_case.path()) {
+            Err(error::Error::PredicateTimedOut(..)) => {}
+            otherwise => {
+                panic!("Expected Error::PredicateTimedOut, found {:?}", otherwise);
+            }
+        }
+
+        let mut test = Script::new(get_exit_0()).unwrap();
+        test.set_timeout(Some(time::Duration::from_secs(30)));
+        assert!(test.is_interesting(test_case.path()).unwrap());
+    }
+
+    #[test]
+    fn command_default_is_exit_zero() {
+        let test_case = temp_file();
+        assert!(Command::new(vec!["true"]).is_interesting(test_case.path()).unwrap());
+        assert!(!Command::new(vec!["false"]).is_interesting(test_case.path()).unwrap());
+    }
+
+    #[test]
+    fn command_expected_exit_code() {
+        let test_case = temp_file();
+        let mut test = Command::new(vec!["sh", "-c", "exit 3"]);
+        test.set_expected_exit_code(Some(3));
+        assert!(test.is_interesting(test_case.path()).unwrap());
+
+        test.set_expected_exit_code(Some(4));
+        assert!(!test.is_interesting(test_case.path()).unwrap());
+    }
+
+    #[test]
+    fn command_expected_signal() {
+        let test_case = temp_file();
+        let mut test = Command::new(vec!["sh", "-c", "kill -SEGV $$"]);
+        test.set_expected_signal(Some(11));
+        assert!(test.is_interesting(test_case.path()).unwrap());
+
+        let mut test = Command::new(vec!["true"]);
+        test.set_expected_signal(Some(11));
+        assert!(!test.is_interesting(test_case.path()).unwrap());
+    }
+
+    #[test]
+    fn command_expected_output() {
+        let test_case = test_case::TempFile::anonymous().unwrap();
+        {
+            let mut file = fs::File::create(test_case.path()).unwrap();
+            writeln!(&mut file, "internal compiler error: oops").unwrap();
+        }
+
+        // The candidate's path is substituted for `{}`.
+        let mut test = Command::new(vec!["sh", "-c", "cat \"$1\" >&2; exit 1", "sh", "{}"]);
+        test.set_expected_stderr("internal compiler error").unwrap();
+        assert!(test.is_interesting(test_case.path()).unwrap());
+
+        test.set_expected_exit_code(Some(1));
+        assert!(test.is_interesting(test_case.path()).unwrap());
+
+        test.set_expected_stdout("internal compiler error").unwrap();
+        assert!(!test.is_interesting(test_case.path()).unwrap());
+
+        // Without `{}`, the candidate's path is appended.
+        let mut test = Command::new(vec!["cat"]);
+        test.set_expected_stdout("^internal").unwrap();
+        assert!(test.is_interesting(test_case.path()).unwrap());
+
+        match Command::new(vec!["cat"]).set_expected_stdout("(") {
+            Err(error::Error::Regex(_)) => {}
+            otherwise => panic!("Expected Error::Regex, found {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn timed_out_output_is_captured() {
+        let test_case = temp_file();
+        let mut test = Command::new(vec!["sh", "-c", "echo started; sleep 30"]);
+        test.set_timeout(Some(time::Duration::from_millis(500)));
+        match test.judge(test_case.path(), true) {
+            Err(error::Error::PredicateTimedOut(_, Some(output))) => {
+                assert_eq!(output.stdout, "started\n");
+            }
+            otherwise => {
+                panic!("Expected Error::PredicateTimedOut with output, found {:?}", otherwise);
+            }
+        }
+    }
+
+    #[test]
+    fn command_fingerprint_tracks_binary() {
+        let dir = tempdir::TempDir::new("preduce-command-fingerprint").unwrap();
+        let program = dir.path().join("predicate");
+        let write_program = |body: &str| {
+            let mut file = fs::File::create(&program).unwrap();
+            writeln!(&mut file, "#!/bin/sh\n{}", body).unwrap();
+        };
+        write_program("exit 0");
+        let mut perms = fs::metadata(&program).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&program, perms).unwrap();
+
+        // The same binary is found by path and by `$PATH` lookup.
+        let by_path = Command::new(vec![program.to_string_lossy().into_owned()]);
+        let mut by_name = Command::new(vec!["predicate"]);
+        by_name.set_env(&[("PATH".into(), dir.path().as_os_str().to_owned())]);
+        let before = by_path.fingerprint().unwrap();
+        assert!(before.contains(&*dir.path().canonicalize().unwrap().to_string_lossy()));
+        assert_eq!(by_name.fingerprint().unwrap(), before);
+
+        // Replacing the binary changes the fingerprint.
+        write_program("exit 1 # a different predicate");
+        assert!(by_path.fingerprint().unwrap() != before);
+
+        // A program that can't be found has no fingerprint.
+        assert!(Command::new(vec!["preduce-no-such-program"]).fingerprint().is_none());
+    }
+
     #[test]
     fn and_both_true() {
         let test = And::new(
@@ -348,6 +1388,18 @@ mod tests {
         assert!(!test.is_interesting(test_case.path()).unwrap());
     }
 
+    #[test]
+    fn and_captures_both_outputs() {
+        let test = And::new(
+            Command::new(vec!["sh", "-c", "echo first"]),
+            Command::new(vec!["sh", "-c", "echo second; exit 1"]),
+        );
+        let test_case = temp_file();
+        let (is_interesting, output) = test.is_interesting_with_output(test_case.path()).unwrap();
+        assert!(!is_interesting);
+        assert_eq!(output.unwrap().stdout, "first\nsecond\n");
+    }
+
     #[test]
     fn or_first_true() {
         let test = Or::new(
@@ -394,6 +1446,99 @@ mod tests {
         assert!(!test.is_interesting(test_case.path()).unwrap());
     }
 
+    /// A predicate that gives each of the `results` in turn.
+    fn flaky(
+        results: &'static [bool],
+    ) -> impl Clone + Send + UnwindSafe + Fn(&path::Path) -> error::Result<bool> {
+        let runs = Arc::new(AtomicUsize::new(0));
+        move |_: &path::Path| Ok(results[runs.fetch_add(1, Ordering::SeqCst)])
+    }
+
+    #[test]
+    fn retry_until_interesting() {
+        let test = Retry::new(flaky(&[false, false, true]), 3);
+        let test_case = temp_file();
+        let (is_interesting, votes) = test.is_interesting_with_votes(test_case.path()).unwrap();
+        assert!(is_interesting);
+        let votes = votes.unwrap();
+        assert_eq!((votes.interesting, votes.runs), (1, 3));
+        assert!(votes.is_near_threshold());
+    }
+
+    #[test]
+    fn retry_stops_at_first_interesting() {
+        let test = Retry::new(flaky(&[true]), 5);
+        let test_case = temp_file();
+        let (is_interesting, votes) = test.is_interesting_with_votes(test_case.path()).unwrap();
+        assert!(is_interesting);
+        assert!(!votes.unwrap().is_near_threshold());
+    }
+
+    #[test]
+    fn retry_all_not_interesting() {
+        let test = Retry::new(flaky(&[false, false, false]), 3);
+        let test_case = temp_file();
+        let (is_interesting, votes) = test.is_interesting_with_votes(test_case.path()).unwrap();
+        assert!(!is_interesting);
+        assert!(!votes.unwrap().is_near_threshold());
+    }
+
+    #[test]
+    fn majority_interesting() {
+        let test = Majority::new(flaky(&[true, false, true]), 2, 3);
+        let test_case = temp_file();
+        let (is_interesting, votes) = test.is_interesting_with_votes(test_case.path()).unwrap();
+        assert!(is_interesting);
+        let votes = votes.unwrap();
+        assert_eq!((votes.interesting, votes.runs), (2, 3));
+        assert!(votes.is_near_threshold());
+    }
+
+    #[test]
+    fn majority_not_interesting() {
+        let test = Majority::new(flaky(&[false, true, false, true, false]), 3, 5);
+        let test_case = temp_file();
+        assert!(!test.is_interesting(test_case.path()).unwrap());
+    }
+
+    #[test]
+    fn majority_stops_when_decided() {
+        let test = Majority::new(flaky(&[false, false]), 2, 3);
+        let test_case = temp_file();
+        let (is_interesting, votes) = test.is_interesting_with_votes(test_case.path()).unwrap();
+        assert!(!is_interesting);
+        let votes = votes.unwrap();
+        assert_eq!(votes.runs, 2);
+        assert!(!votes.is_near_threshold());
+    }
+
+    #[test]
+    fn errored_runs_are_not_interesting() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let test = Majority::new(
+            move |_: &path::Path| match runs.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(error::Error::PredicateTimedOut(time::Duration::from_secs(1), None)),
+                1 => Err(error::Error::ResourceLimitExceeded(ResourceLimit::CpuTime, None)),
+                _ => Ok(true),
+            },
+            2,
+            4,
+        );
+        let test_case = temp_file();
+        let (is_interesting, votes) = test.is_interesting_with_votes(test_case.path()).unwrap();
+        assert!(is_interesting);
+        let votes = votes.unwrap();
+        assert_eq!((votes.interesting, votes.runs), (2, 4));
+    }
+
+    #[test]
+    fn retry_once() {
+        let test = Retry::new(flaky(&[false, true]), 2);
+        let test_case = temp_file();
+        assert!(!test.is_interesting_once(test_case.path()).unwrap());
+        assert!(test.is_interesting_once(test_case.path()).unwrap());
+    }
+
     #[test]
     fn not_executable() {
         match Script::new("./tests/fixtures/lorem-ipsum.txt") {
@@ -21,27 +21,147 @@ extern crate histo;
 extern crate is_executable;
 extern crate lru_cache;
 extern crate num_cpus;
+extern crate preduce_balanced_reducer;
+extern crate preduce_chunks_reducer;
+extern crate preduce_clang_delta_reducer;
+extern crate preduce_clex_reducer;
 extern crate preduce_ipc_types;
+extern crate preduce_ranges_reducer;
+extern crate preduce_reducer_script;
+extern crate preduce_regex_reducer;
+extern crate preduce_topformflat_reducer;
+extern crate regex;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 extern crate serde_json;
 extern crate tempdir;
 extern crate typenum;
 
 mod actors;
+mod cache;
+pub mod capture;
+mod checkpoint;
+mod diff;
 pub mod error;
+pub mod event;
+mod history;
 pub mod interesting;
 pub mod oracle;
+pub mod preflight;
 mod queue;
 pub mod reducers;
+pub mod remote;
+pub mod report;
+pub mod sandbox;
 pub mod score;
 mod signposts;
+mod status;
+mod subprocess;
 pub mod test_case;
 pub mod traits;
 
 #[cfg(test)]
 mod test_utils;
 
+use std::collections::HashMap;
+use std::fmt;
 use std::mem;
 use std::path;
+use std::time;
+
+/// The format of the log file that `preduce` writes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable prose, one message per line.
+    Text,
+
+    /// One JSON object per line, holding the `event::Event` (tagged by its
+    /// `"event"` kind) and a `"timestamp"` in seconds since the Unix epoch.
+    JsonLines,
+}
+
+/// A phase of a reduction: a set of reducers that run together, to completion,
+/// before the next phase's reducers start.
+///
+/// Some reducers are only worth running at the start of a reduction (for
+/// example, ones that inline `#include`s or flatten the test case's
+/// formatting), and others only at the end (for example, ones that reformat
+/// the test case or rename identifiers). Phases let you order them.
+///
+/// ```
+/// # fn _ignore() -> preduce::error::Result<()> {
+/// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+/// let includes = preduce::reducers::Script::new("includes.sh")?;
+/// let chunks = || preduce::reducers::Script::new("chunks.sh");
+/// let clang_format = preduce::reducers::Script::new("clang-format.sh")?;
+///
+/// preduce::Options::new(predicate, vec![Box::new(chunks()?)], "path/to/test-case")
+///     .phases(vec![
+///         preduce::Phase::new("first", vec![Box::new(includes)]),
+///         preduce::Phase::new("main", vec![Box::new(chunks()?)]).repeat_until_fixpoint(true),
+///         preduce::Phase::new("last", vec![Box::new(clang_format)]),
+///     ])
+///     .run()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Phase {
+    name: String,
+    reducers: Vec<Box<traits::Reducer>>,
+    repeat_until_fixpoint: bool,
+}
+
+impl Phase {
+    /// Construct a new phase with the given name and reducers.
+    ///
+    /// By default, a phase runs until its reducers are exhausted on the
+    /// smallest interesting test case, and then the reduction moves on to the
+    /// next phase.
+    ///
+    /// ### Panics
+    ///
+    /// This function panics if the `reducers` vec is empty.
+    pub fn new<N>(name: N, reducers: Vec<Box<traits::Reducer>>) -> Phase
+    where
+        N: Into<String>,
+    {
+        assert!(!reducers.is_empty());
+        Phase {
+            name: name.into(),
+            reducers: reducers,
+            repeat_until_fixpoint: false,
+        }
+    }
+
+    /// Whether this phase should be repeated for as long as it keeps making
+    /// progress, rather than running once.
+    pub fn repeat_until_fixpoint(mut self, should_repeat: bool) -> Phase {
+        self.repeat_until_fixpoint = should_repeat;
+        self
+    }
+
+    /// Get this phase's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get this phase's `Reducer`s.
+    pub fn reducers(&self) -> &[Box<traits::Reducer>] {
+        &self.reducers[..]
+    }
+
+    /// Does this phase repeat until it stops making progress?
+    pub fn repeats_until_fixpoint(&self) -> bool {
+        self.repeat_until_fixpoint
+    }
+
+    /// Take ownership of this phase's `Reducer`s.
+    pub(crate) fn take_reducers(&mut self) -> Vec<Box<traits::Reducer>> {
+        mem::replace(&mut self.reducers, vec![])
+    }
+}
 
 /// A builder to configure a `preduce` run's options, and finally start the
 /// candidate process.
@@ -61,16 +181,69 @@ use std::path;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug)]
 pub struct Options<I>
 where
     I: traits::IsInteresting,
 {
     test_case: path::PathBuf,
     is_interesting: I,
-    reducers: Vec<Box<traits::Reducer>>,
+    phases: Vec<Phase>,
+    reducer_weights: HashMap<String, f64>,
+    oracle: Option<Box<traits::Oracle>>,
     workers: usize,
     print_histograms: bool,
+    checkpoint_dir: Option<path::PathBuf>,
+    checkpoint_interval: time::Duration,
+    resume: bool,
+    cache_dir: Option<path::PathBuf>,
+    capture_output: Option<(path::PathBuf, capture::Retention)>,
+    output: Option<path::PathBuf>,
+    log_file: path::PathBuf,
+    log_format: LogFormat,
+    backup: bool,
+    status: bool,
+    listen: Option<String>,
+    remote_predicate_name: Option<String>,
+    sandbox: Option<sandbox::Sandbox>,
+    preflight: bool,
+    flakiness_check_runs: usize,
+    fairness: queue::Fairness,
+    subscribers: Vec<event::Subscriber>,
+}
+
+// Oracles aren't required to implement `Debug`, so this can't be derived.
+impl<I> fmt::Debug for Options<I>
+where
+    I: fmt::Debug + traits::IsInteresting,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Options")
+            .field("test_case", &self.test_case)
+            .field("is_interesting", &self.is_interesting)
+            .field("phases", &self.phases)
+            .field("reducer_weights", &self.reducer_weights)
+            .field("oracle", &self.oracle.as_ref().map(|_| "Oracle"))
+            .field("workers", &self.workers)
+            .field("print_histograms", &self.print_histograms)
+            .field("checkpoint_dir", &self.checkpoint_dir)
+            .field("checkpoint_interval", &self.checkpoint_interval)
+            .field("resume", &self.resume)
+            .field("cache_dir", &self.cache_dir)
+            .field("capture_output", &self.capture_output)
+            .field("output", &self.output)
+            .field("log_file", &self.log_file)
+            .field("log_format", &self.log_format)
+            .field("backup", &self.backup)
+            .field("status", &self.status)
+            .field("listen", &self.listen)
+            .field("remote_predicate_name", &self.remote_predicate_name)
+            .field("sandbox", &self.sandbox)
+            .field("preflight", &self.preflight)
+            .field("flakiness_check_runs", &self.flakiness_check_runs)
+            .field("fairness", &self.fairness)
+            .field("subscribers", &self.subscribers)
+            .finish()
+    }
 }
 
 /// APIs for configuring options and spawning the candidate process.
@@ -83,6 +256,10 @@ where
     /// You must provide the is-interesting predicate, the test case candidate
     /// generators, and the initial test case.
     ///
+    /// The reducers all run together in a single phase, which repeats until
+    /// none of them can make any more progress. Use `phases` to order the
+    /// reducers into multiple phases instead.
+    ///
     /// ### Panics
     ///
     /// This function panics if the `reducers` vec is empty.
@@ -107,13 +284,72 @@ where
     where
         P: Into<path::PathBuf>,
     {
-        assert!(!reducers.is_empty());
+        let phase = Phase::new("main", reducers).repeat_until_fixpoint(true);
+        Options::with_phases(is_interesting, vec![phase], test_case)
+    }
+
+    /// Construct a new `Options` builder that runs the reduction in the given
+    /// phases, in order.
+    ///
+    /// Each phase runs until its reducers are exhausted on the smallest
+    /// interesting test case (and, if it repeats until fixpoint, until it
+    /// stops making progress) before the next phase starts. See `Phase` for
+    /// details.
+    ///
+    /// ### Panics
+    ///
+    /// This function panics if the `phases` vec is empty.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let includes = preduce::reducers::Script::new("remove_includes.sh")?;
+    /// let lines = preduce::reducers::Script::new("remove_lines.sh")?;
+    ///
+    /// let opts = preduce::Options::with_phases(
+    ///     predicate,
+    ///     vec![
+    ///         preduce::Phase::new("includes", vec![Box::new(includes)]),
+    ///         preduce::Phase::new("lines", vec![Box::new(lines)]),
+    ///     ],
+    ///     "path/to/test-case",
+    /// );
+    /// # let _ = opts;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_phases<P>(is_interesting: I, phases: Vec<Phase>, test_case: P) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        assert!(!phases.is_empty());
         Options {
             test_case: test_case.into(),
             is_interesting: is_interesting,
-            reducers: reducers,
+            phases: phases,
+            reducer_weights: HashMap::new(),
+            oracle: None,
             workers: num_cpus::get(),
             print_histograms: false,
+            checkpoint_dir: None,
+            checkpoint_interval: time::Duration::from_secs(60),
+            resume: false,
+            cache_dir: None,
+            capture_output: None,
+            output: None,
+            log_file: path::PathBuf::from("preduce.log"),
+            log_format: LogFormat::Text,
+            backup: true,
+            status: false,
+            listen: None,
+            remote_predicate_name: None,
+            sandbox: None,
+            preflight: false,
+            flakiness_check_runs: 0,
+            fairness: queue::Fairness::default(),
+            subscribers: vec![],
         }
     }
 
@@ -154,8 +390,584 @@ where
         self
     }
 
+    /// Run the reduction in the given phases, in order, instead of running the
+    /// reducers given to `new` in a single phase.
+    ///
+    /// Each phase runs until its reducers are exhausted on the smallest
+    /// interesting test case (and, if it repeats until fixpoint, until it
+    /// stops making progress) before the next phase starts. See `Phase` for
+    /// an example.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `phases` is empty.
+    pub fn phases(mut self, phases: Vec<Phase>) -> Options<I> {
+        assert!(!phases.is_empty());
+        self.phases = phases;
+        self
+    }
+
+    /// Scale the priority of the candidates generated by the reducer with the
+    /// given name by `weight`.
+    ///
+    /// Weights greater than one make the reducer's candidates more likely to be
+    /// tested before other reducers' candidates, and weights less than one make
+    /// them less likely to be. Reducers default to a weight of one.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .reducer_weight("generate_candidates.sh", 2.0)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `weight` is negative, NaN, or infinite.
+    pub fn reducer_weight<N>(mut self, name: N, weight: f64) -> Options<I>
+    where
+        N: Into<String>,
+    {
+        assert!(weight >= 0.0 && weight.is_finite());
+        self.reducer_weights.insert(name.into(), weight);
+        self
+    }
+
+    /// Prioritize candidates with the given preset oracle, rather than the
+    /// default. See `oracle::Strategy`.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .oracle_strategy(preduce::oracle::Strategy::Bandit)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn oracle_strategy(self, strategy: oracle::Strategy) -> Options<I> {
+        self.oracle(strategy.oracle())
+    }
+
+    /// Prioritize candidates with the given oracle, rather than the default.
+    ///
+    /// Oracles can be combined with the `oracle::Join*` combinators, and
+    /// their scores scaled with `oracle::Weighted`.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// use preduce::oracle;
+    ///
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     // Mostly go for the biggest reductions, but take the historical
+    ///     // interesting rate of each reducer into account too.
+    ///     .oracle(Box::new(oracle::Join4::new(
+    ///         oracle::Weighted::new(oracle::PercentReduced::default(), 3.0),
+    ///         oracle::InterestingRate::default(),
+    ///         oracle::HaveWeSeenIt::default(),
+    ///         oracle::HaveWeTriedIt::default(),
+    ///     )))
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn oracle(mut self, oracle: Box<traits::Oracle>) -> Options<I> {
+        self.oracle = Some(oracle);
+        self
+    }
+
+    /// Whether we should show a live status view of the reduction on stdout.
+    ///
+    /// When stdout is a terminal, this is a dashboard that is redrawn in place,
+    /// showing the current size, candidates tested per second, each worker's
+    /// state, the candidate queue's length, the exhausted reducers, and the
+    /// reducer that most recently made progress. Otherwise, a one-line summary
+    /// is printed every few seconds.
+    pub fn status(mut self, should_show_status: bool) -> Options<I> {
+        self.status = should_show_status;
+        self
+    }
+
+    /// Regularly save checkpoints of the reduction's progress into the given
+    /// directory, so that it can be resumed later with `resume` if it is
+    /// interrupted.
+    ///
+    /// A checkpoint contains the smallest interesting test case found so far,
+    /// the oracle's observations, and the reducers' states.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .checkpoint_dir("path/to/checkpoint")
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn checkpoint_dir<P>(mut self, dir: P) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.checkpoint_dir = Some(dir.into());
+        self
+    }
+
+    /// How often to save a checkpoint, when checkpointing with `checkpoint_dir`
+    /// or `resume`. Defaults to every 60 seconds.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// use std::time::Duration;
+    ///
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .checkpoint_dir("path/to/checkpoint")
+    ///     .checkpoint_interval(Duration::from_secs(10 * 60))
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn checkpoint_interval(mut self, interval: time::Duration) -> Options<I> {
+        assert!(interval != time::Duration::from_secs(0));
+        self.checkpoint_interval = interval;
+        self
+    }
+
+    /// Resume a previously interrupted reduction from the checkpoint in the
+    /// given directory, and continue saving checkpoints there.
+    ///
+    /// The smallest interesting test case from the checkpoint is copied over
+    /// the test case file, and the original test case is not backed up again.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .resume("path/to/checkpoint")
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resume<P>(mut self, dir: P) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.checkpoint_dir = Some(dir.into());
+        self.resume = true;
+        self
+    }
+
+    /// Cache the is-interesting predicate's results in the given directory,
+    /// and reuse results cached there by previous runs.
+    ///
+    /// Results are keyed by the candidate's contents and the predicate's
+    /// fingerprint, so changing the predicate invalidates its cached results.
+    /// Predicates without a fingerprint are never cached.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .cache_dir("path/to/cache")
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cache_dir<P>(mut self, dir: P) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Capture the is-interesting predicate's stdout and stderr for each
+    /// candidate it judges into a file in the given directory, keeping the
+    /// files allowed by the `retention` policy. The log links each judgement
+    /// to its output file. See the `capture` module for details.
+    ///
+    /// Results found in the cache, and candidates judged by remote workers,
+    /// have no captured output.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     // Keep the output of the last 100 candidates judged.
+    ///     .capture_output("path/to/output", preduce::capture::Retention::Last(100))
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capture_output<P>(mut self, dir: P, retention: capture::Retention) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.capture_output = Some((dir.into(), retention));
+        self
+    }
+
+    /// Write the smallest interesting test case to the given path, rather than
+    /// overwriting the initial test case in place.
+    ///
+    /// The initial test case is left untouched, and is therefore not backed
+    /// up. The output is replaced with each new smallest interesting test
+    /// case as the reduction progresses.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .output("path/to/reduced")
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn output<P>(mut self, path: P) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.output = Some(path.into());
+        self
+    }
+
+    /// Write the detailed log to the given file. Defaults to `preduce.log` in
+    /// the current directory.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .log_file("path/to/reduction.log")
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn log_file<P>(mut self, path: P) -> Options<I>
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.log_file = path.into();
+        self
+    }
+
+    /// Set the format of the log file. Defaults to `LogFormat::Text`.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .log_file("path/to/reduction.jsonl")
+    ///     .log_format(preduce::LogFormat::JsonLines)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn log_format(mut self, format: LogFormat) -> Options<I> {
+        self.log_format = format;
+        self
+    }
+
+    /// Whether we should back up the initial test case to `<test case>.orig`
+    /// before overwriting it in place. Defaults to `true`.
+    ///
+    /// Has no effect when an `output` is given, since the initial test case is
+    /// never overwritten then.
+    pub fn backup(mut self, should_backup: bool) -> Options<I> {
+        self.backup = should_backup;
+        self
+    }
+
+    /// Accept connections from remote workers on the given address, and have
+    /// them judge candidates alongside the local workers.
+    ///
+    /// Remote workers run their own copy of the is-interesting predicate, and
+    /// are asked to judge each candidate with their predicate of the given
+    /// name. See `remote::RemoteWorker` and the `preduce-worker` executable. A
+    /// remote worker that disconnects is not replaced, but may connect again.
+    /// Remote workers can only judge test cases that are single files.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .listen("0.0.0.0:4000", "is_interesting.sh")
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn listen<A, N>(mut self, addr: A, predicate_name: N) -> Options<I>
+    where
+        A: Into<String>,
+        N: Into<String>,
+    {
+        self.listen = Some(addr.into());
+        self.remote_predicate_name = Some(predicate_name.into());
+        self
+    }
+
+    /// Apply the given resource limits to the is-interesting predicate's
+    /// processes, so that a candidate that sends the predicate into a runaway
+    /// allocation or an infinite loop can't take the whole machine down with
+    /// it.
+    ///
+    /// A candidate whose predicate exceeds a limit is treated as not
+    /// interesting, and logged separately. Has no effect on predicates that
+    /// don't spawn processes.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .resource_limits(preduce::interesting::ResourceLimits {
+    ///         address_space: Some(4 << 30),
+    ///         cpu_seconds: Some(60),
+    ///         ..Default::default()
+    ///     })
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resource_limits(mut self, limits: interesting::ResourceLimits) -> Options<I> {
+        self.is_interesting.set_resource_limits(&limits);
+        self
+    }
+
+    /// Whether to run the `preflight` checks of the is-interesting predicate
+    /// before starting the reduction: that the initial test case is
+    /// interesting, and that an empty test case and a garbage test case are
+    /// not. Defaults to `false`, in which case the initial test case is only
+    /// checked to be interesting.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .preflight(true)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn preflight(mut self, should_preflight: bool) -> Options<I> {
+        self.preflight = should_preflight;
+        self
+    }
+
+    /// Before starting the reduction, run the is-interesting predicate on the
+    /// initial test case `runs` more times, and warn with the observed
+    /// flakiness rate if any of those runs don't find it interesting. By
+    /// default, the predicate is assumed to be deterministic, and is only run
+    /// once.
+    ///
+    /// Flaky predicates can be made more reliable with `interesting::Retry`
+    /// or `interesting::Majority`. Their inner predicate is run only once per
+    /// check, so that the check measures the flakiness that they hide.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .flakiness_check(10)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn flakiness_check(mut self, runs: usize) -> Options<I> {
+        self.flakiness_check_runs = runs;
+        self
+    }
+
+    /// Limit how many workers one reducer's candidates may occupy at once,
+    /// while other reducers' candidates are waiting to be tested. By default,
+    /// there is no limit, and the oracle's highest scoring candidates always go
+    /// first.
+    ///
+    /// Workers are never left idle because of the quota: when only the
+    /// candidates of reducers that are over their quota are waiting, they are
+    /// tested anyway.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .workers(8)
+    ///     // Leave room for at least two other reducers' candidates.
+    ///     .reducer_quota(6)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `quota` is zero.
+    pub fn reducer_quota(mut self, quota: usize) -> Options<I> {
+        assert!(quota != 0);
+        self.fairness.quota = Some(quota);
+        self
+    }
+
+    /// Age queued candidates so that low scoring candidates are not starved
+    /// forever: each time a candidate is passed over `passes` times in favor
+    /// of others, it moves ahead of every candidate that has been passed over
+    /// fewer times, regardless of their scores. Zero disables aging, which is
+    /// the default.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .priority_aging(16)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn priority_aging(mut self, passes: usize) -> Options<I> {
+        self.fairness.aging = passes;
+        self
+    }
+
+    /// Reserve at least the given fraction of candidates tested for the
+    /// reducer that has had the fewest of its candidates tested so far,
+    /// regardless of their scores. Zero disables exploration, which is the
+    /// default.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .exploration_share(0.25)
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `share` is not between 0 and 1.
+    pub fn exploration_share(mut self, share: f64) -> Options<I> {
+        assert!(share >= 0.0 && share <= 1.0);
+        self.fairness.exploration = share;
+        self
+    }
+
+    /// Run the is-interesting predicate in a private directory for each worker,
+    /// alongside the sandbox's auxiliary files, rather than in each candidate's
+    /// temporary directory. See the `sandbox` module for details.
+    ///
+    /// Remote workers are not sandboxed.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case.c";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .sandbox(preduce::sandbox::Sandbox::new().aux_file("path/to/header.h"))
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sandbox(mut self, sandbox: sandbox::Sandbox) -> Options<I> {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Call the given function with each event that happens during the
+    /// reduction, such as finding a new smallest interesting test case.
+    ///
+    /// The function is called on the logging thread, so it should return
+    /// quickly. It may be called with events after `run` has returned an
+    /// error. Calling `on_event` more than once subscribes each function.
+    ///
+    /// ```
+    /// # fn _ignore() -> preduce::error::Result<()> {
+    /// use preduce::event::Event;
+    ///
+    /// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+    /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+    /// let test_case = "path/to/test-case";
+    ///
+    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+    ///     .on_event(Box::new(|event: &Event| {
+    ///         if let Event::NewSmallest { ref interesting, .. } = *event {
+    ///             println!("{} bytes, thanks to {}", interesting.size, interesting.provenance);
+    ///         }
+    ///     }))
+    ///     .run()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_event(mut self, f: Box<Fn(&event::Event) + Send + 'static>) -> Options<I> {
+        self.subscribers.push(event::Subscriber::new(f));
+        self
+    }
+
     /// Finish configuration and run the test case candidate process to
-    /// completion.
+    /// completion, returning a summary of the reduction.
     ///
     /// ```
     /// # fn _ignore() -> preduce::error::Result<()> {
@@ -163,11 +975,17 @@ where
     /// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
     /// let test_case = "path/to/test-case";
     ///
-    /// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case).run()?;
+    /// let report = preduce::Options::new(predicate, vec![Box::new(reducer)], test_case).run()?;
+    /// println!(
+    ///     "Reduced {} from {} to {} bytes",
+    ///     report.final_path.display(),
+    ///     report.original_size,
+    ///     report.final_size
+    /// );
     /// # Ok(())
     /// # }
     /// ```
-    pub fn run(self) -> error::Result<()> {
+    pub fn run(self) -> error::Result<report::ReductionReport> {
         // We must be robust in the face of one of our reducer scripts dying
         // while we're still trying to communicate with it. We are set up to
         // handle `Err` results properly, but we can't tolerate receiving
@@ -176,8 +994,7 @@ where
         let _ignore_sigpipe = sig::AutoIgnoreSigpipe::default();
 
         let (_, handle) = actors::Supervisor::spawn(self)?;
-        handle.join()??;
-        Ok(())
+        handle.join()?
     }
 }
 
@@ -192,24 +1009,60 @@ where
         self.workers
     }
 
+    /// Get the path that the smallest interesting test case is written to:
+    /// either the configured `output`, or the initial test case itself.
+    pub fn output_path(&self) -> &path::Path {
+        self.output.as_ref().unwrap_or(&self.test_case)
+    }
+
     /// Get this `Options`' `IsInteresting` predicate.
     pub fn predicate(&self) -> &I {
         &self.is_interesting
     }
 
-    /// Get this `Options`' `Reducer`s.
+    /// Get the weight of the reducer with the given name.
+    pub fn weight_of_reducer(&self, name: &str) -> f64 {
+        self.reducer_weights.get(name).cloned().unwrap_or(1.0)
+    }
+
+    /// Get the `Reducer`s of this `Options`' first phase, which are all of the
+    /// `Reducer`s given to `new`. Use `reduction_phases` to get the `Reducer`s
+    /// of every phase.
     pub fn reducers(&self) -> &[Box<traits::Reducer>] {
-        &self.reducers[..]
+        self.phases.first().map_or(&[], |phase| phase.reducers())
     }
 
-    /// Take ownership of this `Options`' `Reducer`s. Panics if the reducers
-    /// have already been taken.
-    pub(crate) fn take_reducers(&mut self) -> Vec<Box<traits::Reducer>> {
+    /// Get this `Options`' phases, in the order they run.
+    pub fn reduction_phases(&self) -> &[Phase] {
+        &self.phases[..]
+    }
+
+    /// Take ownership of this `Options`' oracle, or construct the default
+    /// oracle if none was configured.
+    pub(crate) fn take_oracle(&mut self) -> Box<traits::Oracle> {
+        self.oracle
+            .take()
+            .unwrap_or_else(|| oracle::Strategy::default().oracle())
+    }
+
+    /// Get how the candidate queue should share workers between reducers.
+    pub(crate) fn fairness(&self) -> queue::Fairness {
+        self.fairness
+    }
+
+    /// Take ownership of this `Options`' event subscribers.
+    pub(crate) fn take_subscribers(&mut self) -> Vec<event::Subscriber> {
+        mem::replace(&mut self.subscribers, vec![])
+    }
+
+    /// Take ownership of this `Options`' phases. Panics if the phases have
+    /// already been taken.
+    pub(crate) fn take_phases(&mut self) -> Vec<Phase> {
         assert!(
-            !self.reducers.is_empty(),
-            "should not have already taken the reducers"
+            !self.phases.is_empty(),
+            "should not have already taken the phases"
         );
-        mem::replace(&mut self.reducers, vec![])
+        mem::replace(&mut self.phases, vec![])
     }
 }
 
@@ -1,13 +1,67 @@
 //! Determining the priority of candidates.
 
+use error;
 use fixedbitset::FixedBitSet;
 use lru_cache;
 use score::Score;
+use serde_json;
 use std::collections::HashMap;
+use std::str;
+use std::time;
 use test_case::{self, TestCaseMethods};
 use traits;
 
-#[derive(Debug, Default)]
+impl traits::Oracle for Box<traits::Oracle> {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        (**self).observe_smallest_interesting(interesting, elapsed)
+    }
+
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        (**self).observe_not_smallest_interesting(interesting, elapsed)
+    }
+
+    fn observe_not_interesting(
+        &mut self,
+        candidate: &test_case::Candidate,
+        elapsed: time::Duration,
+    ) {
+        (**self).observe_not_interesting(candidate, elapsed)
+    }
+
+    fn observe_timed_out(&mut self, candidate: &test_case::Candidate, elapsed: time::Duration) {
+        (**self).observe_timed_out(candidate, elapsed)
+    }
+
+    fn observe_exhausted(&mut self, reducer_name: &str) {
+        (**self).observe_exhausted(reducer_name)
+    }
+
+    fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
+        (**self).predict(candidate)
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        (**self).snapshot()
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        (**self).restore(snapshot, smallest)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 struct Observed {
     smallest_interesting_count: u32,
     not_smallest_interesting_count: u32,
@@ -33,21 +87,29 @@ pub struct InterestingRate {
 }
 
 impl traits::Oracle for InterestingRate {
-    fn observe_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.observations
             .entry(interesting.provenance().into())
             .or_insert_with(Default::default)
             .smallest_interesting_count += 1;
     }
 
-    fn observe_not_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.observations
             .entry(interesting.provenance().into())
             .or_insert_with(Default::default)
             .not_smallest_interesting_count += 1;
     }
 
-    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate) {
+    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate, _: time::Duration) {
         self.observations
             .entry(candidate.provenance().into())
             .or_insert_with(Default::default)
@@ -67,113 +129,145 @@ impl traits::Oracle for InterestingRate {
             Score::new(observed.interesting() as f64 / total as f64)
         }
     }
-}
 
-macro_rules! creduce_pass {
-    ( $pass:expr ) => {
-        concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-", $pass)
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::to_value(&self.observations).expect("observations should always serialize")
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        self.observations = serde_json::from_value(snapshot)?;
+        Ok(())
     }
 }
 
+/// The reducer scripts for `preduce`'s built-in reducers are named
+/// `preduce-reducer-<name>`, and live in this directory.
+const REDUCER_SCRIPT_PREFIX: &'static str =
+    concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-");
+
+/// The built-in reducers that were ported from C-Reduce passes, in C-Reduce's
+/// pass order.
 const CREDUCE_PASSES: &'static [&'static str] = &[
-    creduce_pass!("blank"),
-    creduce_pass!("chunks"),
-    creduce_pass!("topformflat-0"),
-    creduce_pass!("topformflat-1"),
-    creduce_pass!("topformflat-2"),
-    creduce_pass!("topformflat-3"),
-    creduce_pass!("topformflat-4"),
-    creduce_pass!("topformflat-6"),
-    creduce_pass!("topformflat-8"),
-    creduce_pass!("topformflat-10"),
-    creduce_pass!("clang-delta-remove-namespace"),
-    creduce_pass!("clang-delta-aggregate-to-scalar"),
-    creduce_pass!("clang-delta-local-to-global"),
-    creduce_pass!("clang-delta-param-to-global"),
-    creduce_pass!("clang-delta-param-to-local"),
-    creduce_pass!("clang-delta-remove-nested-function"),
-    creduce_pass!("clang-delta-rename-fun"),
-    creduce_pass!("clang-delta-union-to-struct"),
-    creduce_pass!("clang-delta-rename-param"),
-    creduce_pass!("clang-delta-rename-var"),
-    creduce_pass!("clang-delta-rename-class"),
-    creduce_pass!("clang-delta-rename-cxx-method"),
-    creduce_pass!("clang-delta-return-void"),
-    creduce_pass!("clang-delta-simple-inliner"),
-    creduce_pass!("clang-delta-reduce-pointer-level"),
-    creduce_pass!("clang-delta-lift-assignment-expr"),
-    creduce_pass!("clang-delta-copy-propagation"),
-    creduce_pass!("clang-delta-callexpr-to-value"),
-    creduce_pass!("clang-delta-replace-callexpr"),
-    creduce_pass!("clang-delta-simplify-callexpr"),
-    creduce_pass!("clang-delta-remove-unused-function"),
-    creduce_pass!("clang-delta-remove-unused-enum-member"),
-    creduce_pass!("clang-delta-remove-enum-member-value"),
-    creduce_pass!("clang-delta-simplify-if"),
-    creduce_pass!("clang-delta-reduce-array-dim"),
-    creduce_pass!("clang-delta-reduce-array-size"),
-    creduce_pass!("clang-delta-move-function-body"),
-    creduce_pass!("clang-delta-simplify-comma-expr"),
-    creduce_pass!("clang-delta-simplify-dependent-typedef"),
-    creduce_pass!("clang-delta-replace-simple-typedef"),
-    creduce_pass!("clang-delta-replace-dependent-typedef"),
-    creduce_pass!("clang-delta-replace-one-level-typedef-type"),
-    creduce_pass!("clang-delta-remove-unused-field"),
-    creduce_pass!("clang-delta-instantiate-template-type-param-to-int"),
-    creduce_pass!("clang-delta-instantiate-template-param"),
-    creduce_pass!("clang-delta-template-arg-to-int"),
-    creduce_pass!("clang-delta-template-non-type-arg-to-int"),
-    creduce_pass!("clang-delta-reduce-class-template-param"),
-    creduce_pass!("clang-delta-remove-trivial-base-template"),
-    creduce_pass!("clang-delta-class-template-to-class"),
-    creduce_pass!("clang-delta-remove-base-class"),
-    creduce_pass!("clang-delta-replace-derived-class"),
-    creduce_pass!("clang-delta-remove-unresolved-base"),
-    creduce_pass!("clang-delta-remove-ctor-initializer"),
-    creduce_pass!("clang-delta-replace-class-with-base-template-spec"),
-    creduce_pass!("clang-delta-simplify-nested-class"),
-    creduce_pass!("clang-delta-remove-unused-outer-class"),
-    creduce_pass!("clang-delta-empty-struct-to-int"),
-    creduce_pass!("clang-delta-remove-pointer"),
-    creduce_pass!("clang-delta-reduce-pointer-pairs"),
-    creduce_pass!("clang-delta-remove-array"),
-    creduce_pass!("clang-delta-remove-addr-taken"),
-    creduce_pass!("clang-delta-simplify-struct"),
-    creduce_pass!("clang-delta-replace-undefined-function"),
-    creduce_pass!("clang-delta-replace-array-index-var"),
-    creduce_pass!("clang-delta-replace-array-access-with-index"),
-    creduce_pass!("clang-delta-replace-dependent-name"),
-    creduce_pass!("clang-delta-simplify-recursive-template-instantiation"),
-    creduce_pass!("clang-delta-vector-to-array"),
-    creduce_pass!("clang-delta-combine-global-var"),
-    creduce_pass!("clang-delta-combine-local-var"),
-    creduce_pass!("clang-delta-simplify-struct-union-decl"),
-    creduce_pass!("clang-delta-move-global-var"),
-    creduce_pass!("clang-delta-unify-function-decl"),
-    creduce_pass!("clang-format"),
-    creduce_pass!("clex-delete-string"),
-    creduce_pass!("clex-rm-toks-1"),
-    creduce_pass!("clex-rm-toks-2"),
-    creduce_pass!("clex-rm-toks-3"),
-    creduce_pass!("clex-rm-toks-4"),
-    creduce_pass!("clex-rm-toks-5"),
-    creduce_pass!("clex-rm-toks-6"),
-    creduce_pass!("clex-rm-toks-7"),
-    creduce_pass!("clex-rm-toks-8"),
-    creduce_pass!("clex-rm-toks-9"),
-    creduce_pass!("clex-rm-toks-10"),
-    creduce_pass!("clex-rm-toks-11"),
-    creduce_pass!("clex-rm-toks-12"),
-    creduce_pass!("clex-rm-toks-13"),
-    creduce_pass!("clex-rm-toks-14"),
-    creduce_pass!("clex-rm-toks-15"),
-    creduce_pass!("clex-rm-toks-16"),
-    creduce_pass!("clex-rm-tok-pattern-4"),
-    creduce_pass!("clex-rename-toks"),
-    creduce_pass!("clex-delete-string"),
-    creduce_pass!("clex-define"),
+    "blank",
+    "chunks",
+    "topformflat-0",
+    "topformflat-1",
+    "topformflat-2",
+    "topformflat-3",
+    "topformflat-4",
+    "topformflat-6",
+    "topformflat-8",
+    "topformflat-10",
+    "clang-delta-remove-namespace",
+    "clang-delta-aggregate-to-scalar",
+    "clang-delta-local-to-global",
+    "clang-delta-param-to-global",
+    "clang-delta-param-to-local",
+    "clang-delta-remove-nested-function",
+    "clang-delta-rename-fun",
+    "clang-delta-union-to-struct",
+    "clang-delta-rename-param",
+    "clang-delta-rename-var",
+    "clang-delta-rename-class",
+    "clang-delta-rename-cxx-method",
+    "clang-delta-return-void",
+    "clang-delta-simple-inliner",
+    "clang-delta-reduce-pointer-level",
+    "clang-delta-lift-assignment-expr",
+    "clang-delta-copy-propagation",
+    "clang-delta-callexpr-to-value",
+    "clang-delta-replace-callexpr",
+    "clang-delta-simplify-callexpr",
+    "clang-delta-remove-unused-function",
+    "clang-delta-remove-unused-enum-member",
+    "clang-delta-remove-enum-member-value",
+    "clang-delta-simplify-if",
+    "clang-delta-reduce-array-dim",
+    "clang-delta-reduce-array-size",
+    "clang-delta-move-function-body",
+    "clang-delta-simplify-comma-expr",
+    "clang-delta-simplify-dependent-typedef",
+    "clang-delta-replace-simple-typedef",
+    "clang-delta-replace-dependent-typedef",
+    "clang-delta-replace-one-level-typedef-type",
+    "clang-delta-remove-unused-field",
+    "clang-delta-instantiate-template-type-param-to-int",
+    "clang-delta-instantiate-template-param",
+    "clang-delta-template-arg-to-int",
+    "clang-delta-template-non-type-arg-to-int",
+    "clang-delta-reduce-class-template-param",
+    "clang-delta-remove-trivial-base-template",
+    "clang-delta-class-template-to-class",
+    "clang-delta-remove-base-class",
+    "clang-delta-replace-derived-class",
+    "clang-delta-remove-unresolved-base",
+    "clang-delta-remove-ctor-initializer",
+    "clang-delta-replace-class-with-base-template-spec",
+    "clang-delta-simplify-nested-class",
+    "clang-delta-remove-unused-outer-class",
+    "clang-delta-empty-struct-to-int",
+    "clang-delta-remove-pointer",
+    "clang-delta-reduce-pointer-pairs",
+    "clang-delta-remove-array",
+    "clang-delta-remove-addr-taken",
+    "clang-delta-simplify-struct",
+    "clang-delta-replace-undefined-function",
+    "clang-delta-replace-array-index-var",
+    "clang-delta-replace-array-access-with-index",
+    "clang-delta-replace-dependent-name",
+    "clang-delta-simplify-recursive-template-instantiation",
+    "clang-delta-vector-to-array",
+    "clang-delta-combine-global-var",
+    "clang-delta-combine-local-var",
+    "clang-delta-simplify-struct-union-decl",
+    "clang-delta-move-global-var",
+    "clang-delta-unify-function-decl",
+    "clang-format",
+    "clex-delete-string",
+    "clex-rm-toks-1",
+    "clex-rm-toks-2",
+    "clex-rm-toks-3",
+    "clex-rm-toks-4",
+    "clex-rm-toks-5",
+    "clex-rm-toks-6",
+    "clex-rm-toks-7",
+    "clex-rm-toks-8",
+    "clex-rm-toks-9",
+    "clex-rm-toks-10",
+    "clex-rm-toks-11",
+    "clex-rm-toks-12",
+    "clex-rm-toks-13",
+    "clex-rm-toks-14",
+    "clex-rm-toks-15",
+    "clex-rm-toks-16",
+    "clex-rm-tok-pattern-4",
+    "clex-rename-toks",
+    "clex-delete-string",
+    "clex-define",
 ];
 
+/// Get the index of the C-Reduce pass for the reducer with the given name,
+/// whether it is running in-process or as a reducer script.
+fn creduce_pass_index(reducer_name: &str) -> Option<usize> {
+    let name = if reducer_name.starts_with(REDUCER_SCRIPT_PREFIX) {
+        &reducer_name[REDUCER_SCRIPT_PREFIX.len()..]
+    } else {
+        reducer_name
+    };
+    CREDUCE_PASSES.iter().position(|p| *p == name)
+}
+
+/// The serialized form of `CreducePassPriorities`.
+#[derive(Deserialize, Serialize)]
+struct CreducePassPrioritiesSnapshot {
+    current_idx: usize,
+    exhausted: Vec<usize>,
+}
+
 /// An oracle that uses C-Reduce's pass ordering to give priority to candidates
 /// generated by a reducer that was ported from a C-Reduce pass.
 #[derive(Debug)]
@@ -192,21 +286,26 @@ impl Default for CreducePassPriorities {
 }
 
 impl traits::Oracle for CreducePassPriorities {
-    fn observe_smallest_interesting(&mut self, _: &test_case::Interesting) {
+    fn observe_smallest_interesting(&mut self, _: &test_case::Interesting, _: time::Duration) {
         self.exhausted.clear();
 
         debug_assert!(CREDUCE_PASSES.iter().all(|p| {
             use std::path::Path;
-            let path = Path::new(p);
-            path.exists()
+            let path = format!("{}{}", REDUCER_SCRIPT_PREFIX, p);
+            Path::new(&path).exists()
         }));
     }
 
-    fn observe_not_smallest_interesting(&mut self, _: &test_case::Interesting) {}
-    fn observe_not_interesting(&mut self, _: &test_case::Candidate) {}
+    fn observe_not_smallest_interesting(
+        &mut self,
+        _: &test_case::Interesting,
+        _: time::Duration,
+    ) {
+    }
+    fn observe_not_interesting(&mut self, _: &test_case::Candidate, _: time::Duration) {}
 
     fn observe_exhausted(&mut self, reducer_name: &str) {
-        let idx = CREDUCE_PASSES.iter().position(|p| *p == reducer_name);
+        let idx = creduce_pass_index(reducer_name);
         if let Some(idx) = idx {
             self.exhausted.insert(idx);
 
@@ -226,9 +325,7 @@ impl traits::Oracle for CreducePassPriorities {
     }
 
     fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
-        let idx = CREDUCE_PASSES
-            .iter()
-            .position(|p| *p == candidate.provenance());
+        let idx = creduce_pass_index(candidate.provenance());
         if let Some(mut idx) = idx {
             if idx < self.current_idx {
                 idx += CREDUCE_PASSES.len();
@@ -239,6 +336,30 @@ impl traits::Oracle for CreducePassPriorities {
 
         Score::new(0.0)
     }
+
+    fn snapshot(&self) -> serde_json::Value {
+        let snapshot = CreducePassPrioritiesSnapshot {
+            current_idx: self.current_idx,
+            exhausted: self.exhausted.ones().collect(),
+        };
+        serde_json::to_value(&snapshot).expect("snapshot should always serialize")
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        let snapshot: CreducePassPrioritiesSnapshot = serde_json::from_value(snapshot)?;
+        self.current_idx = snapshot.current_idx % CREDUCE_PASSES.len();
+        self.exhausted.clear();
+        for idx in snapshot.exhausted {
+            if idx < CREDUCE_PASSES.len() {
+                self.exhausted.insert(idx);
+            }
+        }
+        Ok(())
+    }
 }
 
 /// An `Oracle` that scores candidates by how much they were able to
@@ -249,12 +370,21 @@ pub struct PercentReduced {
 }
 
 impl traits::Oracle for PercentReduced {
-    fn observe_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.smallest = Some(interesting.clone());
     }
 
-    fn observe_not_smallest_interesting(&mut self, _: &test_case::Interesting) {}
-    fn observe_not_interesting(&mut self, _: &test_case::Candidate) {}
+    fn observe_not_smallest_interesting(
+        &mut self,
+        _: &test_case::Interesting,
+        _: time::Duration,
+    ) {
+    }
+    fn observe_not_interesting(&mut self, _: &test_case::Candidate, _: time::Duration) {}
     fn observe_exhausted(&mut self, _: &str) {}
 
     fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
@@ -266,10 +396,54 @@ impl traits::Oracle for PercentReduced {
 
         Score::new(0.0)
     }
+
+    // The smallest interesting test case is saved in checkpoints on its own, so
+    // we only need to remember whether we had observed one.
+
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::Value::Bool(self.smallest.is_some())
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        self.smallest = match snapshot {
+            serde_json::Value::Bool(true) => Some(smallest.clone()),
+            _ => None,
+        };
+        Ok(())
+    }
 }
 
 const LRU_CAPACITY: usize = 4096;
 
+/// Serialize an LRU cache of hashes as a list of hex strings, from least to
+/// most recently used.
+fn snapshot_lru(lru: &lru_cache::LruCache<test_case::Blake2Hash, ()>) -> serde_json::Value {
+    let hashes: Vec<_> = lru.iter()
+        .map(|(hash, _)| serde_json::Value::String(test_case::hash_to_hex(hash)))
+        .collect();
+    serde_json::Value::Array(hashes)
+}
+
+/// Restore an LRU cache of hashes that was serialized with `snapshot_lru`.
+fn restore_lru(
+    lru: &mut lru_cache::LruCache<test_case::Blake2Hash, ()>,
+    snapshot: serde_json::Value,
+) -> error::Result<()> {
+    let hashes: Vec<String> = serde_json::from_value(snapshot)?;
+    lru.clear();
+    for hex in hashes {
+        let hash = test_case::hash_from_hex(&hex).ok_or_else(|| {
+            error::Error::InvalidCheckpoint(format!("invalid hash: {}", hex))
+        })?;
+        lru.insert(hash, ());
+    }
+    Ok(())
+}
+
 /// An oracle that keeps track of test cases we've already seen before
 /// (conservatively, based on `TestCaseMethods::full_hash`) and severely
 /// de-prioritizes test cases we've already seen.
@@ -287,15 +461,23 @@ impl Default for HaveWeSeenIt {
 }
 
 impl traits::Oracle for HaveWeSeenIt {
-    fn observe_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.seen.insert(interesting.full_hash(), ());
     }
 
-    fn observe_not_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.seen.insert(interesting.full_hash(), ());
     }
 
-    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate) {
+    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate, _: time::Duration) {
         self.seen.insert(candidate.full_hash(), ());
     }
 
@@ -308,6 +490,18 @@ impl traits::Oracle for HaveWeSeenIt {
             Score::new(1.0)
         }
     }
+
+    fn snapshot(&self) -> serde_json::Value {
+        snapshot_lru(&self.seen)
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        restore_lru(&mut self.seen, snapshot)
+    }
 }
 
 /// An oracle that keeps track of reductions we've already tried before
@@ -327,15 +521,23 @@ impl Default for HaveWeTriedIt {
 }
 
 impl traits::Oracle for HaveWeTriedIt {
-    fn observe_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.tried.insert(interesting.diff_hash(), ());
     }
 
-    fn observe_not_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        _: time::Duration,
+    ) {
         self.tried.insert(interesting.diff_hash(), ());
     }
 
-    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate) {
+    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate, _: time::Duration) {
         self.tried.insert(candidate.diff_hash(), ());
     }
 
@@ -348,6 +550,396 @@ impl traits::Oracle for HaveWeTriedIt {
             Score::new(1.0)
         }
     }
+
+    fn snapshot(&self) -> serde_json::Value {
+        snapshot_lru(&self.tried)
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        restore_lru(&mut self.tried, snapshot)
+    }
+}
+
+/// The score of candidates from a reducer that the `Bandit` has not yet seen
+/// any judgements of, so that every reducer gets tried.
+const UNTRIED_SCORE: f64 = 1e6;
+
+/// The least predicate time that the `Bandit` and `CostAware` oracles charge
+/// for judgements, so that a few instant judgements (for example, cache hits)
+/// don't make a reducer's rates infinite.
+const MIN_JUDGEMENT_SECS: f64 = 0.001;
+
+fn as_secs(elapsed: time::Duration) -> f64 {
+    elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9
+}
+
+/// A `Bandit` arm's observations of one reducer.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Arm {
+    /// How many of the reducer's candidates have been judged.
+    pulls: u32,
+
+    /// How many bytes the reducer's candidates removed from the smallest
+    /// interesting test case.
+    bytes_removed: u64,
+
+    /// How long it took to judge the reducer's candidates, in seconds.
+    seconds: f64,
+}
+
+impl Arm {
+    /// The bytes removed per second of predicate time.
+    fn reward_rate(&self) -> f64 {
+        self.bytes_removed as f64 / self.seconds.max(MIN_JUDGEMENT_SECS)
+    }
+}
+
+/// An `Oracle` that treats each reducer (by provenance) as an arm of a
+/// multi-armed bandit, and picks between them with the UCB1 algorithm.
+///
+/// A reducer's reward is the number of bytes its candidates removed from the
+/// smallest interesting test case per second of predicate time spent judging
+/// them, relative to the most rewarding reducer. Each candidate is scored with
+/// its reducer's reward plus an exploration bonus that shrinks as more of the
+/// reducer's candidates are judged. Unlike `InterestingRate`, this keeps
+/// testing reducers that got off to a bad start, or haven't been tried yet,
+/// every so often.
+#[derive(Debug, Default)]
+pub struct Bandit {
+    arms: HashMap<String, Arm>,
+    pulls: u32,
+}
+
+impl Bandit {
+    fn pull(&mut self, provenance: &str, bytes_removed: u64, elapsed: time::Duration) {
+        self.pulls += 1;
+        let arm = self.arms
+            .entry(provenance.into())
+            .or_insert_with(Default::default);
+        arm.pulls += 1;
+        arm.bytes_removed += bytes_removed;
+        arm.seconds += as_secs(elapsed);
+    }
+}
+
+impl traits::Oracle for Bandit {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        self.pull(interesting.provenance(), interesting.delta(), elapsed);
+    }
+
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        self.pull(interesting.provenance(), 0, elapsed);
+    }
+
+    fn observe_not_interesting(
+        &mut self,
+        candidate: &test_case::Candidate,
+        elapsed: time::Duration,
+    ) {
+        self.pull(candidate.provenance(), 0, elapsed);
+    }
+
+    fn observe_exhausted(&mut self, _: &str) {}
+
+    fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
+        let arm = match self.arms.get(candidate.provenance()) {
+            Some(arm) if arm.pulls > 0 => arm,
+            _ => return Score::new(UNTRIED_SCORE),
+        };
+
+        let best = self.arms
+            .values()
+            .map(Arm::reward_rate)
+            .fold(0.0, f64::max);
+        let reward = if best > 0.0 {
+            arm.reward_rate() / best
+        } else {
+            0.0
+        };
+        let bonus = (2.0 * (self.pulls as f64).ln() / arm.pulls as f64).sqrt();
+        Score::new(reward + bonus)
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::to_value(&self.arms).expect("observations should always serialize")
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        self.arms = serde_json::from_value(snapshot)?;
+        self.pulls = self.arms.values().map(|arm| arm.pulls).sum();
+        Ok(())
+    }
+}
+
+/// How many judgements a reducer's overall interesting rate counts for, when
+/// `CostAware` estimates its interesting rate for candidates with a particular
+/// delta size.
+const DELTA_PRIOR_WEIGHT: f64 = 1.0;
+
+/// The judgement time that `CostAware` assumes before it has timed any.
+const DEFAULT_JUDGEMENT_SECS: f64 = 1.0;
+
+/// The total wall time of some number of judgements.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+struct Timing {
+    count: u32,
+    seconds: f64,
+}
+
+impl Timing {
+    fn add(&mut self, elapsed: time::Duration) {
+        self.count += 1;
+        self.seconds += as_secs(elapsed);
+    }
+
+    fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.seconds / self.count as f64)
+        }
+    }
+}
+
+/// Group delta sizes by their order of magnitude.
+fn delta_class(delta: u64) -> usize {
+    (64 - delta.leading_zeros()) as usize
+}
+
+/// A `CostAware` oracle's observations of one reducer.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Costs {
+    interesting: Timing,
+    not_interesting: Timing,
+
+    /// The number of interesting candidates, and of all candidates, judged in
+    /// each `delta_class`.
+    by_delta: Vec<(u32, u32)>,
+}
+
+impl Costs {
+    fn observe(&mut self, delta: u64, interesting: bool, elapsed: time::Duration) {
+        if interesting {
+            self.interesting.add(elapsed);
+        } else {
+            self.not_interesting.add(elapsed);
+        }
+
+        let class = delta_class(delta);
+        if self.by_delta.len() <= class {
+            self.by_delta.resize(class + 1, (0, 0));
+        }
+        let counts = &mut self.by_delta[class];
+        if interesting {
+            counts.0 += 1;
+        }
+        counts.1 += 1;
+    }
+
+    /// Estimate the chance that a candidate with the given delta is
+    /// interesting: the interesting rate of candidates with similar deltas,
+    /// smoothed towards the overall interesting rate.
+    fn interesting_rate(&self, delta: u64) -> f64 {
+        let total = self.interesting.count + self.not_interesting.count;
+        let overall = (self.interesting.count as f64 + 1.0) / (total as f64 + 2.0);
+        match self.by_delta.get(delta_class(delta)) {
+            Some(&(interesting, total)) => {
+                (interesting as f64 + overall * DELTA_PRIOR_WEIGHT)
+                    / (total as f64 + DELTA_PRIOR_WEIGHT)
+            }
+            None => overall,
+        }
+    }
+}
+
+/// An `Oracle` that scores candidates by how many bytes we expect them to
+/// save per second of predicate time.
+///
+/// Candidates from different reducers can take very different amounts of time
+/// to judge: an invalid C file is rejected in milliseconds, while a valid one
+/// runs through the whole optimizer. For each reducer (by provenance), this
+/// learns how likely its candidates are to be interesting given their delta
+/// size, and how long its interesting and uninteresting candidates take to
+/// judge. A candidate's score is its delta times its chance of being
+/// interesting, divided by the time we expect it to take to judge.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct CostAware {
+    reducers: HashMap<String, Costs>,
+
+    /// Every judgement's timing, for reducers we haven't timed yet.
+    all: Timing,
+}
+
+impl CostAware {
+    fn observe(
+        &mut self,
+        provenance: &str,
+        delta: u64,
+        interesting: bool,
+        elapsed: time::Duration,
+    ) {
+        self.all.add(elapsed);
+        self.reducers
+            .entry(provenance.into())
+            .or_insert_with(Default::default)
+            .observe(delta, interesting, elapsed);
+    }
+}
+
+impl traits::Oracle for CostAware {
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        self.observe(interesting.provenance(), interesting.delta(), true, elapsed);
+    }
+
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        self.observe(interesting.provenance(), interesting.delta(), true, elapsed);
+    }
+
+    fn observe_not_interesting(
+        &mut self,
+        candidate: &test_case::Candidate,
+        elapsed: time::Duration,
+    ) {
+        self.observe(candidate.provenance(), candidate.delta(), false, elapsed);
+    }
+
+    fn observe_exhausted(&mut self, _: &str) {}
+
+    fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
+        let unobserved = Costs::default();
+        let costs = self.reducers
+            .get(candidate.provenance())
+            .unwrap_or(&unobserved);
+
+        let p = costs.interesting_rate(candidate.delta());
+        let fallback = self.all.mean().unwrap_or(DEFAULT_JUDGEMENT_SECS);
+        let interesting_secs = costs
+            .interesting
+            .mean()
+            .or(costs.not_interesting.mean())
+            .unwrap_or(fallback);
+        let not_interesting_secs = costs
+            .not_interesting
+            .mean()
+            .or(costs.interesting.mean())
+            .unwrap_or(fallback);
+        let secs = p * interesting_secs + (1.0 - p) * not_interesting_secs;
+
+        Score::new(p * candidate.delta() as f64 / secs.max(MIN_JUDGEMENT_SECS))
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("observations should always serialize")
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        *self = serde_json::from_value(snapshot)?;
+        Ok(())
+    }
+}
+
+/// An `Oracle` that scales another oracle's scores by a constant weight, to
+/// tune how much it counts for when joined with other oracles.
+#[derive(Debug)]
+pub struct Weighted<O> {
+    oracle: O,
+    weight: f64,
+}
+
+impl<O> Weighted<O> {
+    /// Scale the given oracle's scores by `weight`.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if `weight` is negative, NaN, or infinite.
+    pub fn new(oracle: O, weight: f64) -> Weighted<O> {
+        assert!(weight >= 0.0 && weight.is_finite());
+        Weighted {
+            oracle: oracle,
+            weight: weight,
+        }
+    }
+}
+
+impl<O> traits::Oracle for Weighted<O>
+where
+    O: traits::Oracle,
+{
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        self.oracle.observe_smallest_interesting(interesting, elapsed);
+    }
+
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    ) {
+        self.oracle.observe_not_smallest_interesting(interesting, elapsed);
+    }
+
+    fn observe_not_interesting(
+        &mut self,
+        candidate: &test_case::Candidate,
+        elapsed: time::Duration,
+    ) {
+        self.oracle.observe_not_interesting(candidate, elapsed);
+    }
+
+    fn observe_timed_out(&mut self, candidate: &test_case::Candidate, elapsed: time::Duration) {
+        self.oracle.observe_timed_out(candidate, elapsed);
+    }
+
+    fn observe_exhausted(&mut self, reducer_name: &str) {
+        self.oracle.observe_exhausted(reducer_name);
+    }
+
+    fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
+        self.oracle.predict(candidate) * self.weight
+    }
+
+    fn snapshot(&self) -> serde_json::Value {
+        self.oracle.snapshot()
+    }
+
+    fn restore(
+        &mut self,
+        snapshot: serde_json::Value,
+        smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        self.oracle.restore(snapshot, smallest)
+    }
 }
 
 macro_rules! define_join_combinator {
@@ -376,16 +968,36 @@ macro_rules! define_join_combinator {
         impl < $( $generic , )+ > traits::Oracle for $name < $( $generic , )+ >
             where $( $generic : traits::Oracle , )+
         {
-            fn observe_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
-                $( self.$inner.observe_smallest_interesting(interesting); )+
+            fn observe_smallest_interesting(
+                &mut self,
+                interesting: &test_case::Interesting,
+                elapsed: time::Duration,
+            ) {
+                $( self.$inner.observe_smallest_interesting(interesting, elapsed); )+
             }
 
-            fn observe_not_smallest_interesting(&mut self, interesting: &test_case::Interesting) {
-                $( self.$inner.observe_not_smallest_interesting(interesting); )+
+            fn observe_not_smallest_interesting(
+                &mut self,
+                interesting: &test_case::Interesting,
+                elapsed: time::Duration,
+            ) {
+                $( self.$inner.observe_not_smallest_interesting(interesting, elapsed); )+
             }
 
-            fn observe_not_interesting(&mut self, candidate: &test_case::Candidate) {
-                $( self.$inner.observe_not_interesting(candidate); )+
+            fn observe_not_interesting(
+                &mut self,
+                candidate: &test_case::Candidate,
+                elapsed: time::Duration,
+            ) {
+                $( self.$inner.observe_not_interesting(candidate, elapsed); )+
+            }
+
+            fn observe_timed_out(
+                &mut self,
+                candidate: &test_case::Candidate,
+                elapsed: time::Duration,
+            ) {
+                $( self.$inner.observe_timed_out(candidate, elapsed); )+
             }
 
             fn observe_exhausted(&mut self, reducer: &str) {
@@ -395,6 +1007,34 @@ macro_rules! define_join_combinator {
             fn predict(&mut self, candidate: &test_case::Candidate) -> Score {
                 Score::new(0.0) $( + self.$inner.predict(candidate) )+
             }
+
+            fn snapshot(&self) -> serde_json::Value {
+                serde_json::Value::Array(vec![ $( self.$inner.snapshot() , )+ ])
+            }
+
+            fn restore(
+                &mut self,
+                snapshot: serde_json::Value,
+                smallest: &test_case::Interesting,
+            ) -> error::Result<()> {
+                let mut snapshots = match snapshot {
+                    serde_json::Value::Array(snapshots) => snapshots.into_iter(),
+                    _ => {
+                        return Err(error::Error::InvalidCheckpoint(
+                            "expected an array of joined oracle snapshots".into(),
+                        ))
+                    }
+                };
+                $(
+                    let inner_snapshot = snapshots.next().ok_or_else(|| {
+                        error::Error::InvalidCheckpoint(
+                            "missing a joined oracle's snapshot".into(),
+                        )
+                    })?;
+                    self.$inner.restore(inner_snapshot, smallest)?;
+                )+
+                Ok(())
+            }
         }
     }
 }
@@ -430,25 +1070,327 @@ define_join_combinator! {
     }
 }
 
+/// A preset oracle for prioritizing candidates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Sum `InterestingRate`, `CreducePassPriorities`, `PercentReduced`,
+    /// `HaveWeSeenIt`, and `HaveWeTriedIt`. This is the default.
+    Default,
+
+    /// Pick between reducers with a `Bandit`, and avoid test cases and
+    /// reductions we have already tried with `HaveWeSeenIt` and
+    /// `HaveWeTriedIt`.
+    Bandit,
+
+    /// Follow C-Reduce's pass ordering with `CreducePassPriorities`, and avoid
+    /// test cases and reductions we have already tried.
+    Creduce,
+
+    /// Greedily test the candidates that would reduce the test case the most
+    /// first with `PercentReduced`, and avoid test cases and reductions we
+    /// have already tried.
+    BiggestFirst,
+
+    /// Test the candidates expected to save the most bytes per second of
+    /// predicate time first with `CostAware`, and avoid test cases and
+    /// reductions we have already tried.
+    CostAware,
+}
+
+impl Strategy {
+    /// The names of every strategy, as parsed by `from_str`.
+    pub const NAMES: &'static [&'static str] =
+        &["default", "bandit", "creduce", "biggest-first", "cost-aware"];
+}
+
+impl str::FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Strategy, String> {
+        match s {
+            "default" => Ok(Strategy::Default),
+            "bandit" => Ok(Strategy::Bandit),
+            "creduce" => Ok(Strategy::Creduce),
+            "biggest-first" => Ok(Strategy::BiggestFirst),
+            "cost-aware" => Ok(Strategy::CostAware),
+            _ => Err(format!(
+                "'{}' is not one of the oracle strategies: {}",
+                s,
+                Strategy::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl Default for Strategy {
+    fn default() -> Strategy {
+        Strategy::Default
+    }
+}
+
+impl Strategy {
+    /// Construct a fresh oracle implementing this strategy.
+    pub fn oracle(&self) -> Box<traits::Oracle> {
+        match *self {
+            Strategy::Default => Box::new(Join5::new(
+                InterestingRate::default(),
+                CreducePassPriorities::default(),
+                PercentReduced::default(),
+                HaveWeSeenIt::default(),
+                HaveWeTriedIt::default(),
+            )),
+            Strategy::Bandit => Box::new(Join3::new(
+                Bandit::default(),
+                HaveWeSeenIt::default(),
+                HaveWeTriedIt::default(),
+            )),
+            Strategy::Creduce => Box::new(Join3::new(
+                CreducePassPriorities::default(),
+                HaveWeSeenIt::default(),
+                HaveWeTriedIt::default(),
+            )),
+            Strategy::BiggestFirst => Box::new(Join3::new(
+                PercentReduced::default(),
+                HaveWeSeenIt::default(),
+                HaveWeTriedIt::default(),
+            )),
+            Strategy::CostAware => Box::new(Join3::new(
+                CostAware::default(),
+                HaveWeSeenIt::default(),
+                HaveWeTriedIt::default(),
+            )),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use score::Score;
+    use serde_json;
     use test_case;
     use traits::Oracle;
 
     struct Constant(Score);
 
     impl traits::Oracle for Constant {
-        fn observe_smallest_interesting(&mut self, _: &test_case::Interesting) {}
-        fn observe_not_smallest_interesting(&mut self, _: &test_case::Interesting) {}
-        fn observe_not_interesting(&mut self, _: &test_case::Candidate) {}
+        fn observe_smallest_interesting(&mut self, _: &test_case::Interesting, _: time::Duration) {
+        }
+        fn observe_not_smallest_interesting(
+            &mut self,
+            _: &test_case::Interesting,
+            _: time::Duration,
+        ) {
+        }
+        fn observe_not_interesting(&mut self, _: &test_case::Candidate, _: time::Duration) {}
         fn observe_exhausted(&mut self, _: &str) {}
         fn predict(&mut self, _: &test_case::Candidate) -> Score {
             self.0
         }
     }
 
+    #[test]
+    fn snapshot_and_restore() {
+        let candidate = test_case::Candidate::testing_only_new();
+        let smallest = test_case::Interesting::testing_only_new();
+
+        let mut oracle = Join2::new(InterestingRate::default(), HaveWeSeenIt::default());
+        oracle.observe_not_interesting(&candidate, time::Duration::from_secs(1));
+        let before = oracle.predict(&candidate);
+
+        let snapshot = oracle.snapshot();
+        let mut restored = Join2::new(InterestingRate::default(), HaveWeSeenIt::default());
+        assert!(restored.predict(&candidate) != before);
+
+        restored.restore(snapshot, &smallest).unwrap();
+        assert_eq!(restored.predict(&candidate), before);
+    }
+
+    #[test]
+    fn restore_malformed_snapshot() {
+        let smallest = test_case::Interesting::testing_only_new();
+        let mut oracle = Join2::new(InterestingRate::default(), HaveWeSeenIt::default());
+        assert!(oracle.restore(serde_json::Value::Null, &smallest).is_err());
+    }
+
+    #[test]
+    fn weighted() {
+        let candidate = test_case::Candidate::testing_only_new();
+
+        let mut weighted = Weighted::new(Constant(Score::new(2.0)), 1.5);
+        assert_eq!(weighted.predict(&candidate), Score::new(3.0));
+
+        let mut weighted = Weighted::new(Constant(Score::skip()), 1.5);
+        assert_eq!(weighted.predict(&candidate), Score::skip());
+
+        let mut joined = Join2::new(
+            Weighted::new(Box::new(Constant(Score::new(1.0))) as Box<traits::Oracle>, 0.5),
+            Weighted::new(Constant(Score::new(1.0)), 2.0),
+        );
+        assert_eq!(joined.predict(&candidate), Score::new(2.5));
+    }
+
+    #[test]
+    fn parse_strategy() {
+        for name in Strategy::NAMES {
+            assert!(name.parse::<Strategy>().is_ok(), "{}", name);
+        }
+        assert_eq!("bandit".parse(), Ok(Strategy::Bandit));
+        assert!("best".parse::<Strategy>().is_err());
+    }
+
+    #[test]
+    fn bandit_tries_every_reducer() {
+        let mut bandit = Bandit::default();
+        let tried = test_case::Candidate::testing_only_with("tried", 10, 1);
+        bandit.observe_not_interesting(&tried, time::Duration::from_secs(1));
+
+        let untried = test_case::Candidate::testing_only_with("untried", 10, 1);
+        assert!(bandit.predict(&untried) > bandit.predict(&tried));
+    }
+
+    #[test]
+    fn bandit_converges_to_productive_reducer() {
+        // Each reducer's chance out of 10 of producing an interesting
+        // candidate, how many bytes those remove, and how many seconds its
+        // candidates take to judge.
+        let reducers = [
+            ("productive", 5, 100, 1),
+            ("slow", 5, 100, 10),
+            ("unproductive", 1, 100, 1),
+            ("useless", 0, 0, 1),
+        ];
+
+        let mut bandit = Bandit::default();
+        let mut pulls = HashMap::new();
+        let mut state: u32 = 1;
+        for _ in 0..1000 {
+            // Test whichever candidate the bandit scores highest.
+            let (name, chance, delta, secs) = *reducers
+                .iter()
+                .max_by_key(|&&(name, _, delta, _)| {
+                    let candidate = test_case::Candidate::testing_only_with(name, 1000, delta);
+                    bandit.predict(&candidate)
+                })
+                .unwrap();
+            *pulls.entry(name).or_insert(0) += 1;
+
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let candidate = test_case::Candidate::testing_only_with(name, 1000, delta);
+            let elapsed = time::Duration::from_secs(secs);
+            if (state >> 16) % 10 < chance {
+                let interesting = test_case::Interesting::testing_only_from(candidate);
+                bandit.observe_smallest_interesting(&interesting, elapsed);
+            } else {
+                bandit.observe_not_interesting(&candidate, elapsed);
+            }
+        }
+
+        for &(name, _, _, _) in &reducers {
+            assert!(pulls[name] > 0, "should try {}", name);
+        }
+        assert!(
+            pulls["productive"] > 800,
+            "should mostly test the productive reducer's candidates: {:?}",
+            pulls
+        );
+    }
+
+    #[test]
+    fn bandit_snapshot_and_restore() {
+        let smallest = test_case::Interesting::testing_only_new();
+        let candidate = test_case::Candidate::testing_only_with("reducer", 10, 5);
+
+        let mut bandit = Bandit::default();
+        let second = time::Duration::from_secs(1);
+        bandit.observe_not_interesting(&candidate, second);
+        let other = test_case::Candidate::testing_only_with("other", 10, 5);
+        let interesting = test_case::Interesting::testing_only_from(other);
+        bandit.observe_smallest_interesting(&interesting, second);
+        let before = bandit.predict(&candidate);
+
+        let mut restored = Bandit::default();
+        restored.restore(bandit.snapshot(), &smallest).unwrap();
+        assert_eq!(restored.predict(&candidate), before);
+    }
+
+    #[test]
+    fn cost_aware_prefers_cheaper_reducers() {
+        let mut oracle = CostAware::default();
+        for &(name, secs) in &[("fast", 1), ("slow", 10)] {
+            let elapsed = time::Duration::from_secs(secs);
+            let candidate = test_case::Candidate::testing_only_with(name, 100, 10);
+            oracle.observe_not_interesting(&candidate, elapsed);
+            let interesting = test_case::Interesting::testing_only_from(candidate);
+            oracle.observe_smallest_interesting(&interesting, elapsed);
+        }
+
+        let fast = test_case::Candidate::testing_only_with("fast", 100, 10);
+        let slow = test_case::Candidate::testing_only_with("slow", 100, 10);
+        assert!(oracle.predict(&fast) > oracle.predict(&slow));
+    }
+
+    #[test]
+    fn cost_aware_charges_timeouts() {
+        let mut oracle: Box<traits::Oracle> = Box::new(Weighted::new(CostAware::default(), 1.0));
+        for &(name, secs) in &[("hangs", 10), ("quick", 1)] {
+            let elapsed = time::Duration::from_secs(secs);
+            let candidate = test_case::Candidate::testing_only_with(name, 100, 10);
+            oracle.observe_not_interesting(&candidate, time::Duration::from_secs(1));
+            oracle.observe_timed_out(&candidate, elapsed);
+        }
+
+        let hangs = test_case::Candidate::testing_only_with("hangs", 100, 10);
+        let quick = test_case::Candidate::testing_only_with("quick", 100, 10);
+        assert!(oracle.predict(&quick) > oracle.predict(&hangs));
+    }
+
+    #[test]
+    fn cost_aware_prefers_bigger_deltas() {
+        let mut oracle = CostAware::default();
+        let small = test_case::Candidate::testing_only_with("reducer", 100, 10);
+        let big = test_case::Candidate::testing_only_with("reducer", 100, 50);
+        assert!(oracle.predict(&big) > oracle.predict(&small));
+    }
+
+    #[test]
+    fn cost_aware_learns_interesting_rate_by_delta() {
+        // Small reductions are usually interesting, and big ones never are.
+        let mut oracle = CostAware::default();
+        let second = time::Duration::from_secs(1);
+        for i in 0..100 {
+            let small = test_case::Candidate::testing_only_with("reducer", 1000, 10);
+            if i % 10 == 0 {
+                oracle.observe_not_interesting(&small, second);
+            } else {
+                let interesting = test_case::Interesting::testing_only_from(small);
+                oracle.observe_smallest_interesting(&interesting, second);
+            }
+
+            let big = test_case::Candidate::testing_only_with("reducer", 1000, 1000);
+            oracle.observe_not_interesting(&big, second);
+        }
+
+        let small = test_case::Candidate::testing_only_with("reducer", 1000, 10);
+        let big = test_case::Candidate::testing_only_with("reducer", 1000, 1000);
+        assert!(oracle.predict(&small) > oracle.predict(&big));
+    }
+
+    #[test]
+    fn cost_aware_snapshot_and_restore() {
+        let smallest = test_case::Interesting::testing_only_new();
+        let candidate = test_case::Candidate::testing_only_with("reducer", 100, 10);
+
+        let mut oracle = CostAware::default();
+        oracle.observe_not_interesting(&candidate, time::Duration::from_secs(3));
+        let before = oracle.predict(&candidate);
+
+        let mut restored = CostAware::default();
+        assert!(restored.predict(&candidate) != before);
+        restored.restore(oracle.snapshot(), &smallest).unwrap();
+        assert_eq!(restored.predict(&candidate), before);
+    }
+
     #[test]
     fn test_joining_oracles() {
         let mut joined = Join3::new(
@@ -0,0 +1,260 @@
+//! Sanity checks of the is-interesting predicate, run before a reduction
+//! starts.
+//!
+//! A predicate that finds everything interesting, for example one that greps
+//! the wrong file, lets the reduction delete the whole test case while making
+//! steady "progress". So before reducing, we check that:
+//!
+//! * the original test case is interesting,
+//!
+//! * an empty test case is not interesting, and
+//!
+//! * a test case full of garbage is not interesting.
+//!
+//! When a check fails, the output of the predicate run that failed it is kept,
+//! so that it can be shown instead of a bare error.
+
+use error;
+use interesting;
+use std::ffi;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path;
+use std::time;
+use tempdir;
+use test_case;
+use traits::IsInteresting;
+
+/// The number of bytes in the garbage test case.
+const GARBAGE_SIZE: usize = 4096;
+
+/// One of the pre-flight checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Check {
+    /// The original test case must be interesting.
+    OriginalIsInteresting,
+
+    /// An empty test case must not be interesting.
+    EmptyIsNotInteresting,
+
+    /// A test case full of garbage must not be interesting.
+    GarbageIsNotInteresting,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Check::OriginalIsInteresting => "the original test case is not interesting",
+            Check::EmptyIsNotInteresting => {
+                "an empty test case is interesting, so the predicate is probably trivially true"
+            }
+            Check::GarbageIsNotInteresting => {
+                "a test case full of garbage is interesting, so the predicate is probably \
+                 trivially true"
+            }
+        })
+    }
+}
+
+/// A failed pre-flight check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// The check that failed.
+    pub check: Check,
+
+    /// The output of the predicate run that failed the check, if the predicate
+    /// captures its output.
+    pub output: Option<interesting::Output>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.check)?;
+        if let Some(ref output) = self.output {
+            write!(f, "\n{}", output)?;
+        }
+        Ok(())
+    }
+}
+
+/// The results of a successful set of pre-flight checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// How long the predicate took to run on the original test case: an
+    /// estimate of how long each candidate will take to judge.
+    pub runtime: time::Duration,
+}
+
+/// Run the pre-flight checks of `predicate` against the `original` test case,
+/// returning `Err(Error::PreflightFailed(..))` if any of them fail.
+///
+/// ```
+/// # fn _ignore() -> preduce::error::Result<()> {
+/// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+/// let report = preduce::preflight::check(&predicate, "path/to/test-case".as_ref())?;
+/// println!("The predicate takes about {:?} per candidate", report.runtime);
+/// # Ok(())
+/// # }
+/// ```
+pub fn check<I>(predicate: &I, original: &path::Path) -> error::Result<Report>
+where
+    I: ?Sized + IsInteresting,
+{
+    let dir = tempdir::TempDir::new("preduce-preflight")?;
+    let file_name = original.file_name().ok_or_else(|| {
+        error::Error::DoesNotExist(original.into())
+    })?;
+    let is_dir = original.is_dir();
+
+    let candidate = make_candidate(&dir, "original", file_name)?;
+    test_case::copy_test_case(original, &candidate)?;
+    let start = time::Instant::now();
+    let judgement = predicate.judge(&candidate, true)?;
+    let runtime = start.elapsed();
+    if !judgement.interesting {
+        return Err(failed(Check::OriginalIsInteresting, judgement.output));
+    }
+
+    let candidate = make_candidate(&dir, "empty", file_name)?;
+    if is_dir {
+        fs::create_dir(&candidate)?;
+    } else {
+        fs::File::create(&candidate)?;
+    }
+    let judgement = predicate.judge(&candidate, true)?;
+    if judgement.interesting {
+        return Err(failed(Check::EmptyIsNotInteresting, judgement.output));
+    }
+
+    let candidate = make_candidate(&dir, "garbage", file_name)?;
+    if is_dir {
+        fs::create_dir(&candidate)?;
+        write_garbage(&candidate.join("garbage"))?;
+    } else {
+        write_garbage(&candidate)?;
+    }
+    let judgement = predicate.judge(&candidate, true)?;
+    if judgement.interesting {
+        return Err(failed(Check::GarbageIsNotInteresting, judgement.output));
+    }
+
+    Ok(Report { runtime: runtime })
+}
+
+/// Make a fresh directory for one of the checks' candidates, and get the path
+/// of the candidate within it, under the original test case's file name.
+fn make_candidate(
+    dir: &tempdir::TempDir,
+    check: &str,
+    file_name: &ffi::OsStr,
+) -> error::Result<path::PathBuf> {
+    let check_dir = dir.path().join(check);
+    fs::create_dir(&check_dir)?;
+    Ok(check_dir.join(file_name))
+}
+
+fn failed(check: Check, output: Option<interesting::Output>) -> error::Error {
+    error::Error::PreflightFailed(Failure {
+        check: check,
+        output: output,
+    })
+}
+
+/// Write a file of printable pseudo-random garbage. It is always the same
+/// garbage, so that the checks are repeatable.
+fn write_garbage(path: &path::Path) -> error::Result<()> {
+    let mut state: u32 = 0x2545_f491;
+    let mut garbage = Vec::with_capacity(GARBAGE_SIZE);
+    for i in 0..GARBAGE_SIZE {
+        if i % 64 == 63 {
+            garbage.push(b'\n');
+            continue;
+        }
+        state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        garbage.push(b' ' + ((state >> 16) % 95) as u8);
+    }
+    fs::File::create(path)?.write_all(&garbage)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error;
+    use interesting;
+    use std::io::Read;
+
+    fn original() -> (tempdir::TempDir, path::PathBuf) {
+        let dir = tempdir::TempDir::new("preflight-test").unwrap();
+        let path = dir.path().join("test-case.txt");
+        fs::File::create(&path)
+            .unwrap()
+            .write_all(b"lorem ipsum\n")
+            .unwrap();
+        (dir, path)
+    }
+
+    fn contains_lorem(path: &path::Path) -> error::Result<bool> {
+        let mut contents = String::new();
+        fs::File::open(path)?.read_to_string(&mut contents)?;
+        Ok(contents.contains("lorem"))
+    }
+
+    #[test]
+    fn passes() {
+        let (_dir, path) = original();
+        let judge = |p: &path::Path| contains_lorem(p);
+        check(&judge, &path).expect("pre-flight checks should pass");
+    }
+
+    #[test]
+    fn original_not_interesting() {
+        let (_dir, path) = original();
+        let judge = |_: &path::Path| Ok(false);
+        match check(&judge, &path) {
+            Err(error::Error::PreflightFailed(ref failure)) => {
+                assert_eq!(failure.check, Check::OriginalIsInteresting);
+            }
+            otherwise => panic!("expected a failed check, found {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn empty_is_interesting() {
+        let (_dir, path) = original();
+        let judge = |_: &path::Path| Ok(true);
+        match check(&judge, &path) {
+            Err(error::Error::PreflightFailed(ref failure)) => {
+                assert_eq!(failure.check, Check::EmptyIsNotInteresting);
+            }
+            otherwise => panic!("expected a failed check, found {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn garbage_is_interesting() {
+        let (_dir, path) = original();
+        match check(&interesting::NonEmpty, &path) {
+            Err(error::Error::PreflightFailed(ref failure)) => {
+                assert_eq!(failure.check, Check::GarbageIsNotInteresting);
+            }
+            otherwise => panic!("expected a failed check, found {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn failure_shows_output() {
+        let (_dir, path) = original();
+        let judge = interesting::Command::new(vec!["sh", "-c", "echo oops >&2; exit 1"]);
+        match check(&judge, &path) {
+            Err(error::Error::PreflightFailed(ref failure)) => {
+                assert_eq!(failure.check, Check::OriginalIsInteresting);
+                let output = failure.output.as_ref().expect("should capture output");
+                assert_eq!(output.stderr, "oops\n");
+                assert!(failure.to_string().contains("oops"));
+            }
+            otherwise => panic!("expected a failed check, found {:?}", otherwise),
+        }
+    }
+}
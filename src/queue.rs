@@ -1,38 +1,112 @@
 //! The queue for candidates that haven't been tested yet.
+//!
+//! Candidates are mostly dispatched in the order the oracle scored them, but a
+//! plain priority queue lets a reducer whose candidates score slightly higher
+//! monopolize every worker, while the other reducers' candidates wait forever.
+//! So the queue also:
+//!
+//! * limits how many workers one reducer's candidates may occupy at once, while
+//!   other reducers' candidates are waiting,
+//!
+//! * ages candidates that keep getting passed over, until they are dispatched
+//!   ahead of everything queued after them, and
+//!
+//! * reserves a share of dispatches for the reducer that has had the fewest of
+//!   its candidates dispatched so far.
+//!
+//! Reducers are told apart by their candidates' provenance.
 
 use actors::ReducerId;
 use score;
 use std::cmp;
-use std::collections::BinaryHeap;
-use std::mem;
+use std::collections::HashMap;
 use std::ops;
-use test_case;
+use std::time;
+use test_case::{self, TestCaseMethods};
 
-#[derive(PartialEq, Eq)]
-struct QueuedCandidate(test_case::Candidate, ReducerId, score::Score);
+/// How the queue shares workers between reducers. See `Options::reducer_quota`,
+/// `Options::priority_aging`, and `Options::exploration_share`. By default,
+/// there is no sharing, and candidates are dispatched in the oracle's order.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fairness {
+    /// The most workers one reducer's candidates may occupy at once, while
+    /// other reducers' candidates are waiting, or `None` for no limit.
+    pub quota: Option<usize>,
 
-impl PartialOrd for QueuedCandidate {
-    fn partial_cmp(&self, rhs: &QueuedCandidate) -> Option<cmp::Ordering> {
-        self.2.partial_cmp(&rhs.2)
-    }
+    /// A queued candidate moves ahead of every candidate that has been passed
+    /// over fewer times each time it is passed over this many times, or never
+    /// if this is zero.
+    pub aging: usize,
+
+    /// The minimum fraction of dispatches given to the reducer with the fewest
+    /// candidates dispatched so far.
+    pub exploration: f64,
 }
 
-impl Ord for QueuedCandidate {
-    fn cmp(&self, rhs: &QueuedCandidate) -> cmp::Ordering {
-        self.2.cmp(&rhs.2)
-    }
+/// A single reducer's use of the queue and the workers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Occupancy {
+    /// How many of this reducer's candidates are queued right now.
+    pub queued: usize,
+
+    /// The most of this reducer's candidates that were ever queued at once.
+    pub peak_queued: usize,
+
+    /// How many of this reducer's candidates are being judged right now.
+    pub in_flight: usize,
+
+    /// How many of this reducer's candidates have been dispatched to workers.
+    pub dispatched: u64,
+
+    /// The total time this reducer's candidates spent waiting in the queue.
+    pub queued_time: time::Duration,
+}
+
+struct QueuedCandidate {
+    candidate: test_case::Candidate,
+    by: Option<ReducerId>,
+    priority: score::Score,
+
+    /// How many times another candidate was dispatched instead of this one.
+    passed_over: usize,
+
+    /// When this candidate was queued.
+    queued_at: time::Instant,
+
+    /// The order this candidate was queued in, to break ties in favor of older
+    /// candidates.
+    sequence: u64,
 }
 
 /// The queue for candidates that haven't been tested for interesting-ness yet.
 pub struct CandidateQueue {
-    candidates: BinaryHeap<QueuedCandidate>,
+    candidates: Vec<QueuedCandidate>,
+    fairness: Fairness,
+    occupancy: HashMap<String, Occupancy>,
+
+    /// The number of candidates dispatched so far.
+    dispatched: u64,
+
+    /// The number of those dispatches that were exploration.
+    explored: u64,
+
+    /// The sequence number for the next queued candidate.
+    next_sequence: u64,
 }
 
 impl CandidateQueue {
-    /// Construct a new queue with capacity for `n` candidates.
-    pub fn with_capacity(n: usize) -> CandidateQueue {
+    /// Construct a new queue with capacity for `n` candidates, that shares
+    /// workers between reducers according to `fairness`.
+    pub fn with_capacity(n: usize, fairness: Fairness) -> CandidateQueue {
+        assert!(fairness.quota != Some(0));
+        assert!(fairness.exploration >= 0.0 && fairness.exploration <= 1.0);
         CandidateQueue {
-            candidates: BinaryHeap::with_capacity(n),
+            candidates: Vec::with_capacity(n),
+            fairness: fairness,
+            occupancy: HashMap::with_capacity(n),
+            dispatched: 0,
+            explored: 0,
+            next_sequence: 0,
         }
     }
 
@@ -48,35 +122,50 @@ impl CandidateQueue {
 
     /// Clear the candidate queue, leaving it empty.
     pub fn clear(&mut self) {
-        self.candidates.clear();
+        self.retain(|_, _| false);
     }
 
     /// Insert a new candidate into the queue, that was produced by the reducer
-    /// actor with the given id.
+    /// actor with the given id, or by the supervisor itself (e.g. by merging
+    /// interesting test cases) if `by` is `None`.
     pub fn insert(
         &mut self,
         candidate: test_case::Candidate,
-        by: ReducerId,
+        by: Option<ReducerId>,
         priority: score::Score,
     ) {
-        self.candidates
-            .push(QueuedCandidate(candidate, by, priority));
+        {
+            let occupancy = self.occupancy_of(candidate.provenance());
+            occupancy.queued += 1;
+            occupancy.peak_queued = cmp::max(occupancy.peak_queued, occupancy.queued);
+        }
+        self.candidates.push(QueuedCandidate {
+            candidate: candidate,
+            by: by,
+            priority: priority,
+            passed_over: 0,
+            queued_at: time::Instant::now(),
+            sequence: self.next_sequence,
+        });
+        self.next_sequence += 1;
     }
 
     /// Retain only the queued candidates for which the predicate returns `true`
     /// and remove all other queued candidates.
     pub fn retain<F>(&mut self, predicate: F)
     where
-        F: FnMut(&test_case::Candidate, ReducerId) -> bool,
+        F: FnMut(&test_case::Candidate, Option<ReducerId>) -> bool,
     {
         let mut predicate = predicate;
-        let retained: BinaryHeap<_> = self.candidates
-            .drain()
-            .filter(|&QueuedCandidate(ref candidate, by, _)| {
-                predicate(candidate, by)
-            })
-            .collect();
-        mem::replace(&mut self.candidates, retained);
+        let mut i = 0;
+        while i < self.candidates.len() {
+            if predicate(&self.candidates[i].candidate, self.candidates[i].by) {
+                i += 1;
+            } else {
+                let removed = self.candidates.swap_remove(i);
+                self.dequeued(&removed);
+            }
+        }
     }
 
     /// Drain the next `..n` candidates from the front of the queue.
@@ -86,6 +175,105 @@ impl CandidateQueue {
             n: range.end,
         }
     }
+
+    /// Note that a worker finished judging a dispatched candidate with the
+    /// given provenance, freeing up its reducer's quota.
+    pub fn judged(&mut self, provenance: &str) {
+        let occupancy = self.occupancy_of(provenance);
+        occupancy.in_flight = occupancy.in_flight.saturating_sub(1);
+    }
+
+    /// Get each reducer's use of the queue so far, keyed by provenance.
+    pub fn occupancy(&self) -> &HashMap<String, Occupancy> {
+        &self.occupancy
+    }
+
+    fn occupancy_of(&mut self, provenance: &str) -> &mut Occupancy {
+        self.occupancy
+            .entry(provenance.to_string())
+            .or_insert_with(Default::default)
+    }
+
+    fn dequeued(&mut self, removed: &QueuedCandidate) {
+        let occupancy = self.occupancy_of(removed.candidate.provenance());
+        occupancy.queued -= 1;
+        occupancy.queued_time += removed.queued_at.elapsed();
+    }
+
+    /// Is this candidate's reducer already using up its quota of workers?
+    fn over_quota(&self, queued: &QueuedCandidate) -> bool {
+        match self.fairness.quota {
+            None => false,
+            Some(quota) => self.occupancy
+                .get(queued.candidate.provenance())
+                .map_or(false, |o| o.in_flight >= quota),
+        }
+    }
+
+    /// The key to order candidates by for normal, non-exploration dispatches:
+    /// how aged they are, then their priority, then how long they've been
+    /// queued.
+    fn rank(&self, queued: &QueuedCandidate) -> (usize, score::Score, cmp::Reverse<u64>) {
+        let age = if self.fairness.aging == 0 {
+            0
+        } else {
+            queued.passed_over / self.fairness.aging
+        };
+        (age, queued.priority, cmp::Reverse(queued.sequence))
+    }
+
+    /// Is the next dispatch owed to exploration?
+    fn should_explore(&self) -> bool {
+        (self.explored as f64) < self.fairness.exploration * (self.dispatched + 1) as f64
+    }
+
+    /// Remove and return the next candidate to dispatch.
+    fn pop(&mut self) -> Option<QueuedCandidate> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+
+        // Candidates of reducers that are over their quota are only dispatched
+        // when nothing else is queued, so that no worker sits idle.
+        let mut eligible: Vec<usize> = (0..self.candidates.len())
+            .filter(|&i| !self.over_quota(&self.candidates[i]))
+            .collect();
+        if eligible.is_empty() {
+            eligible = (0..self.candidates.len()).collect();
+        }
+
+        let explore = self.should_explore();
+        let index = {
+            let dispatched = |i: &usize| {
+                self.occupancy
+                    .get(self.candidates[*i].candidate.provenance())
+                    .map_or(0, |o| o.dispatched)
+            };
+            let fewest = eligible.iter().map(&dispatched).min().unwrap();
+            eligible
+                .into_iter()
+                .filter(|i| !explore || dispatched(i) == fewest)
+                .max_by_key(|&i| self.rank(&self.candidates[i]))
+                .unwrap()
+        };
+
+        let next = self.candidates.swap_remove(index);
+        for queued in &mut self.candidates {
+            queued.passed_over += 1;
+        }
+
+        self.dequeued(&next);
+        {
+            let occupancy = self.occupancy_of(next.candidate.provenance());
+            occupancy.in_flight += 1;
+            occupancy.dispatched += 1;
+        }
+        self.dispatched += 1;
+        if explore {
+            self.explored += 1;
+        }
+        Some(next)
+    }
 }
 
 /// An iterator for the draining candidates from the front of the candidates
@@ -96,17 +284,153 @@ pub struct Drain<'a> {
 }
 
 impl<'a> Iterator for Drain<'a> {
-    type Item = (test_case::Candidate, ReducerId);
+    type Item = (test_case::Candidate, Option<ReducerId>);
 
-    fn next(&mut self) -> Option<(test_case::Candidate, ReducerId)> {
+    fn next(&mut self) -> Option<(test_case::Candidate, Option<ReducerId>)> {
         if self.n == 0 {
             None
         } else {
             self.n -= 1;
-            self.queue
-                .candidates
-                .pop()
-                .map(|QueuedCandidate(candidate, by, _)| (candidate, by))
+            self.queue.pop().map(|queued| (queued.candidate, queued.by))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use score::Score;
+
+    fn candidate(provenance: &str) -> test_case::Candidate {
+        test_case::Candidate::testing_only_with(provenance, 1, 1)
+    }
+
+    /// Keep each reducer's single candidate queued, as the supervisor does by
+    /// requesting a reducer's next candidate when its last one is dispatched,
+    /// and dispatch `n` candidates, judging each right away. Return how many
+    /// times each reducer's candidate was dispatched.
+    fn simulate(fairness: Fairness, scores: &[(&str, f64)], n: usize) -> HashMap<String, usize> {
+        let mut queue = CandidateQueue::with_capacity(scores.len(), fairness);
+        for &(name, score) in scores {
+            queue.insert(candidate(name), None, Score::new(score));
+        }
+
+        let mut counts = HashMap::new();
+        for _ in 0..n {
+            let (next, _) = queue.drain(..1).next().unwrap();
+            let name = next.provenance().to_string();
+            queue.judged(&name);
+            let score = scores.iter().find(|&&(s, _)| s == name).unwrap().1;
+            queue.insert(candidate(&name), None, Score::new(score));
+            *counts.entry(name).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn priority_order() {
+        let mut queue = CandidateQueue::with_capacity(3, Fairness::default());
+        queue.insert(candidate("low"), None, Score::new(1.0));
+        queue.insert(candidate("high"), None, Score::new(3.0));
+        queue.insert(candidate("middle"), None, Score::new(2.0));
+
+        let order: Vec<_> = queue
+            .drain(..3)
+            .map(|(c, _)| c.provenance().to_string())
+            .collect();
+        assert_eq!(order, vec!["high", "middle", "low"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn without_fairness_starves() {
+        let counts = simulate(Fairness::default(), &[("best", 1.01), ("other", 1.0)], 100);
+        assert_eq!(counts.get("best"), Some(&100));
+        assert_eq!(counts.get("other"), None);
+    }
+
+    #[test]
+    fn aging_prevents_starvation() {
+        let fairness = Fairness {
+            aging: 4,
+            ..Default::default()
+        };
+        let counts = simulate(fairness, &[("best", 1.01), ("other", 1.0)], 100);
+        assert_eq!(counts["other"], 20, "dispatched once every aging + 1 dispatches");
+        assert_eq!(counts["best"], 80);
+    }
+
+    #[test]
+    fn exploration_share() {
+        let fairness = Fairness {
+            exploration: 0.25,
+            ..Default::default()
+        };
+        let counts = simulate(
+            fairness,
+            &[("best", 3.0), ("second", 2.0), ("third", 1.0)],
+            100,
+        );
+        // Every fourth dispatch explores. The first goes to the best reducer,
+        // since nothing has been dispatched yet, and the rest alternate
+        // between the two reducers that have had the fewest dispatches.
+        assert_eq!(counts["second"], 12);
+        assert_eq!(counts["third"], 12);
+        assert_eq!(counts["best"], 76);
+    }
+
+    #[test]
+    fn quota() {
+        let fairness = Fairness {
+            quota: Some(1),
+            ..Default::default()
+        };
+        let mut queue = CandidateQueue::with_capacity(4, fairness);
+        queue.insert(candidate("best"), None, Score::new(3.0));
+        queue.insert(candidate("best"), None, Score::new(2.5));
+        queue.insert(candidate("other"), None, Score::new(1.0));
+
+        // The best reducer already has one candidate being judged, so the
+        // other reducer's candidate goes next, despite its lower score.
+        let first: Vec<_> = queue
+            .drain(..2)
+            .map(|(c, _)| c.provenance().to_string())
+            .collect();
+        assert_eq!(first, vec!["best", "other"]);
+        assert_eq!(queue.occupancy()["best"].in_flight, 1);
+
+        // When only over-quota candidates are queued, they are dispatched
+        // anyway, rather than leaving a worker idle.
+        let (next, _) = queue.drain(..1).next().unwrap();
+        assert_eq!(next.provenance(), "best");
+        assert_eq!(queue.occupancy()["best"].in_flight, 2);
+
+        queue.judged("best");
+        queue.judged("best");
+        assert_eq!(queue.occupancy()["best"].in_flight, 0);
+    }
+
+    #[test]
+    fn occupancy() {
+        let mut queue = CandidateQueue::with_capacity(4, Fairness::default());
+        queue.insert(candidate("a"), None, Score::new(1.0));
+        queue.insert(candidate("a"), None, Score::new(2.0));
+        queue.insert(candidate("b"), None, Score::new(3.0));
+        assert_eq!(queue.occupancy()["a"].queued, 2);
+        assert_eq!(queue.occupancy()["a"].peak_queued, 2);
+
+        assert_eq!(queue.drain(..1).count(), 1);
+        queue.retain(|c, _| c.provenance() != "a");
+        assert!(queue.is_empty());
+
+        let a = queue.occupancy()["a"];
+        assert_eq!(a.queued, 0);
+        assert_eq!(a.peak_queued, 2);
+        assert_eq!(a.dispatched, 0);
+        let b = queue.occupancy()["b"];
+        assert_eq!(b.queued, 0);
+        assert_eq!(b.peak_queued, 1);
+        assert_eq!(b.dispatched, 1);
+        assert_eq!(b.in_flight, 1);
+    }
+}
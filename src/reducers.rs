@@ -2,15 +2,26 @@
 
 use error;
 use is_executable::IsExecutable;
+use preduce_balanced_reducer::{Angles, Curlies, Parens, RemoveBalancedReducer, Squares};
+use preduce_chunks_reducer::{Blank, Chunks};
+use preduce_clang_delta_reducer::ClangDeltaReducer;
+use preduce_clex_reducer::ClexReducer;
 use preduce_ipc_types::{FastForwardRequest, NewRequest, NextOnInterestingRequest, NextRequest,
                         ReduceRequest, Request};
 use preduce_ipc_types::{FastForwardResponse, NewResponse, NextOnInterestingResponse, NextResponse,
                         ReduceResponse, Response};
+use preduce_ranges_reducer::RemoveRangesReducer;
+use preduce_reducer_script;
+use preduce_regex_reducer::{Includes, RemoveRegexReducer};
+use preduce_topformflat_reducer::TopformflatReducer;
 use serde_json;
 use std::any::Any;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::fmt;
+use std::fs;
 use std::io::{self, BufRead, Write};
+use std::marker::PhantomData;
 use std::path;
 use std::process;
 use std::sync::Arc;
@@ -71,6 +82,18 @@ impl Reducer for Box<Reducer> {
     ) -> error::Result<Option<test_case::Candidate>> {
         (**self).reduce(seed, state)
     }
+
+    fn snapshot_state(&self, state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        (**self).snapshot_state(state)
+    }
+
+    fn restore_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        (**self).restore_state(seed, snapshot)
+    }
 }
 
 /// A test case reducer that is implemented as an external script.
@@ -429,6 +452,7 @@ impl Reducer for Script {
             seed: seed.path().into(),
             state: state.clone(),
             dest: temp_file.path().into(),
+            member: seed.member_path().map(Into::into),
         }))?;
 
         match response {
@@ -459,6 +483,487 @@ impl Reducer for Script {
             }
         }
     }
+
+    // Reducer scripts' states are already JSON values, so they can be saved and
+    // restored as-is.
+
+    fn snapshot_state(&self, state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        Some(Self::downcast(state).clone())
+    }
+
+    fn restore_state(
+        &mut self,
+        _seed: &test_case::Interesting,
+        snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        Ok(Some(Box::new(snapshot)))
+    }
+}
+
+/// A test case reducer that runs a `preduce_reducer_script::Reducer`
+/// implementation in-process.
+///
+/// This is the same reducer that running `preduce_reducer_script::run::<R>()`
+/// in a reducer script would be, but without spawning a child process, or
+/// serializing and deserializing `R`'s state for every request. The state is
+/// only serialized to snapshot it for a checkpoint.
+///
+/// See `reducers::builtin` for getting `preduce`'s built-in reducers by name.
+///
+/// ### Example
+///
+/// ```
+/// extern crate preduce;
+/// extern crate preduce_chunks_reducer;
+/// extern crate preduce_ranges_reducer;
+///
+/// use preduce_chunks_reducer::Chunks;
+/// use preduce_ranges_reducer::RemoveRangesReducer;
+///
+/// # fn main() {
+/// let chunks = preduce::reducers::InProcess::<RemoveRangesReducer<Chunks>>::new("chunks");
+/// # let _ = chunks;
+/// # }
+/// ```
+pub struct InProcess<R> {
+    name: String,
+    out_dir: Option<Arc<tempdir::TempDir>>,
+    counter: usize,
+    reducer: PhantomData<fn() -> R>,
+}
+
+impl<R> fmt::Debug for InProcess<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InProcess")
+            .field("name", &self.name)
+            .field("out_dir", &self.out_dir)
+            .field("counter", &self.counter)
+            .finish()
+    }
+}
+
+impl<R> InProcess<R>
+where
+    R: 'static + preduce_reducer_script::Reducer + Clone + Send,
+{
+    /// Construct an in-process reducer with the given `name`, which is used as
+    /// the provenance of the candidates it generates.
+    pub fn new<S>(name: S) -> InProcess<R>
+    where
+        S: Into<String>,
+    {
+        InProcess {
+            name: name.into(),
+            out_dir: None,
+            counter: 0,
+            reducer: PhantomData,
+        }
+    }
+
+    fn next_temp_file(&mut self) -> error::Result<test_case::TempFile> {
+        if self.out_dir.is_none() {
+            self.out_dir = Some(Arc::new(tempdir::TempDir::new("preduce-reducer")?));
+        }
+
+        let mut file_name = String::from("candidate");
+        file_name.push_str(&self.counter.to_string());
+        self.counter += 1;
+
+        let file_path = path::PathBuf::from(file_name);
+        test_case::TempFile::new(self.out_dir.as_ref().unwrap().clone(), file_path)
+    }
+
+    fn downcast(state: &Box<Any + Send>) -> &R {
+        state
+            .downcast_ref::<R>()
+            .expect("InProcess::downcast given unexpected state")
+    }
+
+    fn error<E: fmt::Display>(&self, method: &str, e: E) -> error::Error {
+        error::Error::Reducer(format!("{}: {}: {}", self.name, method, e))
+    }
+
+    fn boxed(state: Option<R>) -> Option<Box<Any + Send>> {
+        state.map(|s| Box::new(s) as Box<Any + Send>)
+    }
+}
+
+impl<R> Reducer for InProcess<R>
+where
+    R: 'static + preduce_reducer_script::Reducer + Clone + Send,
+{
+    fn name(&self) -> Cow<str> {
+        Cow::from(&self.name[..])
+    }
+
+    fn clone_boxed(&self) -> Box<Reducer>
+    where
+        Self: 'static,
+    {
+        Box::new(InProcess::<R>::new(self.name.clone()))
+    }
+
+    fn new_state(&mut self, seed: &test_case::Interesting) -> error::Result<Box<Any + Send>> {
+        match R::new(seed.path().into()) {
+            Ok(state) => Ok(Box::new(state)),
+            Err(e) => Err(self.error("new", e)),
+        }
+    }
+
+    fn clone_state(&self, state: &Box<Any + Send>) -> Box<Any + Send> {
+        Box::new(Self::downcast(state).clone())
+    }
+
+    fn next_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        match Self::downcast(state).clone().next(seed.path().into()) {
+            Ok(next_state) => Ok(Self::boxed(next_state)),
+            Err(e) => Err(self.error("next", e)),
+        }
+    }
+
+    fn next_state_on_interesting(
+        &mut self,
+        new_seed: &test_case::Interesting,
+        old_seed: &test_case::Interesting,
+        state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        match Self::downcast(state)
+            .clone()
+            .next_on_interesting(old_seed.path().into(), new_seed.path().into())
+        {
+            Ok(next_state) => Ok(Self::boxed(next_state)),
+            Err(e) => Err(self.error("next_on_interesting", e)),
+        }
+    }
+
+    fn fast_forward_states(
+        &mut self,
+        seed: &test_case::Interesting,
+        n: usize,
+        state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        match Self::downcast(state).clone().fast_forward(seed.path().into(), n) {
+            Ok(next_state) => Ok(Self::boxed(next_state)),
+            Err(e) => Err(self.error("fast_forward", e)),
+        }
+    }
+
+    fn reduce(
+        &mut self,
+        seed: &test_case::Interesting,
+        state: &Box<Any + Send>,
+    ) -> error::Result<Option<test_case::Candidate>> {
+        let temp_file = self.next_temp_file()?;
+        let reduced = match Self::downcast(state)
+            .clone()
+            .reduce(seed.path().into(), temp_file.path().into())
+        {
+            Ok(reduced) => reduced,
+            Err(e) => return Err(self.error("reduce", e)),
+        };
+
+        if !reduced {
+            return Ok(None);
+        }
+        if !temp_file.path().is_file() {
+            return Err(error::Error::Reducer(format!(
+                "{} did not generate a test case file at {}",
+                self.name,
+                temp_file.path().display()
+            )));
+        }
+        Ok(Some(test_case::Candidate::new(
+            seed.clone(),
+            self.name.clone(),
+            temp_file,
+        )?))
+    }
+
+    // The state is snapshotted in the same JSON form that a reducer script
+    // would use for it.
+
+    fn snapshot_state(&self, state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        serde_json::to_value(Self::downcast(state)).ok()
+    }
+
+    fn restore_state(
+        &mut self,
+        _seed: &test_case::Interesting,
+        snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let state: R = serde_json::from_value(snapshot)?;
+        Ok(Some(Box::new(state)))
+    }
+}
+
+/// Declare a marker type for each of the given `clang_delta`
+/// transformations, `clex` commands, or `topformflat` levels.
+macro_rules! markers {
+    ( $trait_:path, $method:ident, $ret:ty, { $( $marker:ident => $value:expr, )* } ) => {
+        $(
+            #[derive(Clone, Debug)]
+            struct $marker;
+
+            impl $trait_ for $marker {
+                fn $method() -> $ret {
+                    $value
+                }
+            }
+        )*
+    }
+}
+
+markers!(
+    preduce_clang_delta_reducer::ClangDelta,
+    transformation,
+    &'static str,
+    {
+        ClangDeltaAggregateToScalar => "aggregate-to-scalar",
+        ClangDeltaCallexprToValue => "callexpr-to-value",
+        ClangDeltaClassTemplateToClass => "class-template-to-class",
+        ClangDeltaCombineGlobalVar => "combine-global-var",
+        ClangDeltaCombineLocalVar => "combine-local-var",
+        ClangDeltaCopyPropagation => "copy-propagation",
+        ClangDeltaEmptyStructToInt => "empty-struct-to-int",
+        ClangDeltaInstantiateTemplateParam => "instantiate-template-param",
+        ClangDeltaInstantiateTemplateTypeParamToInt => "instantiate-template-type-param-to-int",
+        ClangDeltaLiftAssignmentExpr => "lift-assignment-expr",
+        ClangDeltaLocalToGlobal => "local-to-global",
+        ClangDeltaMoveFunctionBody => "move-function-body",
+        ClangDeltaMoveGlobalVar => "move-global-var",
+        ClangDeltaParamToGlobal => "param-to-global",
+        ClangDeltaParamToLocal => "param-to-local",
+        ClangDeltaReduceArrayDim => "reduce-array-dim",
+        ClangDeltaReduceArraySize => "reduce-array-size",
+        ClangDeltaReduceClassTemplateParam => "reduce-class-template-param",
+        ClangDeltaReducePointerLevel => "reduce-pointer-level",
+        ClangDeltaReducePointerPairs => "reduce-pointer-pairs",
+        ClangDeltaRemoveAddrTaken => "remove-addr-taken",
+        ClangDeltaRemoveArray => "remove-array",
+        ClangDeltaRemoveBaseClass => "remove-base-class",
+        ClangDeltaRemoveCtorInitializer => "remove-ctor-initializer",
+        ClangDeltaRemoveEnumMemberValue => "remove-enum-member-value",
+        ClangDeltaRemoveNamespace => "remove-namespace",
+        ClangDeltaRemoveNestedFunction => "remove-nested-function",
+        ClangDeltaRemovePointer => "remove-pointer",
+        ClangDeltaRemoveTrivialBaseTemplate => "remove-trivial-base-template",
+        ClangDeltaRemoveUnresolvedBase => "remove-unresolved-base",
+        ClangDeltaRemoveUnusedEnumMember => "remove-unused-enum-member",
+        ClangDeltaRemoveUnusedField => "remove-unused-field",
+        ClangDeltaRemoveUnusedFunction => "remove-unused-function",
+        ClangDeltaRemoveUnusedOuterClass => "remove-unused-outer-class",
+        ClangDeltaRenameClass => "rename-class",
+        ClangDeltaRenameCxxMethod => "rename-cxx-method",
+        ClangDeltaRenameFun => "rename-fun",
+        ClangDeltaRenameParam => "rename-param",
+        ClangDeltaRenameVar => "rename-var",
+        ClangDeltaReplaceArrayAccessWithIndex => "replace-array-access-with-index",
+        ClangDeltaReplaceArrayIndexVar => "replace-array-index-var",
+        ClangDeltaReplaceCallexpr => "replace-callexpr",
+        ClangDeltaReplaceClassWithBaseTemplateSpec => "replace-class-with-base-template-spec",
+        ClangDeltaReplaceDependentName => "replace-dependent-name",
+        ClangDeltaReplaceDependentTypedef => "replace-dependent-typedef",
+        ClangDeltaReplaceDerivedClass => "replace-derived-class",
+        ClangDeltaReplaceFunctionDefWithDecl => "replace-function-def-with-decl",
+        ClangDeltaReplaceOneLevelTypedefType => "replace-one-level-typedef-type",
+        ClangDeltaReplaceSimpleTypedef => "replace-simple-typedef",
+        ClangDeltaReplaceUndefinedFunction => "replace-undefined-function",
+        ClangDeltaReturnVoid => "return-void",
+        ClangDeltaSimpleInliner => "simple-inliner",
+        ClangDeltaSimplifyCallexpr => "simplify-callexpr",
+        ClangDeltaSimplifyCommaExpr => "simplify-comma-expr",
+        ClangDeltaSimplifyDependentTypedef => "simplify-dependent-typedef",
+        ClangDeltaSimplifyIf => "simplify-if",
+        ClangDeltaSimplifyNestedClass => "simplify-nested-class",
+        ClangDeltaSimplifyRecursiveTemplateInstantiation => "simplify-recursive-template-instantiation",
+        ClangDeltaSimplifyStructUnionDecl => "simplify-struct-union-decl",
+        ClangDeltaSimplifyStruct => "simplify-struct",
+        ClangDeltaTemplateArgToInt => "template-arg-to-int",
+        ClangDeltaTemplateNonTypeArgToInt => "template-non-type-arg-to-int",
+        ClangDeltaUnifyFunctionDecl => "unify-function-decl",
+        ClangDeltaUnionToStruct => "union-to-struct",
+        ClangDeltaVectorToArray => "vector-to-array",
+    }
+);
+
+markers!(
+    preduce_clex_reducer::Clex,
+    clex_command,
+    &'static str,
+    {
+        ClexDefine => "define",
+        ClexDeleteString => "delete-string",
+        ClexRenameToks => "rename-toks",
+        ClexRmTokPattern4 => "rm-tok-pattern-4",
+        ClexRmToks1 => "rm-toks-1",
+        ClexRmToks10 => "rm-toks-10",
+        ClexRmToks11 => "rm-toks-11",
+        ClexRmToks12 => "rm-toks-12",
+        ClexRmToks13 => "rm-toks-13",
+        ClexRmToks14 => "rm-toks-14",
+        ClexRmToks15 => "rm-toks-15",
+        ClexRmToks16 => "rm-toks-16",
+        ClexRmToks2 => "rm-toks-2",
+        ClexRmToks3 => "rm-toks-3",
+        ClexRmToks4 => "rm-toks-4",
+        ClexRmToks5 => "rm-toks-5",
+        ClexRmToks6 => "rm-toks-6",
+        ClexRmToks7 => "rm-toks-7",
+        ClexRmToks8 => "rm-toks-8",
+        ClexRmToks9 => "rm-toks-9",
+    }
+);
+
+markers!(
+    preduce_topformflat_reducer::Topformflat,
+    flatten,
+    u8,
+    {
+        Topformflat0 => 0,
+        Topformflat1 => 1,
+        Topformflat10 => 10,
+        Topformflat2 => 2,
+        Topformflat3 => 3,
+        Topformflat4 => 4,
+        Topformflat6 => 6,
+        Topformflat8 => 8,
+    }
+);
+
+/// Declare the table of built-in reducers.
+macro_rules! builtins {
+    ( $( $name:tt => $reducer:ty, )* ) => {
+        /// The names of `preduce`'s built-in reducers, which can be run
+        /// in-process with `reducers::builtin`.
+        ///
+        /// Each built-in reducer is also available as a reducer script named
+        /// `preduce-reducer-<name>`.
+        pub const BUILTINS: &'static [&'static str] = &[ $( $name, )* ];
+
+        /// Construct the built-in reducer with the given name, running
+        /// in-process, or `None` if there is no such built-in reducer.
+        ///
+        /// ```
+        /// let chunks = preduce::reducers::builtin("chunks");
+        /// assert!(chunks.is_some());
+        ///
+        /// let nope = preduce::reducers::builtin("no-such-reducer");
+        /// assert!(nope.is_none());
+        /// ```
+        pub fn builtin(name: &str) -> Option<Box<Reducer>> {
+            match name {
+                $( $name => Some(Box::new(InProcess::<$reducer>::new(name))), )*
+                _ => None,
+            }
+        }
+    }
+}
+
+builtins! {
+    "balanced-angle" => RemoveRangesReducer<RemoveBalancedReducer<Angles>>,
+    "balanced-curly" => RemoveRangesReducer<RemoveBalancedReducer<Curlies>>,
+    "balanced-paren" => RemoveRangesReducer<RemoveBalancedReducer<Parens>>,
+    "balanced-square" => RemoveRangesReducer<RemoveBalancedReducer<Squares>>,
+    "blank" => RemoveRangesReducer<Blank>,
+    "chunks" => RemoveRangesReducer<Chunks>,
+    "clang-delta-aggregate-to-scalar" => ClangDeltaReducer<ClangDeltaAggregateToScalar>,
+    "clang-delta-callexpr-to-value" => ClangDeltaReducer<ClangDeltaCallexprToValue>,
+    "clang-delta-class-template-to-class" => ClangDeltaReducer<ClangDeltaClassTemplateToClass>,
+    "clang-delta-combine-global-var" => ClangDeltaReducer<ClangDeltaCombineGlobalVar>,
+    "clang-delta-combine-local-var" => ClangDeltaReducer<ClangDeltaCombineLocalVar>,
+    "clang-delta-copy-propagation" => ClangDeltaReducer<ClangDeltaCopyPropagation>,
+    "clang-delta-empty-struct-to-int" => ClangDeltaReducer<ClangDeltaEmptyStructToInt>,
+    "clang-delta-instantiate-template-param" => ClangDeltaReducer<ClangDeltaInstantiateTemplateParam>,
+    "clang-delta-instantiate-template-type-param-to-int" => ClangDeltaReducer<ClangDeltaInstantiateTemplateTypeParamToInt>,
+    "clang-delta-lift-assignment-expr" => ClangDeltaReducer<ClangDeltaLiftAssignmentExpr>,
+    "clang-delta-local-to-global" => ClangDeltaReducer<ClangDeltaLocalToGlobal>,
+    "clang-delta-move-function-body" => ClangDeltaReducer<ClangDeltaMoveFunctionBody>,
+    "clang-delta-move-global-var" => ClangDeltaReducer<ClangDeltaMoveGlobalVar>,
+    "clang-delta-param-to-global" => ClangDeltaReducer<ClangDeltaParamToGlobal>,
+    "clang-delta-param-to-local" => ClangDeltaReducer<ClangDeltaParamToLocal>,
+    "clang-delta-reduce-array-dim" => ClangDeltaReducer<ClangDeltaReduceArrayDim>,
+    "clang-delta-reduce-array-size" => ClangDeltaReducer<ClangDeltaReduceArraySize>,
+    "clang-delta-reduce-class-template-param" => ClangDeltaReducer<ClangDeltaReduceClassTemplateParam>,
+    "clang-delta-reduce-pointer-level" => ClangDeltaReducer<ClangDeltaReducePointerLevel>,
+    "clang-delta-reduce-pointer-pairs" => ClangDeltaReducer<ClangDeltaReducePointerPairs>,
+    "clang-delta-remove-addr-taken" => ClangDeltaReducer<ClangDeltaRemoveAddrTaken>,
+    "clang-delta-remove-array" => ClangDeltaReducer<ClangDeltaRemoveArray>,
+    "clang-delta-remove-base-class" => ClangDeltaReducer<ClangDeltaRemoveBaseClass>,
+    "clang-delta-remove-ctor-initializer" => ClangDeltaReducer<ClangDeltaRemoveCtorInitializer>,
+    "clang-delta-remove-enum-member-value" => ClangDeltaReducer<ClangDeltaRemoveEnumMemberValue>,
+    "clang-delta-remove-namespace" => ClangDeltaReducer<ClangDeltaRemoveNamespace>,
+    "clang-delta-remove-nested-function" => ClangDeltaReducer<ClangDeltaRemoveNestedFunction>,
+    "clang-delta-remove-pointer" => ClangDeltaReducer<ClangDeltaRemovePointer>,
+    "clang-delta-remove-trivial-base-template" => ClangDeltaReducer<ClangDeltaRemoveTrivialBaseTemplate>,
+    "clang-delta-remove-unresolved-base" => ClangDeltaReducer<ClangDeltaRemoveUnresolvedBase>,
+    "clang-delta-remove-unused-enum-member" => ClangDeltaReducer<ClangDeltaRemoveUnusedEnumMember>,
+    "clang-delta-remove-unused-field" => ClangDeltaReducer<ClangDeltaRemoveUnusedField>,
+    "clang-delta-remove-unused-function" => ClangDeltaReducer<ClangDeltaRemoveUnusedFunction>,
+    "clang-delta-remove-unused-outer-class" => ClangDeltaReducer<ClangDeltaRemoveUnusedOuterClass>,
+    "clang-delta-rename-class" => ClangDeltaReducer<ClangDeltaRenameClass>,
+    "clang-delta-rename-cxx-method" => ClangDeltaReducer<ClangDeltaRenameCxxMethod>,
+    "clang-delta-rename-fun" => ClangDeltaReducer<ClangDeltaRenameFun>,
+    "clang-delta-rename-param" => ClangDeltaReducer<ClangDeltaRenameParam>,
+    "clang-delta-rename-var" => ClangDeltaReducer<ClangDeltaRenameVar>,
+    "clang-delta-replace-array-access-with-index" => ClangDeltaReducer<ClangDeltaReplaceArrayAccessWithIndex>,
+    "clang-delta-replace-array-index-var" => ClangDeltaReducer<ClangDeltaReplaceArrayIndexVar>,
+    "clang-delta-replace-callexpr" => ClangDeltaReducer<ClangDeltaReplaceCallexpr>,
+    "clang-delta-replace-class-with-base-template-spec" => ClangDeltaReducer<ClangDeltaReplaceClassWithBaseTemplateSpec>,
+    "clang-delta-replace-dependent-name" => ClangDeltaReducer<ClangDeltaReplaceDependentName>,
+    "clang-delta-replace-dependent-typedef" => ClangDeltaReducer<ClangDeltaReplaceDependentTypedef>,
+    "clang-delta-replace-derived-class" => ClangDeltaReducer<ClangDeltaReplaceDerivedClass>,
+    "clang-delta-replace-function-def-with-decl" => ClangDeltaReducer<ClangDeltaReplaceFunctionDefWithDecl>,
+    "clang-delta-replace-one-level-typedef-type" => ClangDeltaReducer<ClangDeltaReplaceOneLevelTypedefType>,
+    "clang-delta-replace-simple-typedef" => ClangDeltaReducer<ClangDeltaReplaceSimpleTypedef>,
+    "clang-delta-replace-undefined-function" => ClangDeltaReducer<ClangDeltaReplaceUndefinedFunction>,
+    "clang-delta-return-void" => ClangDeltaReducer<ClangDeltaReturnVoid>,
+    "clang-delta-simple-inliner" => ClangDeltaReducer<ClangDeltaSimpleInliner>,
+    "clang-delta-simplify-callexpr" => ClangDeltaReducer<ClangDeltaSimplifyCallexpr>,
+    "clang-delta-simplify-comma-expr" => ClangDeltaReducer<ClangDeltaSimplifyCommaExpr>,
+    "clang-delta-simplify-dependent-typedef" => ClangDeltaReducer<ClangDeltaSimplifyDependentTypedef>,
+    "clang-delta-simplify-if" => ClangDeltaReducer<ClangDeltaSimplifyIf>,
+    "clang-delta-simplify-nested-class" => ClangDeltaReducer<ClangDeltaSimplifyNestedClass>,
+    "clang-delta-simplify-recursive-template-instantiation" => ClangDeltaReducer<ClangDeltaSimplifyRecursiveTemplateInstantiation>,
+    "clang-delta-simplify-struct-union-decl" => ClangDeltaReducer<ClangDeltaSimplifyStructUnionDecl>,
+    "clang-delta-simplify-struct" => ClangDeltaReducer<ClangDeltaSimplifyStruct>,
+    "clang-delta-template-arg-to-int" => ClangDeltaReducer<ClangDeltaTemplateArgToInt>,
+    "clang-delta-template-non-type-arg-to-int" => ClangDeltaReducer<ClangDeltaTemplateNonTypeArgToInt>,
+    "clang-delta-unify-function-decl" => ClangDeltaReducer<ClangDeltaUnifyFunctionDecl>,
+    "clang-delta-union-to-struct" => ClangDeltaReducer<ClangDeltaUnionToStruct>,
+    "clang-delta-vector-to-array" => ClangDeltaReducer<ClangDeltaVectorToArray>,
+    "clex-define" => ClexReducer<ClexDefine>,
+    "clex-delete-string" => ClexReducer<ClexDeleteString>,
+    "clex-rename-toks" => ClexReducer<ClexRenameToks>,
+    "clex-rm-tok-pattern-4" => ClexReducer<ClexRmTokPattern4>,
+    "clex-rm-toks-1" => ClexReducer<ClexRmToks1>,
+    "clex-rm-toks-10" => ClexReducer<ClexRmToks10>,
+    "clex-rm-toks-11" => ClexReducer<ClexRmToks11>,
+    "clex-rm-toks-12" => ClexReducer<ClexRmToks12>,
+    "clex-rm-toks-13" => ClexReducer<ClexRmToks13>,
+    "clex-rm-toks-14" => ClexReducer<ClexRmToks14>,
+    "clex-rm-toks-15" => ClexReducer<ClexRmToks15>,
+    "clex-rm-toks-16" => ClexReducer<ClexRmToks16>,
+    "clex-rm-toks-2" => ClexReducer<ClexRmToks2>,
+    "clex-rm-toks-3" => ClexReducer<ClexRmToks3>,
+    "clex-rm-toks-4" => ClexReducer<ClexRmToks4>,
+    "clex-rm-toks-5" => ClexReducer<ClexRmToks5>,
+    "clex-rm-toks-6" => ClexReducer<ClexRmToks6>,
+    "clex-rm-toks-7" => ClexReducer<ClexRmToks7>,
+    "clex-rm-toks-8" => ClexReducer<ClexRmToks8>,
+    "clex-rm-toks-9" => ClexReducer<ClexRmToks9>,
+    "includes" => RemoveRangesReducer<RemoveRegexReducer<Includes>>,
+    "topformflat-0" => TopformflatReducer<Topformflat0>,
+    "topformflat-1" => TopformflatReducer<Topformflat1>,
+    "topformflat-10" => TopformflatReducer<Topformflat10>,
+    "topformflat-2" => TopformflatReducer<Topformflat2>,
+    "topformflat-3" => TopformflatReducer<Topformflat3>,
+    "topformflat-4" => TopformflatReducer<Topformflat4>,
+    "topformflat-6" => TopformflatReducer<Topformflat6>,
+    "topformflat-8" => TopformflatReducer<Topformflat8>,
 }
 
 /// A reducer which ends after the first `Ok(None)` or `Err`.
@@ -652,12 +1157,492 @@ where
             }
         }
     }
+
+    fn snapshot_state(&self, state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        let state = self.downcast(state);
+        let state = state.borrow();
+        match *state {
+            FuseState::Finished => None,
+            FuseState::NotFinished(ref inner) => self.inner.snapshot_state(inner),
+        }
+    }
+
+    fn restore_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let inner = self.inner.restore_state(seed, snapshot)?;
+        Ok(inner.map(|inner| {
+            Box::new(RefCell::new(FuseState::NotFinished(inner))) as Box<Any + Send>
+        }))
+    }
+}
+
+/// Create a new, empty directory within the given reducer's output directory
+/// (creating the output directory if needed), and return a temporary file
+/// within it that has the same file name as the seed.
+fn next_dir_candidate(
+    out_dir: &mut Option<Arc<tempdir::TempDir>>,
+    counter: &mut usize,
+    seed: &test_case::Interesting,
+) -> error::Result<test_case::TempFile> {
+    if out_dir.is_none() {
+        *out_dir = Some(Arc::new(tempdir::TempDir::new("preduce-dir-reducer")?));
+    }
+    let out_dir = out_dir.as_ref().unwrap();
+
+    let dir_name = format!("candidate{}", *counter);
+    *counter += 1;
+    fs::create_dir(out_dir.path().join(&dir_name))?;
+
+    let file_name = seed.path()
+        .file_name()
+        .expect("seeds always have a file name");
+    test_case::TempFile::new(out_dir.clone(), path::Path::new(&dir_name).join(file_name))
+}
+
+/// A reducer combinator that applies a single-file reducer to each file within
+/// a directory test case, one after the other.
+///
+/// The inner reducer is given a view of one member file as its seed, and its
+/// candidates are spliced back into a copy of the whole directory. When the
+/// test case is a single file, rather than a directory, the inner reducer is
+/// used as-is.
+///
+/// ### Example
+///
+/// ```
+/// extern crate preduce;
+/// use preduce::traits::Reducer;
+///
+/// # fn main() { fn _foo() -> preduce::error::Result<()> {
+/// let script = preduce::reducers::Script::new("/path/to/some/reducer/script")?;
+/// let mut members = preduce::reducers::Members::new(script);
+///
+/// # let some_directory_test_case = || unimplemented!();
+/// let seed = some_directory_test_case();
+/// let state = members.new_state(&seed)?;
+///
+/// if let Some(candidate) = members.reduce(&seed, &state)? {
+///     // The candidate is a copy of the whole directory, with one of its files
+///     // reduced by the script.
+///     println!("A candidate is {:?}", candidate);
+/// }
+/// # Ok(()) } }
+/// ```
+#[derive(Debug)]
+pub struct Members<R> {
+    inner: R,
+    out_dir: Option<Arc<tempdir::TempDir>>,
+    counter: usize,
+}
+
+#[derive(Debug)]
+struct MembersState {
+    /// The seed's member files, or `None` if the seed is a single file.
+    members: Option<Arc<Vec<path::PathBuf>>>,
+
+    /// The index of the member file currently being reduced.
+    index: usize,
+
+    /// The inner reducer's state for the current member file, or `None` if
+    /// there are no member files left.
+    inner: Option<Box<Any + Send>>,
+}
+
+/// The saved form of a `MembersState`.
+#[derive(Debug, Deserialize, Serialize)]
+struct MembersSnapshot {
+    member: Option<path::PathBuf>,
+    inner: serde_json::Value,
+}
+
+impl<R> Members<R> {
+    /// Apply the given single-file `inner` reducer to each file of a directory
+    /// test case.
+    pub fn new(inner: R) -> Members<R> {
+        Members {
+            inner: inner,
+            out_dir: None,
+            counter: 0,
+        }
+    }
+
+    fn downcast(state: &Box<Any + Send>) -> &MembersState {
+        state
+            .downcast_ref::<MembersState>()
+            .expect("Members::downcast given unexpected state")
+    }
+}
+
+impl<R> Members<R>
+where
+    R: Reducer,
+{
+    /// Create the inner reducer's state for the first member file at or after
+    /// the given index.
+    fn start_at(
+        &mut self,
+        seed: &test_case::Interesting,
+        members: Arc<Vec<path::PathBuf>>,
+        index: usize,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        if index >= members.len() {
+            return Ok(None);
+        }
+
+        let inner = self.inner.new_state(&seed.member(&members[index])?)?;
+        Ok(Some(Box::new(MembersState {
+            members: Some(members),
+            index: index,
+            inner: Some(inner),
+        })))
+    }
+
+    /// Wrap a new inner state for the same member file as `prev`, or move on to
+    /// the next member file if the inner reducer is exhausted.
+    fn advance(
+        &mut self,
+        seed: &test_case::Interesting,
+        prev: &MembersState,
+        next_inner: Option<Box<Any + Send>>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        match (prev.members.as_ref(), next_inner) {
+            (None, next_inner) => Ok(next_inner.map(|inner| {
+                Box::new(MembersState {
+                    members: None,
+                    index: 0,
+                    inner: Some(inner),
+                }) as Box<Any + Send>
+            })),
+            (Some(members), Some(inner)) => Ok(Some(Box::new(MembersState {
+                members: Some(members.clone()),
+                index: prev.index,
+                inner: Some(inner),
+            }))),
+            (Some(members), None) => self.start_at(seed, members.clone(), prev.index + 1),
+        }
+    }
+}
+
+impl<R> Reducer for Members<R>
+where
+    R: Reducer,
+{
+    fn name(&self) -> Cow<str> {
+        self.inner.name()
+    }
+
+    fn clone_boxed(&self) -> Box<Reducer>
+    where
+        Self: 'static,
+    {
+        Box::new(Members::new(self.inner.clone_boxed()))
+    }
+
+    fn new_state(&mut self, seed: &test_case::Interesting) -> error::Result<Box<Any + Send>> {
+        if !seed.is_dir() {
+            return Ok(Box::new(MembersState {
+                members: None,
+                index: 0,
+                inner: Some(self.inner.new_state(seed)?),
+            }));
+        }
+
+        let members = Arc::new(test_case::members(seed.path())?);
+        match self.start_at(seed, members.clone(), 0)? {
+            Some(state) => Ok(state),
+            None => Ok(Box::new(MembersState {
+                members: Some(members),
+                index: 0,
+                inner: None,
+            })),
+        }
+    }
+
+    fn clone_state(&self, state: &Box<Any + Send>) -> Box<Any + Send> {
+        let state = Self::downcast(state);
+        Box::new(MembersState {
+            members: state.members.clone(),
+            index: state.index,
+            inner: state.inner.as_ref().map(|i| self.inner.clone_state(i)),
+        })
+    }
+
+    fn next_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        prev_state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let prev_state = Self::downcast(prev_state);
+        let inner = match prev_state.inner {
+            None => return Ok(None),
+            Some(ref inner) => inner,
+        };
+
+        let next_inner = match prev_state.members {
+            None => self.inner.next_state(seed, inner)?,
+            Some(ref members) => {
+                let member = seed.member(&members[prev_state.index])?;
+                self.inner.next_state(&member, inner)?
+            }
+        };
+        self.advance(seed, prev_state, next_inner)
+    }
+
+    fn next_state_on_interesting(
+        &mut self,
+        new_seed: &test_case::Interesting,
+        old_seed: &test_case::Interesting,
+        prev_state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let prev_state = Self::downcast(prev_state);
+        let inner = match prev_state.inner {
+            None => return Ok(None),
+            Some(ref inner) => inner,
+        };
+
+        let old_members = match prev_state.members {
+            None => {
+                let next_inner = self.inner
+                    .next_state_on_interesting(new_seed, old_seed, inner)?;
+                return self.advance(new_seed, prev_state, next_inner);
+            }
+            Some(ref members) => members,
+        };
+
+        // The new seed may have a different set of member files than the old
+        // one, so find where the current member file is within it.
+        let member = &old_members[prev_state.index];
+        let new_members = Arc::new(test_case::members(new_seed.path())?);
+        match new_members.binary_search(member) {
+            Ok(index) => {
+                let next_inner = self.inner.next_state_on_interesting(
+                    &new_seed.member(member)?,
+                    &old_seed.member(member)?,
+                    inner,
+                )?;
+                let prev = MembersState {
+                    members: Some(new_members),
+                    index: index,
+                    inner: None,
+                };
+                self.advance(new_seed, &prev, next_inner)
+            }
+            Err(index) => self.start_at(new_seed, new_members, index),
+        }
+    }
+
+    fn reduce(
+        &mut self,
+        seed: &test_case::Interesting,
+        state: &Box<Any + Send>,
+    ) -> error::Result<Option<test_case::Candidate>> {
+        let state = Self::downcast(state);
+        let inner = match state.inner {
+            None => return Ok(None),
+            Some(ref inner) => inner,
+        };
+
+        let members = match state.members {
+            None => return self.inner.reduce(seed, inner),
+            Some(ref members) => members,
+        };
+
+        let member = &members[state.index];
+        let reduced = match self.inner.reduce(&seed.member(member)?, inner)? {
+            None => return Ok(None),
+            Some(reduced) => reduced,
+        };
+
+        let temp_file = next_dir_candidate(&mut self.out_dir, &mut self.counter, seed)?;
+        test_case::copy_test_case(seed.path(), temp_file.path())?;
+        fs::copy(reduced.path(), temp_file.path().join(member))?;
+
+        Ok(Some(test_case::Candidate::new(
+            seed.clone(),
+            reduced.provenance(),
+            temp_file,
+        )?))
+    }
+
+    fn snapshot_state(&self, state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        let state = Self::downcast(state);
+        let inner = self.inner.snapshot_state(state.inner.as_ref()?)?;
+        let snapshot = MembersSnapshot {
+            member: state.members.as_ref().map(|m| m[state.index].clone()),
+            inner: inner,
+        };
+        serde_json::to_value(snapshot).ok()
+    }
+
+    fn restore_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let snapshot: MembersSnapshot = serde_json::from_value(snapshot)?;
+
+        let member = match snapshot.member {
+            None => {
+                let inner = self.inner.restore_state(seed, snapshot.inner)?;
+                return Ok(inner.map(|inner| {
+                    Box::new(MembersState {
+                        members: None,
+                        index: 0,
+                        inner: Some(inner),
+                    }) as Box<Any + Send>
+                }));
+            }
+            Some(member) => member,
+        };
+
+        let members = Arc::new(test_case::members(seed.path())?);
+        let index = match members.binary_search(&member) {
+            Ok(index) => index,
+            Err(_) => return Ok(None),
+        };
+
+        let inner = self.inner
+            .restore_state(&seed.member(&member)?, snapshot.inner)?;
+        Ok(inner.map(|inner| {
+            Box::new(MembersState {
+                members: Some(members),
+                index: index,
+                inner: Some(inner),
+            }) as Box<Any + Send>
+        }))
+    }
+}
+
+/// A reducer that removes whole files from a directory test case, one at a
+/// time.
+///
+/// When the test case is a single file, rather than a directory, this reducer
+/// never generates any candidates.
+#[derive(Debug, Default)]
+pub struct RemoveFiles {
+    out_dir: Option<Arc<tempdir::TempDir>>,
+    counter: usize,
+}
+
+impl RemoveFiles {
+    /// Construct a new `RemoveFiles` reducer.
+    pub fn new() -> RemoveFiles {
+        RemoveFiles::default()
+    }
+
+    fn downcast(state: &Box<Any + Send>) -> usize {
+        *state
+            .downcast_ref::<usize>()
+            .expect("RemoveFiles::downcast given unexpected state")
+    }
+
+    /// Get the next index after `index` if there is a file there in the given
+    /// seed.
+    fn next_index(seed: &test_case::Interesting, index: usize) -> error::Result<Option<usize>> {
+        if !seed.is_dir() || index >= test_case::members(seed.path())?.len() {
+            return Ok(None);
+        }
+        Ok(Some(index))
+    }
+}
+
+impl Reducer for RemoveFiles {
+    fn name(&self) -> Cow<str> {
+        Cow::from("remove-files")
+    }
+
+    fn clone_boxed(&self) -> Box<Reducer>
+    where
+        Self: 'static,
+    {
+        Box::new(RemoveFiles::new())
+    }
+
+    fn new_state(&mut self, _seed: &test_case::Interesting) -> error::Result<Box<Any + Send>> {
+        Ok(Box::new(0usize))
+    }
+
+    fn clone_state(&self, state: &Box<Any + Send>) -> Box<Any + Send> {
+        Box::new(Self::downcast(state))
+    }
+
+    fn next_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        prev_state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let index = Self::downcast(prev_state);
+        Ok(Self::next_index(seed, index + 1)?.map(|i| Box::new(i) as Box<Any + Send>))
+    }
+
+    fn next_state_on_interesting(
+        &mut self,
+        new_seed: &test_case::Interesting,
+        _old_seed: &test_case::Interesting,
+        prev_state: &Box<Any + Send>,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        // The file at our index was removed, so the next file has shifted down
+        // into its place.
+        let index = Self::downcast(prev_state);
+        Ok(Self::next_index(new_seed, index)?.map(|i| Box::new(i) as Box<Any + Send>))
+    }
+
+    fn reduce(
+        &mut self,
+        seed: &test_case::Interesting,
+        state: &Box<Any + Send>,
+    ) -> error::Result<Option<test_case::Candidate>> {
+        if !seed.is_dir() {
+            return Ok(None);
+        }
+
+        let members = test_case::members(seed.path())?;
+        let member = match members.get(Self::downcast(state)) {
+            None => return Ok(None),
+            Some(member) => member,
+        };
+
+        let temp_file = next_dir_candidate(&mut self.out_dir, &mut self.counter, seed)?;
+        test_case::copy_test_case(seed.path(), temp_file.path())?;
+        fs::remove_file(temp_file.path().join(member))?;
+
+        Ok(Some(test_case::Candidate::new(
+            seed.clone(),
+            self.name(),
+            temp_file,
+        )?))
+    }
+
+    fn snapshot_state(&self, state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        Some(serde_json::Value::from(Self::downcast(state) as u64))
+    }
+
+    fn restore_state(
+        &mut self,
+        seed: &test_case::Interesting,
+        snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        let index = snapshot.as_u64().ok_or_else(|| {
+            error::Error::InvalidCheckpoint(format!(
+                "expected a file index for remove-files, found {}",
+                snapshot
+            ))
+        })?;
+        Ok(Self::next_index(seed, index as usize)?.map(|i| Box::new(i) as Box<Any + Send>))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::borrow::Cow;
+    use std::fs;
+    use std::io::Write;
+    use std::path;
+    use tempdir;
     use test_case;
     use traits::Reducer;
 
@@ -736,6 +1721,178 @@ mod tests {
         assert!(reducer.reduce(&seed, &state).unwrap().is_none());
     }
 
+    /// Create an interesting directory test case with the given files.
+    fn directory_seed(dir: &tempdir::TempDir, files: &[(&str, &str)]) -> test_case::Interesting {
+        let root = dir.path().join("seed");
+        for &(name, contents) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let mut file = fs::File::create(path).unwrap();
+            write!(&mut file, "{}", contents).unwrap();
+        }
+
+        let judge = |_: &path::Path| Ok(true);
+        test_case::Interesting::initial(&root, &judge)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn members() {
+        /// Empties the seed file, exactly once.
+        #[derive(Clone, Debug)]
+        struct Empty;
+
+        impl Reducer for Empty {
+            fn name(&self) -> Cow<str> {
+                Cow::from("empty")
+            }
+
+            fn clone_boxed(&self) -> Box<Reducer>
+            where
+                Self: 'static,
+            {
+                Box::new(self.clone())
+            }
+
+            fn new_state(&mut self, seed: &test_case::Interesting) -> error::Result<Box<Any + Send>> {
+                assert!(!seed.is_dir());
+                assert!(seed.member_path().is_some());
+                Ok(Box::new(()))
+            }
+
+            fn clone_state(&self, _: &Box<Any + Send>) -> Box<Any + Send> {
+                Box::new(())
+            }
+
+            fn next_state(
+                &mut self,
+                _seed: &test_case::Interesting,
+                _prev_state: &Box<Any + Send>,
+            ) -> error::Result<Option<Box<Any + Send>>> {
+                Ok(None)
+            }
+
+            fn next_state_on_interesting(
+                &mut self,
+                _new_seed: &test_case::Interesting,
+                _old_seed: &test_case::Interesting,
+                _prev_state: &Box<Any + Send>,
+            ) -> error::Result<Option<Box<Any + Send>>> {
+                Ok(None)
+            }
+
+            fn reduce(
+                &mut self,
+                seed: &test_case::Interesting,
+                _state: &Box<Any + Send>,
+            ) -> error::Result<Option<test_case::Candidate>> {
+                let temp_file = test_case::TempFile::anonymous()?;
+                fs::File::create(temp_file.path())?;
+                Ok(Some(test_case::Candidate::new(seed.clone(), "empty", temp_file)?))
+            }
+
+            fn snapshot_state(&self, _state: &Box<Any + Send>) -> Option<serde_json::Value> {
+                Some(serde_json::Value::Null)
+            }
+
+            fn restore_state(
+                &mut self,
+                _seed: &test_case::Interesting,
+                _snapshot: serde_json::Value,
+            ) -> error::Result<Option<Box<Any + Send>>> {
+                Ok(Some(Box::new(())))
+            }
+        }
+
+        let dir = tempdir::TempDir::new("reducers_members").unwrap();
+        let seed = directory_seed(&dir, &[("a", "aaa"), ("sub/b", "bb")]);
+
+        let mut reducer = Members::new(Empty);
+        let state = reducer.new_state(&seed).unwrap();
+
+        let candidate = reducer.reduce(&seed, &state).unwrap().unwrap();
+        assert_eq!(candidate.provenance(), "empty");
+        assert_eq!(candidate.size(), 2);
+        assert_eq!(fs::metadata(candidate.path().join("a")).unwrap().len(), 0);
+        assert_eq!(fs::metadata(candidate.path().join("sub/b")).unwrap().len(), 2);
+
+        let state = reducer.next_state(&seed, &state).unwrap().unwrap();
+        let snapshot = reducer.snapshot_state(&state).unwrap();
+        let candidate = reducer.reduce(&seed, &state).unwrap().unwrap();
+        assert_eq!(candidate.size(), 3);
+        assert_eq!(fs::metadata(candidate.path().join("sub/b")).unwrap().len(), 0);
+
+        assert!(reducer.next_state(&seed, &state).unwrap().is_none());
+
+        let restored = reducer.restore_state(&seed, snapshot).unwrap().unwrap();
+        let candidate = reducer.reduce(&seed, &restored).unwrap().unwrap();
+        assert_eq!(candidate.size(), 3);
+    }
+
+    #[test]
+    fn remove_files() {
+        let dir = tempdir::TempDir::new("reducers_remove_files").unwrap();
+        let seed = directory_seed(&dir, &[("a", "aaa"), ("b", "bb"), ("c", "c")]);
+
+        let mut reducer = RemoveFiles::new();
+        let state = reducer.new_state(&seed).unwrap();
+        let candidate = reducer.reduce(&seed, &state).unwrap().unwrap();
+        assert_eq!(candidate.provenance(), "remove-files");
+        assert_eq!(
+            test_case::members(candidate.path()).unwrap(),
+            vec![path::PathBuf::from("b"), path::PathBuf::from("c")]
+        );
+
+        // Once "a" is gone, "b" is at the same index.
+        let judge = |_: &path::Path| Ok(true);
+        let new_seed = candidate.into_interesting(&judge).unwrap().left().unwrap();
+        let state = reducer
+            .next_state_on_interesting(&new_seed, &seed, &state)
+            .unwrap()
+            .unwrap();
+        let candidate = reducer.reduce(&new_seed, &state).unwrap().unwrap();
+        assert_eq!(
+            test_case::members(candidate.path()).unwrap(),
+            vec![path::PathBuf::from("c")]
+        );
+
+        let state = reducer.next_state(&new_seed, &state).unwrap().unwrap();
+        assert!(reducer.next_state(&new_seed, &state).unwrap().is_none());
+
+        // Single-file test cases have no files to remove.
+        let file_seed = test_case::Interesting::testing_only_new();
+        let state = reducer.new_state(&file_seed).unwrap();
+        assert!(reducer.reduce(&file_seed, &state).unwrap().is_none());
+    }
+
+    #[test]
+    fn in_process_snapshot() {
+        let dir = tempdir::TempDir::new("reducers_in_process_snapshot").unwrap();
+        let seed_path = dir.path().join("seed");
+        write!(&mut fs::File::create(&seed_path).unwrap(), "a\nb\nc\nd\n").unwrap();
+        let judge = |_: &path::Path| Ok(true);
+        let seed = test_case::Interesting::initial(&seed_path, &judge)
+            .unwrap()
+            .unwrap();
+
+        let mut reducer = builtin("chunks").expect("chunks is a built-in reducer");
+        assert_eq!(reducer.name(), "chunks");
+
+        let state = reducer.new_state(&seed).unwrap();
+        let state = reducer.next_state(&seed, &state).unwrap().unwrap();
+        let candidate = reducer.reduce(&seed, &state).unwrap().unwrap();
+        assert_eq!(candidate.provenance(), "chunks");
+
+        // A restored state generates the same candidate.
+        let snapshot = reducer.snapshot_state(&state).unwrap();
+        let restored = reducer.restore_state(&seed, snapshot).unwrap().unwrap();
+        let restored_candidate = reducer.reduce(&seed, &restored).unwrap().unwrap();
+        assert_eq!(restored_candidate.size(), candidate.size());
+
+        assert!(builtin("no-such-reducer").is_none());
+    }
+
     #[test]
     fn not_executable() {
         match Script::new("./tests/fixtures/lorem-ipsum.txt") {
@@ -0,0 +1,516 @@
+//! Judging candidates on remote workers over TCP.
+//!
+//! A `preduce` coordinator listens for connections from remote workers (see
+//! `Options::listen`). Each connection is driven by its own worker actor, just
+//! like a local worker, except that the worker's is-interesting predicate sends
+//! the candidate's contents over the connection, and waits for the remote
+//! worker to run its own copy of the predicate and report the judgement back.
+//!
+//! The other end of the connection is served by a `RemoteWorker`, which is
+//! what the `preduce-worker` executable runs. The wire protocol is described
+//! in the `preduce_ipc_types` crate.
+
+use error;
+use preduce_ipc_types::{ErrorResponse, HelloRequest, HelloResponse, JudgeRequest, JudgedResponse,
+                        TimedOutResponse, WorkerRequest, WorkerResponse, WORKER_PROTOCOL_VERSION};
+use serde_json;
+use std::collections::BTreeMap;
+use std::ffi;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
+use std::net;
+use std::path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time;
+use tempdir;
+use traits::IsInteresting;
+
+/// How long the listener sleeps between polls for new connections.
+const ACCEPT_POLL_INTERVAL_MILLIS: u64 = 50;
+
+/// The version of the protocol spoken by a coordinator that doesn't say hello.
+const UNVERSIONED_PROTOCOL_VERSION: u32 = 1;
+
+/// The file name a candidate is judged under when the coordinator's file name
+/// is unusable.
+const DEFAULT_CANDIDATE_FILE_NAME: &'static str = "candidate";
+
+/// Serves judgement requests from a `preduce` coordinator, running the
+/// requested is-interesting predicates locally.
+///
+/// ```no_run
+/// # fn _ignore() -> preduce::error::Result<()> {
+/// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+///
+/// let mut worker = preduce::remote::RemoteWorker::new();
+/// worker.add_predicate("is_interesting.sh", predicate);
+///
+/// let stream = std::net::TcpStream::connect("coordinator:4000")?;
+/// worker.serve(stream)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RemoteWorker {
+    predicates: BTreeMap<String, Box<IsInteresting>>,
+}
+
+impl fmt::Debug for RemoteWorker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RemoteWorker")
+            .field("predicates", &self.predicates.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for RemoteWorker {
+    fn default() -> RemoteWorker {
+        RemoteWorker::new()
+    }
+}
+
+impl RemoteWorker {
+    /// Construct a new remote worker, without any predicates.
+    pub fn new() -> RemoteWorker {
+        RemoteWorker {
+            predicates: BTreeMap::new(),
+        }
+    }
+
+    /// Run the given predicate when the coordinator asks for a judgement from
+    /// the predicate with the given name.
+    pub fn add_predicate<N, I>(&mut self, name: N, predicate: I)
+    where
+        N: Into<String>,
+        I: 'static + IsInteresting,
+    {
+        self.predicates.insert(name.into(), Box::new(predicate));
+    }
+
+    /// Serve the coordinator's judgement requests on the given connection,
+    /// until the coordinator closes it.
+    ///
+    /// Returns `Error::RemoteProtocolMismatch` if the coordinator speaks a
+    /// different version of the protocol.
+    pub fn serve(&self, stream: net::TcpStream) -> error::Result<()> {
+        let dir = tempdir::TempDir::new("preduce-remote-worker")?;
+
+        let mut writer = stream.try_clone()?;
+        let mut reader = io::BufReader::new(stream);
+        let mut line = String::new();
+        let mut greeted = false;
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            let request = match serde_json::from_str(&line) {
+                Ok(WorkerRequest::Hello(hello)) => {
+                    respond(
+                        &mut writer,
+                        &WorkerResponse::Hello(HelloResponse {
+                            protocol_version: WORKER_PROTOCOL_VERSION,
+                        }),
+                    )?;
+                    if hello.protocol_version != WORKER_PROTOCOL_VERSION {
+                        return Err(error::Error::RemoteProtocolMismatch(
+                            WORKER_PROTOCOL_VERSION,
+                            hello.protocol_version,
+                        ));
+                    }
+                    greeted = true;
+                    continue;
+                }
+                // Coordinators that predate the handshake start right away
+                // with requests that we can't understand.
+                Ok(WorkerRequest::Judge(_)) | Err(_) if !greeted => {
+                    return Err(error::Error::RemoteProtocolMismatch(
+                        WORKER_PROTOCOL_VERSION,
+                        UNVERSIONED_PROTOCOL_VERSION,
+                    ))
+                }
+                Ok(WorkerRequest::Judge(request)) => request,
+                Err(e) => return Err(e.into()),
+            };
+
+            let candidate = dir.path().join(candidate_file_name(&request.file_name));
+            {
+                let mut file = fs::File::create(&candidate)?;
+                let copied = io::copy(&mut (&mut reader).take(request.len), &mut file)?;
+                if copied != request.len {
+                    return Err(error::Error::Io(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "coordinator disconnected while sending a candidate",
+                    )));
+                }
+            }
+
+            let response = self.judge(&request.predicate, &candidate);
+            fs::remove_file(&candidate)?;
+            respond(&mut writer, &response)?;
+        }
+    }
+
+    fn judge(&self, predicate: &str, candidate: &path::Path) -> WorkerResponse {
+        let predicate = match self.predicates.get(predicate) {
+            Some(p) => p,
+            None => {
+                return WorkerResponse::Error(ErrorResponse {
+                    message: format!("no predicate named '{}'", predicate),
+                })
+            }
+        };
+
+        match predicate.is_interesting(candidate) {
+            Ok(interesting) => WorkerResponse::Judged(JudgedResponse {
+                interesting: interesting,
+            }),
+            Err(error::Error::PredicateTimedOut(timeout, _)) => {
+                WorkerResponse::TimedOut(TimedOutResponse {
+                    millis: timeout.as_secs() * 1000 + (timeout.subsec_nanos() / 1_000_000) as u64,
+                })
+            }
+            Err(e) => WorkerResponse::Error(ErrorResponse {
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// Get the file name to judge a candidate under from the file name the
+/// coordinator gave it, ignoring anything that would put it outside of the
+/// candidate's directory.
+fn candidate_file_name(file_name: &str) -> &str {
+    if path::Path::new(file_name).file_name() == Some(ffi::OsStr::new(file_name)) {
+        file_name
+    } else {
+        DEFAULT_CANDIDATE_FILE_NAME
+    }
+}
+
+/// Write a response to the coordinator.
+fn respond(writer: &mut net::TcpStream, response: &WorkerResponse) -> error::Result<()> {
+    serde_json::to_writer(&mut *writer, response)?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Accepts connections from remote workers on a background thread.
+#[derive(Debug)]
+pub(crate) struct Listener {
+    local_addr: net::SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl Listener {
+    /// Listen on the given address, and call `on_connect` with each remote
+    /// worker's connection.
+    pub fn spawn<F>(addr: &str, on_connect: F) -> error::Result<Listener>
+    where
+        F: 'static + Send + Fn(net::TcpStream),
+    {
+        let listener = net::TcpListener::bind(addr)?;
+        // Poll for connections, so that we notice when we should stop.
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop2 = stop.clone();
+        let handle = thread::Builder::new()
+            .name("preduce-listener".into())
+            .spawn(move || while !stop2.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if stream.set_nonblocking(false).is_ok() {
+                            on_connect(stream);
+                        }
+                    }
+                    Err(_) => {
+                        thread::sleep(time::Duration::from_millis(ACCEPT_POLL_INTERVAL_MILLIS))
+                    }
+                }
+            })?;
+
+        Ok(Listener {
+            local_addr: local_addr,
+            stop: stop,
+            handle: handle,
+        })
+    }
+
+    /// Get the address we are listening on.
+    pub fn local_addr(&self) -> net::SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections.
+    pub fn shutdown(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = self.handle.join();
+    }
+}
+
+/// The connection to a remote worker.
+#[derive(Debug)]
+struct Connection {
+    reader: io::BufReader<net::TcpStream>,
+    writer: net::TcpStream,
+    greeted: bool,
+}
+
+impl Connection {
+    /// Send the given request, followed by the given bytes, and wait for the
+    /// remote worker's response.
+    fn request(
+        &mut self,
+        request: &WorkerRequest,
+        contents: &[u8],
+    ) -> error::Result<WorkerResponse> {
+        serde_json::to_writer(&mut self.writer, request)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.write_all(contents)?;
+        self.writer.flush()?;
+
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(error::Error::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "remote worker disconnected",
+            )));
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    /// Make sure that the remote worker speaks our version of the protocol,
+    /// if we haven't already.
+    fn greet(&mut self) -> error::Result<()> {
+        if self.greeted {
+            return Ok(());
+        }
+
+        let hello = WorkerRequest::Hello(HelloRequest {
+            protocol_version: WORKER_PROTOCOL_VERSION,
+        });
+        match self.request(&hello, &[]) {
+            Ok(WorkerResponse::Hello(ref hello))
+                if hello.protocol_version == WORKER_PROTOCOL_VERSION =>
+            {
+                self.greeted = true;
+                Ok(())
+            }
+            Ok(WorkerResponse::Hello(hello)) => Err(error::Error::RemoteProtocolMismatch(
+                WORKER_PROTOCOL_VERSION,
+                hello.protocol_version,
+            )),
+            Ok(WorkerResponse::Error(e)) => Err(error::Error::RemoteWorker(e.message)),
+            // Workers that predate the handshake can't understand it, and hang
+            // up on us.
+            Ok(_) | Err(_) => Err(error::Error::RemoteProtocolMismatch(
+                WORKER_PROTOCOL_VERSION,
+                UNVERSIONED_PROTOCOL_VERSION,
+            )),
+        }
+    }
+}
+
+/// An `IsInteresting` predicate that asks a remote worker to judge candidates
+/// with its predicate of the given name.
+#[derive(Clone, Debug)]
+pub(crate) struct Predicate {
+    name: String,
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl Predicate {
+    /// Construct a predicate that judges candidates on the remote worker at
+    /// the other end of the given connection.
+    pub fn new(name: String, stream: net::TcpStream) -> error::Result<Predicate> {
+        let writer = stream.try_clone()?;
+        Ok(Predicate {
+            name: name,
+            connection: Arc::new(Mutex::new(Connection {
+                reader: io::BufReader::new(stream),
+                writer: writer,
+                greeted: false,
+            })),
+        })
+    }
+}
+
+impl IsInteresting for Predicate {
+    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
+        if candidate.is_dir() {
+            return Err(error::Error::RemoteWorker(
+                "remote workers can only judge single file test cases".into(),
+            ));
+        }
+
+        let mut contents = vec![];
+        fs::File::open(candidate)?.read_to_end(&mut contents)?;
+
+        let mut connection = self.connection.lock().unwrap();
+        connection.greet()?;
+
+        let request = WorkerRequest::Judge(JudgeRequest {
+            predicate: self.name.clone(),
+            file_name: candidate
+                .file_name()
+                .map_or(String::new(), |n| n.to_string_lossy().into_owned()),
+            len: contents.len() as u64,
+        });
+        match connection.request(&request, &contents)? {
+            WorkerResponse::Judged(judged) => Ok(judged.interesting),
+            WorkerResponse::TimedOut(timed_out) => Err(error::Error::PredicateTimedOut(
+                time::Duration::from_millis(timed_out.millis),
+                None,
+            )),
+            WorkerResponse::Error(e) => Err(error::Error::RemoteWorker(e.message)),
+            WorkerResponse::Hello(_) => Err(error::Error::RemoteWorker(
+                "remote worker said hello instead of judging the candidate".into(),
+            )),
+        }
+    }
+
+    fn clone(&self) -> Box<IsInteresting>
+    where
+        Self: 'static,
+    {
+        Box::new(Clone::clone(self)) as _
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use error;
+    use interesting;
+    use std::fs;
+    use std::io::Write;
+    use std::net;
+    use std::thread;
+    use tempdir::TempDir;
+    use traits::IsInteresting;
+
+    #[test]
+    fn loopback() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let mut worker = RemoteWorker::new();
+            worker.add_predicate("non-empty", interesting::NonEmpty);
+            worker.serve(net::TcpStream::connect(addr).unwrap())
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        let non_empty = Predicate::new("non-empty".into(), stream.try_clone().unwrap()).unwrap();
+        let unknown = Predicate::new("unknown".into(), stream).unwrap();
+
+        let dir = TempDir::new("remote_loopback").unwrap();
+        let empty = dir.path().join("empty");
+        fs::File::create(&empty).unwrap();
+        let full = dir.path().join("full");
+        writeln!(&mut fs::File::create(&full).unwrap(), "la la la").unwrap();
+
+        assert!(non_empty.is_interesting(&full).unwrap());
+        assert!(!non_empty.is_interesting(&empty).unwrap());
+        match unknown.is_interesting(&full) {
+            Err(error::Error::RemoteWorker(_)) => {}
+            otherwise => panic!("expected a remote worker error, found {:?}", otherwise),
+        }
+
+        // Closing the connection stops the remote worker.
+        drop(non_empty);
+        drop(unknown);
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn candidate_file_name_is_sent() {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let mut worker = RemoteWorker::new();
+            worker.add_predicate(
+                "is-c",
+                interesting::Command::new(vec!["sh", "-c", "test \"${1%.c}\" != \"$1\"", "sh"]),
+            );
+            worker.serve(net::TcpStream::connect(addr).unwrap())
+        });
+
+        let (stream, _) = listener.accept().unwrap();
+        let is_c = Predicate::new("is-c".into(), stream).unwrap();
+
+        let dir = TempDir::new("remote_candidate_file_name").unwrap();
+        let c = dir.path().join("test.c");
+        fs::File::create(&c).unwrap();
+        let txt = dir.path().join("test.txt");
+        fs::File::create(&txt).unwrap();
+
+        assert!(is_c.is_interesting(&c).unwrap());
+        assert!(!is_c.is_interesting(&txt).unwrap());
+
+        drop(is_c);
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn candidate_file_name_stays_in_its_directory() {
+        assert_eq!(candidate_file_name("test.c"), "test.c");
+        assert_eq!(candidate_file_name("../test.c"), DEFAULT_CANDIDATE_FILE_NAME);
+        assert_eq!(candidate_file_name("/tmp/test.c"), DEFAULT_CANDIDATE_FILE_NAME);
+        assert_eq!(candidate_file_name(".."), DEFAULT_CANDIDATE_FILE_NAME);
+        assert_eq!(candidate_file_name(""), DEFAULT_CANDIDATE_FILE_NAME);
+    }
+
+    #[test]
+    fn protocol_mismatch() {
+        use std::io::{BufRead, BufReader};
+
+        // A coordinator speaking another version is turned away by the worker.
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            RemoteWorker::new().serve(net::TcpStream::connect(addr).unwrap())
+        });
+        let (mut stream, _) = listener.accept().unwrap();
+        writeln!(&mut stream, r#"{{"Hello":{{"protocol_version":999}}}}"#).unwrap();
+        match server.join().unwrap() {
+            Err(error::Error::RemoteProtocolMismatch(ours, 999)) => {
+                assert_eq!(ours, WORKER_PROTOCOL_VERSION)
+            }
+            otherwise => panic!("expected a protocol mismatch, found {:?}", otherwise),
+        }
+
+        // And a worker speaking another version is turned away by the
+        // coordinator.
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let stream = net::TcpStream::connect(addr).unwrap();
+            let mut line = String::new();
+            BufReader::new(stream.try_clone().unwrap())
+                .read_line(&mut line)
+                .unwrap();
+            writeln!(&stream, r#"{{"Hello":{{"protocol_version":999}}}}"#).unwrap();
+        });
+        let (stream, _) = listener.accept().unwrap();
+        let predicate = Predicate::new("non-empty".into(), stream).unwrap();
+
+        let dir = TempDir::new("remote_protocol_mismatch").unwrap();
+        let candidate = dir.path().join("candidate");
+        fs::File::create(&candidate).unwrap();
+        match predicate.is_interesting(&candidate) {
+            Err(error::Error::RemoteProtocolMismatch(_, 999)) => {}
+            otherwise => panic!("expected a protocol mismatch, found {:?}", otherwise),
+        }
+        server.join().unwrap();
+    }
+}
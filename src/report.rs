@@ -0,0 +1,67 @@
+//! A summary of a completed reduction, returned from `Options::run`.
+
+use std::collections::BTreeMap;
+use std::path;
+use std::time;
+
+/// Why a reduction stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Termination {
+    /// No reducer could generate an interesting candidate that was smaller than
+    /// the smallest interesting test case.
+    Fixpoint,
+
+    /// We received SIGINT and stopped early.
+    Sigint,
+}
+
+/// Counters for the candidates generated by a single reducer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReducerStats {
+    /// The number of candidates this reducer generated.
+    pub generated: u64,
+
+    /// The number of this reducer's candidates that were tested with the
+    /// is-interesting predicate.
+    pub tested: u64,
+
+    /// The number of this reducer's candidates that were judged interesting,
+    /// whether or not they became the smallest interesting test case.
+    pub interesting: u64,
+
+    /// The total number of bytes removed by this reducer's candidates that
+    /// became the smallest interesting test case.
+    pub bytes_removed: u64,
+
+    /// The most of this reducer's candidates that were waiting in the queue to
+    /// be tested at once.
+    pub peak_queued: u64,
+
+    /// The total time this reducer's candidates spent waiting in the queue.
+    /// Divided by the reduction's `wall_time`, this is the average number of
+    /// its candidates in the queue.
+    pub queued_time: time::Duration,
+}
+
+/// A summary of a completed reduction.
+#[derive(Clone, Debug)]
+pub struct ReductionReport {
+    /// The size (in bytes) of the original test case.
+    pub original_size: u64,
+
+    /// The size (in bytes) of the final, smallest interesting test case.
+    pub final_size: u64,
+
+    /// The path holding the final, smallest interesting test case's contents.
+    pub final_path: path::PathBuf,
+
+    /// Why the reduction stopped.
+    pub termination: Termination,
+
+    /// How long the reduction ran for.
+    pub wall_time: time::Duration,
+
+    /// Counters for each reducer's candidates, keyed by the reducer's name
+    /// (the candidates' provenance).
+    pub reducers: BTreeMap<String, ReducerStats>,
+}
@@ -0,0 +1,384 @@
+//! Running the is-interesting predicate in a private, per-worker directory.
+//!
+//! Without a sandbox, the predicate runs in the candidate's temporary
+//! directory, with nothing else in it. That doesn't work for predicates that
+//! need side files next to the test case (headers, a `Makefile`, some
+//! configuration), and predicates that write to fixed output file names
+//! clobber each other when multiple workers run them at the same time.
+//!
+//! With a sandbox, each worker gets its own directory, into which the auxiliary
+//! files are copied (or symlinked) once. Each candidate is copied into that
+//! directory under the initial test case's file name before the predicate runs
+//! on it, and the predicate runs with that directory as its working
+//! directory. The predicate's processes are also given these environment
+//! variables:
+//!
+//! * `PREDUCE_WORKER_ID`: the id of the worker running the predicate.
+//!
+//! * `PREDUCE_SCRATCH_DIR`: the worker's directory, which the predicate may
+//!   freely write to.
+//!
+//! * `PREDUCE_ORIGINAL`: the path to a copy of the initial test case, as it
+//!   was when the reduction started.
+
+use error;
+use interesting;
+use std::ffi;
+use std::path;
+use std::sync::Arc;
+use tempdir;
+use test_case;
+use traits::IsInteresting;
+
+/// How the auxiliary files are placed into each worker's directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuxFileMode {
+    /// Copy the auxiliary files, so that the predicate may modify them.
+    Copy,
+
+    /// Symlink to the auxiliary files, which is cheaper for large files, but
+    /// shares them between workers.
+    Symlink,
+}
+
+/// The configuration of the predicate sandbox.
+///
+/// ```
+/// # fn _ignore() -> preduce::error::Result<()> {
+/// let predicate = preduce::interesting::Script::new("is_interesting.sh")?;
+/// let reducer = preduce::reducers::Script::new("generate_candidates.sh")?;
+/// let test_case = "path/to/test-case.c";
+///
+/// preduce::Options::new(predicate, vec![Box::new(reducer)], test_case)
+///     .sandbox(
+///         preduce::sandbox::Sandbox::new()
+///             .aux_file("path/to/header.h")
+///             .aux_file("path/to/Makefile"),
+///     )
+///     .run()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sandbox {
+    aux_files: Vec<path::PathBuf>,
+    mode: AuxFileMode,
+}
+
+impl Default for Sandbox {
+    fn default() -> Sandbox {
+        Sandbox::new()
+    }
+}
+
+impl Sandbox {
+    /// Construct a new sandbox configuration, without any auxiliary files.
+    pub fn new() -> Sandbox {
+        Sandbox {
+            aux_files: vec![],
+            mode: AuxFileMode::Copy,
+        }
+    }
+
+    /// Place the given file or directory into each worker's directory, under
+    /// its own file name.
+    pub fn aux_file<P>(mut self, path: P) -> Sandbox
+    where
+        P: Into<path::PathBuf>,
+    {
+        self.aux_files.push(path.into());
+        self
+    }
+
+    /// Set how the auxiliary files are placed into each worker's directory.
+    /// Defaults to `AuxFileMode::Copy`.
+    pub fn aux_file_mode(mut self, mode: AuxFileMode) -> Sandbox {
+        self.mode = mode;
+        self
+    }
+
+    /// Get the auxiliary files.
+    pub fn aux_files(&self) -> &[path::PathBuf] {
+        &self.aux_files[..]
+    }
+
+    /// Combine the given predicate fingerprint with the auxiliary files'
+    /// names and contents, since changing them can change the predicate's
+    /// results.
+    pub(crate) fn fingerprint(&self, predicate: &str) -> Option<String> {
+        let mut aux = String::new();
+        for file in &self.aux_files {
+            let hash = test_case::hash_test_case(file).ok()?;
+            aux.push_str(&file.file_name()?.to_string_lossy());
+            aux.push(':');
+            aux.push_str(&test_case::hash_to_hex(&hash));
+            aux.push(',');
+        }
+        Some(format!("sandbox({},{})", predicate, aux))
+    }
+}
+
+/// The state shared between every worker's sandbox for a single reduction.
+#[derive(Debug)]
+pub(crate) struct Sandboxes {
+    config: Sandbox,
+    candidate_name: ffi::OsString,
+    original: test_case::TempFile,
+}
+
+impl Sandboxes {
+    /// Validate the sandbox configuration, and take a copy of the initial test
+    /// case at the given path.
+    pub fn new(config: &Sandbox, test_case: &path::Path) -> error::Result<Sandboxes> {
+        let candidate_name = test_case
+            .file_name()
+            .ok_or_else(|| {
+                error::Error::Sandbox(format!("'{}' has no file name", test_case.display()))
+            })?
+            .to_os_string();
+
+        let mut aux_files = Vec::with_capacity(config.aux_files.len());
+        for file in &config.aux_files {
+            if !file.exists() {
+                return Err(error::Error::DoesNotExist(file.clone()));
+            }
+            let name = file.file_name().ok_or_else(|| {
+                error::Error::Sandbox(format!("'{}' has no file name", file.display()))
+            })?;
+            if name == candidate_name {
+                return Err(error::Error::Sandbox(format!(
+                    "the auxiliary file '{}' has the same name as the test case",
+                    file.display()
+                )));
+            }
+            if config
+                .aux_files
+                .iter()
+                .filter(|f| f.file_name() == Some(name))
+                .count() > 1
+            {
+                return Err(error::Error::Sandbox(format!(
+                    "there is more than one auxiliary file named '{}'",
+                    name.to_string_lossy()
+                )));
+            }
+            aux_files.push(file.canonicalize()?);
+        }
+
+        let dir = Arc::new(tempdir::TempDir::new("preduce-original")?);
+        let original = test_case::TempFile::new(dir, &candidate_name)?;
+        test_case::copy_test_case(test_case, original.path())?;
+
+        Ok(Sandboxes {
+            config: Sandbox {
+                aux_files: aux_files,
+                mode: config.mode,
+            },
+            candidate_name: candidate_name,
+            original: original,
+        })
+    }
+
+    /// Create a new directory for the worker with the given id, and wrap the
+    /// given predicate so that it runs there.
+    pub fn sandbox(
+        &self,
+        worker_id: usize,
+        mut predicate: Box<IsInteresting>,
+    ) -> error::Result<Box<IsInteresting>> {
+        let dir = tempdir::TempDir::new(&format!("preduce-worker-{}", worker_id))?;
+        let scratch = dir.path().canonicalize()?;
+
+        for file in &self.config.aux_files {
+            let dest = scratch.join(file.file_name().expect("validated in `new`"));
+            match self.config.mode {
+                AuxFileMode::Copy => test_case::copy_test_case(file, dest)?,
+                AuxFileMode::Symlink => symlink(file, &dest)?,
+            }
+        }
+
+        predicate.set_env(&[
+            ("PREDUCE_WORKER_ID".into(), worker_id.to_string().into()),
+            ("PREDUCE_SCRATCH_DIR".into(), scratch.clone().into()),
+            (
+                "PREDUCE_ORIGINAL".into(),
+                self.original.path().to_path_buf().into(),
+            ),
+        ]);
+
+        Ok(Box::new(Sandboxed {
+            _dir: Arc::new(dir),
+            candidate: scratch.join(&self.candidate_name),
+            _original: self.original.clone(),
+            predicate: predicate,
+        }))
+    }
+}
+
+#[cfg(unix)]
+fn symlink(from: &path::Path, to: &path::Path) -> error::Result<()> {
+    ::std::os::unix::fs::symlink(from, to)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn symlink(from: &path::Path, to: &path::Path) -> error::Result<()> {
+    test_case::copy_test_case(from, to)
+}
+
+/// A predicate that copies each candidate into its worker's directory before
+/// judging it there.
+struct Sandboxed {
+    // Keeps the worker's directory alive for as long as the predicate is.
+    _dir: Arc<tempdir::TempDir>,
+    candidate: path::PathBuf,
+    // Keeps the copy of the initial test case alive for as long as the
+    // predicate is.
+    _original: test_case::TempFile,
+    predicate: Box<IsInteresting>,
+}
+
+impl IsInteresting for Sandboxed {
+    fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool> {
+        test_case::copy_test_case(candidate, &self.candidate)?;
+        self.predicate.is_interesting(&self.candidate)
+    }
+
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        test_case::copy_test_case(candidate, &self.candidate)?;
+        self.predicate.is_interesting_once(&self.candidate)
+    }
+
+    fn judge(
+        &self,
+        candidate: &path::Path,
+        capture_output: bool,
+    ) -> error::Result<interesting::Judgement> {
+        test_case::copy_test_case(candidate, &self.candidate)?;
+        self.predicate.judge(&self.candidate, capture_output)
+    }
+
+    fn clone(&self) -> Box<IsInteresting> {
+        Box::new(Sandboxed {
+            _dir: self._dir.clone(),
+            candidate: self.candidate.clone(),
+            _original: self._original.clone(),
+            predicate: self.predicate.clone(),
+        })
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        self.predicate.fingerprint()
+    }
+
+    fn set_env(&mut self, vars: &[(String, ffi::OsString)]) {
+        self.predicate.set_env(vars)
+    }
+
+    fn set_resource_limits(&mut self, limits: &interesting::ResourceLimits) {
+        self.predicate.set_resource_limits(limits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::{Read, Write};
+    use test_case;
+
+    fn write_file(path: &path::Path, contents: &str) {
+        let mut file = fs::File::create(path).unwrap();
+        write!(&mut file, "{}", contents).unwrap();
+    }
+
+    fn read_file(path: &path::Path) -> String {
+        let mut contents = String::new();
+        fs::File::open(path)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn sandboxed_predicate_runs_in_worker_dir() {
+        let dir = tempdir::TempDir::new("sandboxed_predicate_runs_in_worker_dir").unwrap();
+        let test_case = dir.path().join("test.c");
+        let header = dir.path().join("header.h");
+        write_file(&test_case, "original");
+        write_file(&header, "aux");
+
+        let sandboxes = Sandboxes::new(&Sandbox::new().aux_file(&header), &test_case).unwrap();
+
+        // The predicate sees the aux file and the candidate under the
+        // test case's name, in the scratch directory it was told about.
+        let predicate = ::interesting::Command::new(vec![
+            "sh",
+            "-c",
+            "test \"$1\" = test.c && test \"$(cat header.h)\" = aux && \
+             test \"$(pwd -P)\" = \"$PREDUCE_SCRATCH_DIR\" && \
+             test \"$PREDUCE_WORKER_ID\" = 7 && \
+             test \"$(cat \"$PREDUCE_ORIGINAL\")\" = original && \
+             grep -q candidate \"$1\"",
+            "sh",
+            "{}",
+        ]);
+        let predicate = sandboxes.sandbox(7, Box::new(predicate)).unwrap();
+
+        let candidate = test_case::TempFile::anonymous().unwrap();
+        write_file(candidate.path(), "candidate");
+        assert!(predicate.is_interesting(candidate.path()).unwrap());
+
+        write_file(candidate.path(), "something else");
+        assert!(!predicate.is_interesting(candidate.path()).unwrap());
+
+        // Changing the test case doesn't change the original.
+        write_file(&test_case, "reduced");
+        let original = sandboxes.original.path();
+        assert_eq!(read_file(original), "original");
+    }
+
+    #[test]
+    fn workers_get_separate_dirs() {
+        let dir = tempdir::TempDir::new("workers_get_separate_dirs").unwrap();
+        let test_case = dir.path().join("test.c");
+        write_file(&test_case, "original");
+
+        let sandboxes = Sandboxes::new(&Sandbox::new(), &test_case).unwrap();
+        let candidate = test_case::TempFile::anonymous().unwrap();
+        write_file(candidate.path(), "candidate");
+
+        // Each worker's predicate records where its scratch directory is.
+        let record = format!(
+            "pwd -P > out && cp out {}/$PREDUCE_WORKER_ID",
+            dir.path().display()
+        );
+        for worker_id in 0..2 {
+            let predicate = ::interesting::Command::new(vec!["sh", "-c", &record]);
+            let predicate = sandboxes.sandbox(worker_id, Box::new(predicate)).unwrap();
+            assert!(predicate.is_interesting(candidate.path()).unwrap());
+        }
+
+        let first = read_file(&dir.path().join("0"));
+        let second = read_file(&dir.path().join("1"));
+        assert!(first != second);
+    }
+
+    #[test]
+    fn invalid_aux_files() {
+        let dir = tempdir::TempDir::new("invalid_aux_files").unwrap();
+        let test_case = dir.path().join("test.c");
+        write_file(&test_case, "original");
+
+        match Sandboxes::new(&Sandbox::new().aux_file(dir.path().join("nope")), &test_case) {
+            Err(error::Error::DoesNotExist(_)) => {}
+            otherwise => panic!("Expected Error::DoesNotExist, found {:?}", otherwise),
+        }
+
+        match Sandboxes::new(&Sandbox::new().aux_file(&test_case), &test_case) {
+            Err(error::Error::Sandbox(_)) => {}
+            otherwise => panic!("Expected Error::Sandbox, found {:?}", otherwise),
+        }
+    }
+}
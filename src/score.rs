@@ -70,3 +70,20 @@ impl ops::Add for Score {
         }
     }
 }
+
+impl ops::Mul<f64> for Score {
+    type Output = Score;
+
+    /// Scale this score by the given factor. A score that says to skip the
+    /// candidate still says to skip it.
+    ///
+    /// ### Panics
+    ///
+    /// Panics if the scaled score is NaN or infinite.
+    fn mul(self, rhs: f64) -> Score {
+        match self {
+            SkipIt => SkipIt,
+            TryIt(x) => Score::new(x * rhs),
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! A live status view of a reduction in progress, drawn on stdout.
+//!
+//! When stdout is a terminal, the status view is a small dashboard that is
+//! redrawn in place. Otherwise, a one-line summary is printed periodically, so
+//! that logs of non-interactive runs don't fill up with redraws.
+
+use event::Event;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time;
+
+/// How often the dashboard is redrawn when stdout is a terminal.
+const REDRAW_INTERVAL_MILLIS: u64 = 100;
+
+/// How often a one-line summary is printed when stdout is not a terminal.
+const SUMMARY_INTERVAL_SECS: u64 = 10;
+
+/// Whether a worker is currently judging a candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WorkerState {
+    Idle,
+    Busy,
+}
+
+/// The status view's knowledge of the reduction, built up from events.
+#[derive(Debug)]
+pub struct Status {
+    is_tty: bool,
+    start: time::Instant,
+    last_drawn: Option<time::Instant>,
+    drawn_lines: usize,
+
+    smallest_size: Option<u64>,
+    original_size: Option<u64>,
+    last_progress_by: Option<String>,
+    phase: Option<String>,
+    judged: u64,
+    workers: BTreeMap<usize, WorkerState>,
+    queued_candidates: usize,
+    reducers: usize,
+    exhausted_reducers: Vec<String>,
+}
+
+impl Status {
+    /// Construct a new status view, which draws a dashboard if stdout is a
+    /// terminal, and periodic one-line summaries otherwise.
+    pub fn new() -> Status {
+        Status::with_tty(imp::stdout_is_tty())
+    }
+
+    fn with_tty(is_tty: bool) -> Status {
+        Status {
+            is_tty: is_tty,
+            start: time::Instant::now(),
+            last_drawn: None,
+            drawn_lines: 0,
+            smallest_size: None,
+            original_size: None,
+            last_progress_by: None,
+            phase: None,
+            judged: 0,
+            workers: BTreeMap::new(),
+            queued_candidates: 0,
+            reducers: 0,
+            exhausted_reducers: vec![],
+        }
+    }
+
+    /// Update the status with the given event, and redraw it if it is due.
+    pub fn observe(&mut self, event: &Event) {
+        match *event {
+            Event::SpawnedWorker { worker } => {
+                self.workers.insert(worker, WorkerState::Idle);
+            }
+            Event::ShutdownWorker { worker } |
+            Event::WorkerPanicked { worker, .. } |
+            Event::WorkerErrored { worker, .. } => {
+                self.workers.remove(&worker);
+            }
+            Event::StartJudgingInteresting { worker, .. } => {
+                self.workers.insert(worker, WorkerState::Busy);
+            }
+            Event::JudgedInteresting { worker, .. } |
+            Event::JudgedNotInteresting { worker, .. } |
+            Event::JudgedTimedOut { worker, .. } |
+            Event::JudgedLimitExceeded { worker, .. } |
+            Event::CachedResult { worker, .. } => {
+                self.judged += 1;
+                self.workers.insert(worker, WorkerState::Idle);
+            }
+            Event::NewSmallest {
+                ref interesting,
+                original_size,
+            } => {
+                self.smallest_size = Some(interesting.size);
+                self.original_size = Some(original_size);
+                self.last_progress_by = Some(interesting.provenance.clone());
+            }
+            Event::StartingPhase { ref name } => {
+                self.phase = Some(name.clone());
+            }
+            Event::Progress {
+                smallest_size,
+                original_size,
+                queued_candidates,
+                reducers,
+                ref exhausted_reducers,
+                ..
+            } => {
+                self.smallest_size = Some(smallest_size);
+                self.original_size = Some(original_size);
+                self.queued_candidates = queued_candidates;
+                self.reducers = reducers;
+                self.exhausted_reducers = exhausted_reducers.clone();
+            }
+            _ => return,
+        }
+
+        let interval = if self.is_tty {
+            time::Duration::from_millis(REDRAW_INTERVAL_MILLIS)
+        } else {
+            time::Duration::from_secs(SUMMARY_INTERVAL_SECS)
+        };
+        if self.last_drawn.map_or(true, |t| t.elapsed() >= interval) {
+            self.draw();
+        }
+    }
+
+    /// Erase the dashboard, if one is drawn, so that other output can be
+    /// printed. The dashboard is drawn again with the next redraw.
+    pub fn clear(&mut self) {
+        if self.drawn_lines > 0 {
+            let stdout = io::stdout();
+            let mut stdout = stdout.lock();
+            let _ = write!(&mut stdout, "\x1b[{}A\x1b[J", self.drawn_lines);
+            let _ = stdout.flush();
+            self.drawn_lines = 0;
+        }
+    }
+
+    /// Draw the status now.
+    pub fn draw(&mut self) {
+        self.last_drawn = Some(time::Instant::now());
+
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        if self.is_tty {
+            let lines = self.dashboard();
+            if self.drawn_lines > 0 {
+                let _ = write!(&mut stdout, "\x1b[{}A\x1b[J", self.drawn_lines);
+            }
+            for line in &lines {
+                let _ = writeln!(&mut stdout, "{}", line);
+            }
+            self.drawn_lines = lines.len();
+        } else {
+            let _ = writeln!(&mut stdout, "{}", self.summary());
+        }
+        let _ = stdout.flush();
+    }
+
+    fn elapsed_secs(&self) -> f64 {
+        let elapsed = self.start.elapsed();
+        elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1e9
+    }
+
+    fn elapsed_hms(&self) -> String {
+        let secs = self.start.elapsed().as_secs();
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+
+    fn candidates_per_sec(&self) -> f64 {
+        let secs = self.elapsed_secs();
+        if secs > 0.0 {
+            self.judged as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    fn size(&self) -> String {
+        match (self.smallest_size, self.original_size) {
+            (Some(smallest), Some(original)) => {
+                let percent = if original == 0 {
+                    100.0
+                } else {
+                    ((original - smallest) as f64) / (original as f64) * 100.0
+                };
+                format!("{} bytes ({:.2}% reduced)", smallest, percent)
+            }
+            _ => "? bytes".into(),
+        }
+    }
+
+    fn busy_workers(&self) -> usize {
+        self.workers
+            .values()
+            .filter(|&&s| s == WorkerState::Busy)
+            .count()
+    }
+
+    fn dashboard(&self) -> Vec<String> {
+        let workers: String = self.workers
+            .values()
+            .map(|s| match *s {
+                WorkerState::Busy => '#',
+                WorkerState::Idle => '.',
+            })
+            .collect();
+        vec![
+            format!(
+                "preduce: {}, {:.1} candidates/s, {} elapsed",
+                self.size(),
+                self.candidates_per_sec(),
+                self.elapsed_hms()
+            ),
+            format!(
+                "  workers: [{}] {} busy, {} idle",
+                workers,
+                self.busy_workers(),
+                self.workers.len() - self.busy_workers()
+            ),
+            format!("  queued candidates: {}", self.queued_candidates),
+            format!(
+                "  exhausted reducers ({}/{}): {}",
+                self.exhausted_reducers.len(),
+                self.reducers,
+                if self.exhausted_reducers.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.exhausted_reducers.join(", ")
+                }
+            ),
+            format!(
+                "  last progress by: {}",
+                self.last_progress_by.as_ref().map_or("none", |p| &p[..])
+            ),
+            format!("  phase: {}", self.phase.as_ref().map_or("none", |p| &p[..])),
+        ]
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "[{}] {}, {:.1} candidates/s, {}/{} workers busy, {} queued, {}/{} reducers \
+             exhausted, last progress by {}",
+            self.elapsed_hms(),
+            self.size(),
+            self.candidates_per_sec(),
+            self.busy_workers(),
+            self.workers.len(),
+            self.queued_candidates,
+            self.exhausted_reducers.len(),
+            self.reducers,
+            self.last_progress_by.as_ref().map_or("none", |p| &p[..])
+        )
+    }
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+mod imp {
+    extern crate libc;
+
+    pub fn stdout_is_tty() -> bool {
+        unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    pub fn stdout_is_tty() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use event::{Event, TestCaseInfo};
+    use std::path;
+
+    #[test]
+    fn observe() {
+        let mut status = Status::with_tty(false);
+        // Don't print anything during the test.
+        status.last_drawn = Some(time::Instant::now());
+
+        status.observe(&Event::SpawnedWorker { worker: 0 });
+        status.observe(&Event::SpawnedWorker { worker: 1 });
+        status.observe(&Event::StartJudgingInteresting {
+            worker: 1,
+            candidate: TestCaseInfo {
+                path: path::PathBuf::from("candidate"),
+                size: 50,
+                delta: 50,
+                provenance: "chunks".into(),
+            },
+        });
+        status.observe(&Event::NewSmallest {
+            interesting: TestCaseInfo {
+                path: path::PathBuf::from("smallest"),
+                size: 75,
+                delta: 25,
+                provenance: "lines".into(),
+            },
+            original_size: 100,
+        });
+        status.observe(&Event::Progress {
+            smallest_size: 75,
+            original_size: 100,
+            queued_candidates: 3,
+            workers: 2,
+            idle_workers: 1,
+            reducers: 2,
+            exhausted_reducers: vec!["lines".into()],
+        });
+
+        assert_eq!(status.busy_workers(), 1);
+        let dashboard = status.dashboard();
+        assert!(dashboard[0].contains("75 bytes (25.00% reduced)"));
+        assert!(dashboard[1].contains("[.#] 1 busy, 1 idle"));
+        assert!(dashboard[2].contains("queued candidates: 3"));
+        assert!(dashboard[3].contains("(1/2): lines"));
+        assert!(dashboard[4].contains("last progress by: lines"));
+        assert!(status.summary().contains("1/2 workers busy, 3 queued"));
+    }
+}
@@ -0,0 +1,355 @@
+//! Running subprocesses with a wall-clock timeout and resource limits.
+//!
+//! Each subprocess is spawned as the leader of its own process group, so that
+//! when it times out we can kill it along with everything it spawned (for
+//! example, the compiler that an is-interesting script invokes), rather than
+//! leaving orphans running in the background.
+
+use interesting::{ResourceLimit, ResourceLimits};
+use std::cmp;
+use std::io;
+use std::process;
+use std::thread;
+use std::time;
+
+/// The longest we will sleep between polls of a running subprocess.
+const MAX_POLL_INTERVAL_MILLIS: u64 = 50;
+
+/// Spawn the given command as the leader of a new process group, with the
+/// given resource limits.
+pub fn spawn(cmd: &mut process::Command, limits: &ResourceLimits) -> io::Result<process::Child> {
+    imp::before_exec(cmd, *limits);
+    cmd.spawn()
+}
+
+/// Once a process that was set an address space limit has used at least this
+/// fraction of it as resident memory, an abort or segfault is blamed on the
+/// limit. Resident memory understates the address space in use (mapped but
+/// untouched pages don't count), so demanding it reach the limit itself would
+/// never blame anything.
+const ADDRESS_SPACE_RSS_FRACTION: f64 = 0.5;
+
+/// The resources that an exited process used, including those used by any of
+/// its children that it waited on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The user and system CPU time used.
+    pub cpu_time: time::Duration,
+
+    /// The maximum resident set size, in bytes.
+    pub max_rss: u64,
+}
+
+/// Guess whether the process that exited with the given status, having used
+/// the given resources, was killed for exceeding one of the given limits.
+///
+/// `SIGXCPU` and `SIGXFSZ` are only ever sent for exceeding the CPU time and
+/// file size limits, so they are blamed on them outright. Everything else that
+/// a limit can cause is also caused by plain bugs, so it is only blamed on a
+/// limit when the process's usage shows that it got there: a `SIGKILL` when the
+/// process used up its CPU time, and an abort or segfault when its memory use
+/// came close to the address space limit.
+///
+/// A process that exits with `128 + N` is treated as if it were killed by
+/// signal `N`, since that is how shells report that the last command they ran
+/// was killed. Exceeding the process limit makes `fork` fail, which can't be
+/// told apart from any other failure, so it is never reported.
+pub fn exceeded_limit(
+    status: process::ExitStatus,
+    usage: &Usage,
+    limits: &ResourceLimits,
+) -> Option<ResourceLimit> {
+    let signal = imp::exit_signal(status).or_else(|| match status.code() {
+        Some(code) if code > 128 => Some(code - 128),
+        _ => None,
+    })?;
+
+    if let Some(cpu_seconds) = limits.cpu_seconds {
+        if signal == imp::SIGXCPU ||
+            (signal == imp::SIGKILL && usage.cpu_time >= time::Duration::from_secs(cpu_seconds))
+        {
+            return Some(ResourceLimit::CpuTime);
+        }
+    }
+    if limits.file_size.is_some() && signal == imp::SIGXFSZ {
+        return Some(ResourceLimit::FileSize);
+    }
+    if let Some(address_space) = limits.address_space {
+        if (signal == imp::SIGABRT || signal == imp::SIGSEGV) &&
+            usage.max_rss as f64 >= address_space as f64 * ADDRESS_SPACE_RSS_FRACTION
+        {
+            return Some(ResourceLimit::AddressSpace);
+        }
+    }
+    None
+}
+
+/// Wait for the given child to exit, for at most `timeout` if one is given,
+/// returning its exit status and resource usage.
+///
+/// If the child has not exited by the time the timeout has elapsed, then the
+/// child's whole process group is killed and `None` is returned.
+pub fn wait_timeout(
+    child: &mut process::Child,
+    timeout: Option<time::Duration>,
+) -> io::Result<Option<(process::ExitStatus, Usage)>> {
+    let timeout = match timeout {
+        None => return imp::wait(child, true),
+        Some(t) => t,
+    };
+
+    let start = time::Instant::now();
+    let mut interval = time::Duration::from_millis(1);
+    loop {
+        if let Some(exit) = imp::wait(child, false)? {
+            return Ok(Some(exit));
+        }
+
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            imp::kill_process_group(child);
+            child.wait()?;
+            return Ok(None);
+        }
+
+        // Back off exponentially, so that quick subprocesses are noticed
+        // quickly, and slow ones don't have us spinning.
+        thread::sleep(cmp::min(interval, timeout - elapsed));
+        interval = cmp::min(
+            interval * 2,
+            time::Duration::from_millis(MAX_POLL_INTERVAL_MILLIS),
+        );
+    }
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+mod imp {
+    extern crate libc;
+
+    use super::Usage;
+    use interesting::ResourceLimits;
+    use std::io;
+    use std::mem;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process;
+    use std::time;
+
+    pub const SIGABRT: i32 = libc::SIGABRT;
+    pub const SIGKILL: i32 = libc::SIGKILL;
+    pub const SIGSEGV: i32 = libc::SIGSEGV;
+    pub const SIGXCPU: i32 = libc::SIGXCPU;
+    pub const SIGXFSZ: i32 = libc::SIGXFSZ;
+
+    pub fn before_exec(cmd: &mut process::Command, limits: ResourceLimits) {
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                // Give the process a second past its CPU time limit to handle
+                // `SIGXCPU` before it is killed outright.
+                set_limit(libc::RLIMIT_AS, limits.address_space, 0)?;
+                set_limit(libc::RLIMIT_CPU, limits.cpu_seconds, 1)?;
+                set_limit(libc::RLIMIT_FSIZE, limits.file_size, 0)?;
+                set_limit(libc::RLIMIT_NPROC, limits.processes, 0)?;
+                Ok(())
+            });
+        }
+    }
+
+    /// Only async-signal-safe functions may be called here, since this runs in
+    /// the forked child.
+    fn set_limit(resource: libc::c_int, limit: Option<u64>, grace: u64) -> io::Result<()> {
+        let limit = match limit {
+            None => return Ok(()),
+            Some(limit) => limit,
+        };
+        let rlimit = libc::rlimit {
+            rlim_cur: limit as libc::rlim_t,
+            rlim_max: limit.saturating_add(grace) as libc::rlim_t,
+        };
+        if unsafe { libc::setrlimit(resource, &rlimit) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub fn exit_signal(status: process::ExitStatus) -> Option<i32> {
+        status.signal()
+    }
+
+    /// Reap the child if it has exited, or block until it does if `block` is
+    /// set. We use `wait4` rather than `Child::wait` to get the child's
+    /// resource usage along with its status; `Child` never learns that it was
+    /// reaped, so the child must not be waited on again afterwards.
+    pub fn wait(
+        child: &mut process::Child,
+        block: bool,
+    ) -> io::Result<Option<(process::ExitStatus, Usage)>> {
+        let pid = child.id() as libc::pid_t;
+        let options = if block { 0 } else { libc::WNOHANG };
+        loop {
+            let mut status = 0;
+            let mut rusage: libc::rusage = unsafe { mem::zeroed() };
+            match unsafe { libc::wait4(pid, &mut status, options, &mut rusage) } {
+                0 => return Ok(None),
+                -1 => {
+                    let err = io::Error::last_os_error();
+                    if err.kind() != io::ErrorKind::Interrupted {
+                        return Err(err);
+                    }
+                }
+                _ => {
+                    let usage = Usage {
+                        cpu_time: duration(rusage.ru_utime) + duration(rusage.ru_stime),
+                        max_rss: rusage.ru_maxrss as u64 * MAX_RSS_UNIT,
+                    };
+                    return Ok(Some((process::ExitStatus::from_raw(status), usage)));
+                }
+            }
+        }
+    }
+
+    fn duration(tv: libc::timeval) -> time::Duration {
+        time::Duration::new(tv.tv_sec as u64, tv.tv_usec as u32 * 1000)
+    }
+
+    /// `ru_maxrss` is in bytes on macOS, and in kilobytes everywhere else.
+    #[cfg(target_os = "macos")]
+    const MAX_RSS_UNIT: u64 = 1;
+    #[cfg(not(target_os = "macos"))]
+    const MAX_RSS_UNIT: u64 = 1024;
+
+    pub fn kill_process_group(child: &mut process::Child) {
+        // The child is its process group's leader, so the group id is the same
+        // as the child's pid. Fall back to killing just the child in case the
+        // group has already gone away.
+        let pgid = child.id() as libc::pid_t;
+        if unsafe { libc::kill(-pgid, libc::SIGKILL) } != 0 {
+            let _ = child.kill();
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    use super::Usage;
+    use interesting::ResourceLimits;
+    use std::io;
+    use std::process;
+
+    // Signals don't exist here, so these never match an exit signal.
+    pub const SIGABRT: i32 = -1;
+    pub const SIGKILL: i32 = -1;
+    pub const SIGSEGV: i32 = -1;
+    pub const SIGXCPU: i32 = -1;
+    pub const SIGXFSZ: i32 = -1;
+
+    pub fn before_exec(_cmd: &mut process::Command, _limits: ResourceLimits) {}
+
+    pub fn exit_signal(_status: process::ExitStatus) -> Option<i32> {
+        None
+    }
+
+    // Resource usage isn't available here, but then neither are the limits.
+    pub fn wait(
+        child: &mut process::Child,
+        block: bool,
+    ) -> io::Result<Option<(process::ExitStatus, Usage)>> {
+        let status = if block {
+            Some(child.wait()?)
+        } else {
+            child.try_wait()?
+        };
+        Ok(status.map(|status| (status, Usage::default())))
+    }
+
+    pub fn kill_process_group(child: &mut process::Child) {
+        let _ = child.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+    use std::time;
+
+    #[test]
+    fn exits_before_timeout() {
+        let mut child = spawn(&mut process::Command::new("true"), &Default::default()).unwrap();
+        let exit = wait_timeout(&mut child, Some(time::Duration::from_secs(10))).unwrap();
+        let (status, _) = exit.expect("should not time out");
+        assert!(status.success());
+    }
+
+    #[test]
+    fn times_out_and_kills_process_group() {
+        // The grandchild `sleep` holds the pipe open; if it were not killed
+        // along with the shell, reading stdout to the end would hang.
+        let mut cmd = process::Command::new("sh");
+        cmd.args(&["-c", "sleep 30; echo done"])
+            .stdout(process::Stdio::piped());
+        let mut child = spawn(&mut cmd, &Default::default()).unwrap();
+
+        let start = time::Instant::now();
+        let status = wait_timeout(&mut child, Some(time::Duration::from_millis(100))).unwrap();
+        assert!(status.is_none());
+
+        let output = child.wait_with_output().unwrap();
+        assert!(output.stdout.is_empty());
+        assert!(start.elapsed() < time::Duration::from_secs(10));
+    }
+
+    #[test]
+    fn file_size_limit() {
+        let limits = ResourceLimits {
+            file_size: Some(1024),
+            ..Default::default()
+        };
+        let dir = ::tempdir::TempDir::new("file_size_limit").unwrap();
+        let mut cmd = process::Command::new("sh");
+        cmd.args(&["-c", "head -c 4096 /dev/zero > big"])
+            .current_dir(dir.path());
+        let mut child = spawn(&mut cmd, &limits).unwrap();
+        let (status, usage) = wait_timeout(&mut child, None).unwrap().unwrap();
+        assert!(!status.success());
+        assert_eq!(exceeded_limit(status, &usage, &limits), Some(ResourceLimit::FileSize));
+        assert_eq!(exceeded_limit(status, &usage, &Default::default()), None);
+    }
+
+    #[test]
+    fn cpu_time_limit() {
+        let limits = ResourceLimits {
+            cpu_seconds: Some(1),
+            ..Default::default()
+        };
+        let mut cmd = process::Command::new("sh");
+        cmd.args(&["-c", "while true; do :; done"]);
+        let mut child = spawn(&mut cmd, &limits).unwrap();
+        let (status, usage) = wait_timeout(&mut child, Some(time::Duration::from_secs(30)))
+            .unwrap()
+            .expect("should be killed by the CPU time limit");
+        assert_eq!(exceeded_limit(status, &usage, &limits), Some(ResourceLimit::CpuTime));
+    }
+
+    #[test]
+    fn crashes_are_not_blamed_on_unreached_limits() {
+        let limits = ResourceLimits {
+            address_space: Some(1 << 34),
+            cpu_seconds: Some(60),
+            ..Default::default()
+        };
+        for signal in &["SEGV", "ABRT", "KILL"] {
+            let mut cmd = process::Command::new("sh");
+            cmd.args(&["-c", &format!("kill -{} $$", signal)]);
+            let mut child = spawn(&mut cmd, &limits).unwrap();
+            let (status, usage) = wait_timeout(&mut child, None).unwrap().unwrap();
+            assert!(!status.success());
+            assert_eq!(exceeded_limit(status, &usage, &limits), None);
+        }
+    }
+}
@@ -1,15 +1,18 @@
 //! Types related to test cases, their interestingness, and candidates
 //! of them.
+//!
+//! A test case is either a single file, or a directory of files. The size and
+//! hashes of a directory test case are computed over every file within it.
 
 use blake2::{Blake2b, Digest};
 use either::{Either, Left, Right};
+use diff;
 use error;
 use generic_array;
 use std::fs;
 use std::hash;
 use std::io::{self, Read};
 use std::path;
-use std::process;
 use std::sync::Arc;
 use tempdir;
 use traits;
@@ -140,6 +143,25 @@ impl TempFile {
         assert!(self.inner.file_path.is_absolute());
         &self.inner.file_path
     }
+
+    /// Get a temporary file for the given relative path within this temporary
+    /// file, which must be a directory. The new temporary file keeps this
+    /// one's temporary directory alive.
+    pub fn join<P>(&self, member: P) -> TempFile
+    where
+        P: AsRef<path::Path>,
+    {
+        assert!(
+            member.as_ref().is_relative(),
+            "The given member should be relative to the temporary file"
+        );
+        TempFile {
+            inner: Arc::new(TempFileInner {
+                file_path: self.inner.file_path.join(member),
+                dir: self.inner.dir.clone(),
+            }),
+        }
+    }
 }
 
 impl From<Candidate> for TempFile {
@@ -159,6 +181,7 @@ impl From<InterestingKind> for TempFile {
         match kind {
             InterestingKind::Initial(i) => i.into(),
             InterestingKind::Candidate(r) => r.into(),
+            InterestingKind::Member(m) => m.into(),
         }
     }
 }
@@ -169,6 +192,12 @@ impl From<InitialInteresting> for TempFile {
     }
 }
 
+impl From<MemberInteresting> for TempFile {
+    fn from(member: MemberInteresting) -> TempFile {
+        member.test_case
+    }
+}
+
 /// A test case with potential: it may or may not be smaller than our smallest
 /// interesting test case, and it may or may not be interesting.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -190,6 +219,10 @@ pub struct Candidate {
 
     /// The hash of the diff with the seed.
     diff_hash: Blake2Hash,
+
+    /// The hash of the full contents of the seed this candidate was generated
+    /// from.
+    seed_hash: Blake2Hash,
 }
 
 impl hash::Hash for Candidate {
@@ -224,7 +257,8 @@ impl TestCaseMethods for Candidate {
     }
 }
 
-fn hash<R: Read>(mut src: R) -> error::Result<Blake2Hash> {
+/// Hash everything that can be read from the given source.
+pub(crate) fn hash<R: Read>(mut src: R) -> error::Result<Blake2Hash> {
     let mut hasher = Blake2b::default();
     let mut buf = vec![0; 1024 * 1024];
     loop {
@@ -243,6 +277,202 @@ fn hash<R: Read>(mut src: R) -> error::Result<Blake2Hash> {
     Ok(hasher.result())
 }
 
+/// Hash the test case at the given path, which may be either a file or a
+/// directory.
+///
+/// A directory's hash covers each of its files' relative paths and contents,
+/// in sorted order.
+pub(crate) fn hash_test_case(test_case: &path::Path) -> error::Result<Blake2Hash> {
+    if !test_case.is_dir() {
+        return hash(fs::File::open(test_case)?);
+    }
+
+    let mut hasher = Blake2b::default();
+    for member in members(test_case)? {
+        hasher.input(member.to_string_lossy().as_bytes());
+        hasher.input(&[0]);
+        hasher.input(&hash(fs::File::open(test_case.join(&member))?)?);
+    }
+    Ok(hasher.result())
+}
+
+/// Hash the line diff from the `seed` test case to the `candidate` test case,
+/// either of which may be a file or a directory.
+///
+/// Only the deleted and inserted lines go into the hash, not their positions
+/// or the unchanged lines around them, so that the same edit made to different
+/// seeds hashes the same. A directory's diff covers each member file that
+/// changed, by relative path; a missing member diffs as an empty file.
+pub(crate) fn hash_diff(seed: &path::Path, candidate: &path::Path) -> error::Result<Blake2Hash> {
+    let mut hasher = Blake2b::default();
+    if !seed.is_dir() && !candidate.is_dir() {
+        hash_edits(&mut hasher, &read_or_empty(seed)?, &read_or_empty(candidate)?);
+        return Ok(hasher.result());
+    }
+
+    let mut all_members = vec![];
+    for test_case in &[seed, candidate] {
+        if test_case.is_dir() {
+            all_members.extend(members(test_case)?);
+        }
+    }
+    all_members.sort();
+    all_members.dedup();
+
+    for member in all_members {
+        let old = read_or_empty(&seed.join(&member))?;
+        let new = read_or_empty(&candidate.join(&member))?;
+        if old == new {
+            continue;
+        }
+        hasher.input(member.to_string_lossy().as_bytes());
+        hasher.input(&[0]);
+        hash_edits(&mut hasher, &old, &new);
+    }
+    Ok(hasher.result())
+}
+
+fn hash_edits(hasher: &mut Blake2b, old: &[u8], new: &[u8]) {
+    for edit in diff::diff(&diff::lines(old), &diff::lines(new)) {
+        match edit {
+            diff::Edit::Delete(line) => {
+                hasher.input(b"-");
+                hasher.input(line);
+            }
+            diff::Edit::Insert(line) => {
+                hasher.input(b"+");
+                hasher.input(line);
+            }
+        }
+    }
+}
+
+/// Read the whole file at the given path, or nothing if there is no file
+/// there.
+fn read_or_empty(path: &path::Path) -> error::Result<Vec<u8>> {
+    let mut contents = vec![];
+    if path.is_file() {
+        fs::File::open(path)?.read_to_end(&mut contents)?;
+    }
+    Ok(contents)
+}
+
+/// Get the size (in bytes) of the test case at the given path. The size of a
+/// directory test case is the sum of the sizes of the files within it.
+fn size_of_test_case(test_case: &path::Path) -> error::Result<u64> {
+    if !test_case.is_dir() {
+        return Ok(fs::metadata(test_case)?.len());
+    }
+
+    let mut size = 0;
+    for member in members(test_case)? {
+        size += fs::metadata(test_case.join(member))?.len();
+    }
+    Ok(size)
+}
+
+/// Get the paths of all the files within the given directory test case,
+/// relative to the directory, in sorted order.
+pub fn members<P>(dir: P) -> error::Result<Vec<path::PathBuf>>
+where
+    P: AsRef<path::Path>,
+{
+    fn collect(
+        root: &path::Path,
+        dir: &path::Path,
+        members: &mut Vec<path::PathBuf>,
+    ) -> error::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                collect(root, &path, members)?;
+            } else {
+                let member = path.strip_prefix(root)
+                    .expect("entries are always within the root")
+                    .to_path_buf();
+                members.push(member);
+            }
+        }
+        Ok(())
+    }
+
+    let mut members = vec![];
+    collect(dir.as_ref(), dir.as_ref(), &mut members)?;
+    members.sort();
+    Ok(members)
+}
+
+/// Copy the test case at `from` to `to`, replacing anything that is already
+/// at `to`. Works for both file and directory test cases.
+pub fn copy_test_case<P, Q>(from: P, to: Q) -> error::Result<()>
+where
+    P: AsRef<path::Path>,
+    Q: AsRef<path::Path>,
+{
+    let from = from.as_ref();
+    let to = to.as_ref();
+
+    if !from.is_dir() {
+        if to.is_dir() {
+            fs::remove_dir_all(to)?;
+        }
+        fs::copy(from, to)?;
+        return Ok(());
+    }
+
+    // Never remove what is at `to` before the new copy is complete: it may be
+    // the only up-to-date copy of the smallest interesting test case. Instead,
+    // copy into a temporary directory next to `to`, and then swap the copy in
+    // with renames, which can't leave a partial tree behind.
+    let parent = match to.parent() {
+        Some(parent) if parent != path::Path::new("") => parent,
+        _ => path::Path::new("."),
+    };
+    let staging = tempdir::TempDir::new_in(parent, ".preduce-copy")?;
+    let new = staging.path().join("new");
+    fs::create_dir(&new)?;
+    for member in members(from)? {
+        let dest = new.join(&member);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(from.join(&member), dest)?;
+    }
+
+    let old = staging.path().join("old");
+    let replacing = fs::symlink_metadata(to).is_ok();
+    if replacing {
+        fs::rename(to, &old)?;
+    }
+    if let Err(e) = fs::rename(&new, to) {
+        if replacing {
+            let _ = fs::rename(&old, to);
+        }
+        return Err(e.into());
+    }
+
+    // Dropping the staging directory removes the old test case.
+    Ok(())
+}
+
+/// Format the given hash as a lowercase hexadecimal string.
+pub(crate) fn hash_to_hex(hash: &Blake2Hash) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parse a hash that was previously formatted with `hash_to_hex`.
+pub(crate) fn hash_from_hex(hex: &str) -> Option<Blake2Hash> {
+    if !hex.is_ascii() || hex.len() != Blake2Hash::default().len() * 2 {
+        return None;
+    }
+
+    let bytes: Option<Vec<u8>> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect();
+    bytes.map(|b| Blake2Hash::clone_from_slice(&b))
+}
+
 impl Candidate {
     /// Construct a new candidate.
     ///
@@ -268,25 +498,10 @@ impl Candidate {
         let diff_hash;
         {
             let path = test_case.path();
-            size = fs::metadata(&path)?.len();
-
-            let file = fs::File::open(&path)?;
-            full_hash = hash(file)?;
-
-            let diff = process::Command::new("diff")
-                .args(&[
-                    "--unchanged-line-format=''",
-                    "--new-line-format='+%L'",
-                    "--old-line-format='-%L'",
-                    &provenance,
-                    &path.display().to_string(),
-                ])
-                .stdout(process::Stdio::piped())
-                .stderr(process::Stdio::null())
-                .stdin(process::Stdio::null())
-                .output()?
-                .stdout;
-            diff_hash = hash(&diff[..])?;
+            size = size_of_test_case(path)?;
+            full_hash = hash_test_case(path)?;
+
+            diff_hash = hash_diff(seed.path(), path)?;
         }
         Ok(Candidate {
             provenance: provenance,
@@ -295,9 +510,16 @@ impl Candidate {
             delta: seed.size().saturating_sub(size),
             full_hash,
             diff_hash,
+            seed_hash: seed.full_hash(),
         })
     }
 
+    /// Get the hash of the full contents of the seed test case that this
+    /// candidate was generated from.
+    pub fn seed_hash(&self) -> Blake2Hash {
+        self.seed_hash
+    }
+
     /// Try and convert this *potentially interesting* candidate into a *known
     /// interesting* test case by validating whether it is interesting or not
     /// using the given `judge`.
@@ -305,7 +527,7 @@ impl Candidate {
     where
         I: ?Sized + traits::IsInteresting,
     {
-        assert!(self.path().is_file());
+        assert!(self.path().exists());
 
         if !judge.is_interesting(self.path())? {
             return Ok(Right(self));
@@ -370,18 +592,18 @@ impl Interesting {
         let file_name = path::PathBuf::from(file_path.as_ref().file_name().ok_or(
             error::Error::Io(io::Error::new(
                 io::ErrorKind::Other,
-                "Initial test case must be a file",
+                "Initial test case must be a file or directory",
             )),
         )?);
         let temp_file = TempFile::new(dir, file_name)?;
-        fs::copy(file_path.as_ref(), temp_file.path())?;
+        copy_test_case(file_path.as_ref(), temp_file.path())?;
 
         if !judge.is_interesting(temp_file.path())? {
             return Ok(None);
         }
 
-        let size = fs::metadata(temp_file.path())?.len();
-        let full_hash = hash(fs::File::open(file_path.as_ref())?)?;
+        let size = size_of_test_case(temp_file.path())?;
+        let full_hash = hash_test_case(temp_file.path())?;
 
         Ok(Some(Interesting {
             kind: InterestingKind::Initial(InitialInteresting {
@@ -396,10 +618,58 @@ impl Interesting {
     /// the initial interesting test case, coerce it to a `Candidate`.
     pub fn as_candidate(&self) -> Option<&Candidate> {
         match self.kind {
-            InterestingKind::Initial(..) => None,
+            InterestingKind::Initial(..) | InterestingKind::Member(..) => None,
             InterestingKind::Candidate(ref r) => Some(r),
         }
     }
+
+    /// Is this a directory test case?
+    pub fn is_dir(&self) -> bool {
+        self.path().is_dir()
+    }
+
+    /// Get a view of the given member file of this directory test case, for
+    /// use as a seed for reducers that only know how to reduce a single file.
+    ///
+    /// The `member` path must be relative to this test case's directory.
+    pub fn member<P>(&self, member: P) -> error::Result<Interesting>
+    where
+        P: AsRef<path::Path>,
+    {
+        let member = member.as_ref();
+        let test_case = self.test_case().join(member);
+        if !test_case.path().is_file() {
+            return Err(error::Error::DoesNotExist(test_case.path().into()));
+        }
+
+        let size = size_of_test_case(test_case.path())?;
+        let full_hash = hash_test_case(test_case.path())?;
+        Ok(Interesting {
+            kind: InterestingKind::Member(MemberInteresting {
+                member: member.into(),
+                test_case: test_case,
+                size: size,
+                full_hash: full_hash,
+            }),
+        })
+    }
+
+    /// If this is a view of a single member file of a directory test case,
+    /// get the member's path relative to the directory.
+    pub fn member_path(&self) -> Option<&path::Path> {
+        match self.kind {
+            InterestingKind::Member(ref m) => Some(&m.member),
+            _ => None,
+        }
+    }
+
+    fn test_case(&self) -> &TempFile {
+        match self.kind {
+            InterestingKind::Initial(ref i) => &i.test_case,
+            InterestingKind::Candidate(ref c) => &c.test_case,
+            InterestingKind::Member(ref m) => &m.test_case,
+        }
+    }
 }
 
 /// An enumeration of the kinds of interesting test cases.
@@ -411,6 +681,9 @@ enum InterestingKind {
     /// A candidate of the initial test case that has been found to be
     /// interesting.
     Candidate(Candidate),
+
+    /// A view of a single member file of an interesting directory test case.
+    Member(MemberInteresting),
 }
 
 impl hash::Hash for InterestingKind {
@@ -424,6 +697,7 @@ impl TestCaseMethods for InterestingKind {
         match *self {
             InterestingKind::Initial(ref initial) => initial.path(),
             InterestingKind::Candidate(ref candidate) => candidate.path(),
+            InterestingKind::Member(ref member) => member.path(),
         }
     }
 
@@ -431,6 +705,7 @@ impl TestCaseMethods for InterestingKind {
         match *self {
             InterestingKind::Initial(ref initial) => initial.size(),
             InterestingKind::Candidate(ref candidate) => candidate.size(),
+            InterestingKind::Member(ref member) => member.size(),
         }
     }
 
@@ -438,6 +713,7 @@ impl TestCaseMethods for InterestingKind {
         match *self {
             InterestingKind::Initial(ref initial) => initial.delta(),
             InterestingKind::Candidate(ref candidate) => candidate.delta(),
+            InterestingKind::Member(ref member) => member.delta(),
         }
     }
 
@@ -445,6 +721,7 @@ impl TestCaseMethods for InterestingKind {
         match *self {
             InterestingKind::Initial(ref i) => i.provenance(),
             InterestingKind::Candidate(ref r) => r.provenance(),
+            InterestingKind::Member(ref m) => m.provenance(),
         }
     }
 
@@ -452,6 +729,7 @@ impl TestCaseMethods for InterestingKind {
         match *self {
             InterestingKind::Initial(ref i) => i.full_hash(),
             InterestingKind::Candidate(ref r) => r.full_hash(),
+            InterestingKind::Member(ref m) => m.full_hash(),
         }
     }
 
@@ -459,6 +737,7 @@ impl TestCaseMethods for InterestingKind {
         match *self {
             InterestingKind::Initial(ref i) => i.diff_hash(),
             InterestingKind::Candidate(ref r) => r.diff_hash(),
+            InterestingKind::Member(ref m) => m.diff_hash(),
         }
     }
 }
@@ -509,6 +788,54 @@ impl TestCaseMethods for InitialInteresting {
     }
 }
 
+/// A view of a single member file of an interesting directory test case.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct MemberInteresting {
+    /// The path of the member file, relative to the directory test case.
+    member: path::PathBuf,
+
+    /// The member file itself.
+    test_case: TempFile,
+
+    /// The size of the member file.
+    size: u64,
+
+    /// The hash of the member file's contents.
+    full_hash: Blake2Hash,
+}
+
+impl hash::Hash for MemberInteresting {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.path().hash(state);
+    }
+}
+
+impl TestCaseMethods for MemberInteresting {
+    fn path(&self) -> &path::Path {
+        self.test_case.path()
+    }
+
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn delta(&self) -> u64 {
+        0
+    }
+
+    fn provenance(&self) -> &str {
+        "<member>"
+    }
+
+    fn full_hash(&self) -> Blake2Hash {
+        self.full_hash
+    }
+
+    fn diff_hash(&self) -> Blake2Hash {
+        hash(&[][..]).expect("reading from an empty slice cannot fail")
+    }
+}
+
 #[cfg(test)]
 impl Candidate {
     pub fn testing_only_new() -> Candidate {
@@ -519,6 +846,19 @@ impl Candidate {
             delta: 0,
             full_hash: Default::default(),
             diff_hash: Default::default(),
+            seed_hash: Default::default(),
+        }
+    }
+
+    /// Construct a dummy candidate with the given provenance, size, and delta,
+    /// for unit tests that only look at those.
+    #[doc(hidden)]
+    pub fn testing_only_with(provenance: &str, size: u64, delta: u64) -> Candidate {
+        Candidate {
+            provenance: provenance.into(),
+            size: size,
+            delta: delta,
+            ..Candidate::testing_only_new()
         }
     }
 }
@@ -534,6 +874,15 @@ impl Interesting {
             }),
         }
     }
+
+    /// Construct an interesting test case from the given candidate, without
+    /// running any is-interesting predicate, for unit tests.
+    #[doc(hidden)]
+    pub fn testing_only_from(candidate: Candidate) -> Interesting {
+        Interesting {
+            kind: InterestingKind::Candidate(candidate),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -675,4 +1024,136 @@ mod tests {
             "And the test case should have the expected size"
         );
     }
+
+    #[test]
+    fn hash_hex_round_trip() {
+        let hash = super::hash(&b"la la la"[..]).unwrap();
+        let hex = hash_to_hex(&hash);
+        assert_eq!(hex.len(), 128);
+        assert_eq!(hash_from_hex(&hex), Some(hash));
+
+        assert_eq!(hash_from_hex("not hex"), None);
+        assert_eq!(hash_from_hex(&hex[1..]), None);
+    }
+
+    fn write_file(path: &path::Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut file = fs::File::create(path).unwrap();
+        write!(&mut file, "{}", contents).unwrap();
+    }
+
+    #[test]
+    fn interesting_initial_directory() {
+        let dir = TempDir::new("interesting_initial_directory").expect("should create temp dir");
+        let initial_path = dir.path().join("initial");
+        write_file(&initial_path.join("a.c"), "int a;\n");
+        write_file(&initial_path.join("include/b.h"), "int b;\n");
+
+        let judge = |_: &path::Path| Ok(true);
+        let interesting = Interesting::initial(&initial_path, &judge)
+            .expect("should not error")
+            .expect("and should find the initial test case interesting");
+
+        assert!(interesting.is_dir());
+        assert_eq!(interesting.size(), 14, "size is the sum of all the files' sizes");
+        assert_eq!(
+            members(interesting.path()).unwrap(),
+            vec![path::PathBuf::from("a.c"), path::PathBuf::from("include/b.h")]
+        );
+
+        // Changing any file's contents changes the whole directory's hash.
+        let before = interesting.full_hash();
+        write_file(&initial_path.join("include/b.h"), "int c;\n");
+        let changed = Interesting::initial(&initial_path, &judge).unwrap().unwrap();
+        assert!(before != changed.full_hash());
+
+        // So does moving a file, even though the contents are unchanged.
+        fs::rename(initial_path.join("include/b.h"), initial_path.join("b.h")).unwrap();
+        let moved = Interesting::initial(&initial_path, &judge).unwrap().unwrap();
+        assert!(changed.full_hash() != moved.full_hash());
+        assert_eq!(moved.size(), 14);
+
+        let member = interesting.member("include/b.h").unwrap();
+        assert_eq!(member.member_path(), Some(path::Path::new("include/b.h")));
+        assert_eq!(member.size(), 7);
+        assert_eq!(member.path(), interesting.path().join("include/b.h"));
+        assert!(interesting.member("nope.h").is_err());
+    }
+
+    #[test]
+    fn copy_directory_over_existing() {
+        let dir = TempDir::new("copy_directory_over_existing").expect("should create temp dir");
+        let from = dir.path().join("from");
+        let to = dir.path().join("to");
+        write_file(&from.join("a.c"), "int a;\n");
+        write_file(&from.join("include/b.h"), "int b;\n");
+        write_file(&to.join("stale.c"), "int stale;\n");
+
+        copy_test_case(&from, &to).expect("should copy the directory");
+        assert_eq!(
+            members(&to).unwrap(),
+            vec![path::PathBuf::from("a.c"), path::PathBuf::from("include/b.h")]
+        );
+
+        // The staging directory is cleaned up after swapping the copy in.
+        let entries: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries.len(), 2, "should only have `from` and `to`: {:?}", entries);
+    }
+
+    #[test]
+    fn diff_hash_same_edit_different_seeds() {
+        let dir = TempDir::new("diff_hash_same_edit").expect("should create temp dir");
+        let seed_a = dir.path().join("seed_a");
+        let seed_b = dir.path().join("seed_b");
+        let candidate_a = dir.path().join("candidate_a");
+        let candidate_b = dir.path().join("candidate_b");
+        let other = dir.path().join("other");
+        write_file(&seed_a, "int a;\nint unused;\nint b;\n");
+        write_file(&seed_b, "int c;\nint d;\nint unused;\nint e;\n");
+        write_file(&candidate_a, "int a;\nint b;\n");
+        write_file(&candidate_b, "int c;\nint d;\nint e;\n");
+        write_file(&other, "int a;\nint unused;\n");
+
+        let edit_a = hash_diff(&seed_a, &candidate_a).unwrap();
+        let edit_b = hash_diff(&seed_b, &candidate_b).unwrap();
+        assert_eq!(edit_a, edit_b, "the same edit to different seeds should hash the same");
+        assert!(edit_a != hash_diff(&seed_a, &other).unwrap());
+        assert_eq!(
+            hash_diff(&seed_a, &seed_a).unwrap(),
+            super::hash(&[][..]).unwrap(),
+            "no edit should hash like an initial test case's empty diff"
+        );
+    }
+
+    #[test]
+    fn diff_hash_directory() {
+        let dir = TempDir::new("diff_hash_directory").expect("should create temp dir");
+        let seed = dir.path().join("seed");
+        let renamed = dir.path().join("renamed");
+        let candidate_dir = Arc::new(TempDir::new("diff_hash_candidate").unwrap());
+        let edited = candidate_dir.path().join("edited");
+        write_file(&seed.join("a.c"), "int a;\nint unused;\n");
+        write_file(&seed.join("b.c"), "int b;\n");
+        write_file(&renamed.join("a.c"), "int a;\nint unused;\n");
+        write_file(&renamed.join("c.c"), "int b;\n");
+        write_file(&edited.join("a.c"), "int a;\n");
+        write_file(&edited.join("b.c"), "int b;\n");
+
+        let edited_hash = hash_diff(&seed, &edited).unwrap();
+        assert!(hash_diff(&seed, &renamed).unwrap() != edited_hash);
+        assert!(hash_diff(&seed, &seed).unwrap() != edited_hash);
+
+        let judge = |_: &path::Path| Ok(true);
+        let interesting = Interesting::initial(&seed, &judge).unwrap().unwrap();
+        let candidate = Candidate::new(
+            interesting,
+            "test",
+            TempFile::new(candidate_dir.clone(), "edited").unwrap(),
+        )
+            .expect("should create candidate");
+        assert_eq!(candidate.diff_hash(), edited_hash);
+    }
 }
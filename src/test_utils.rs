@@ -22,3 +22,7 @@ pub fn get_exit_0() -> path::PathBuf {
 pub fn get_exit_1() -> path::PathBuf {
     get_predicate("exit_1.sh")
 }
+
+pub fn get_sleep_30() -> path::PathBuf {
+    get_predicate("sleep_30.sh")
+}
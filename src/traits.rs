@@ -1,11 +1,15 @@
 //! Interfaces and common behaviors.
 
 use error;
+use interesting;
 use score;
+use serde_json;
 use std::any::Any;
 use std::borrow::Cow;
+use std::ffi;
 use std::fmt;
 use std::path;
+use std::time;
 use test_case;
 
 /// A reducer generates potentially-interesting candidates from a
@@ -278,6 +282,28 @@ pub trait Reducer: fmt::Debug + Send {
         seed: &test_case::Interesting,
         state: &Box<Any + Send>,
     ) -> error::Result<Option<test_case::Candidate>>;
+
+    /// Serialize the given state, so that it can be saved in a checkpoint and
+    /// restored with `restore_state` when the reduction is resumed.
+    ///
+    /// By default, states are not serialized, and a resumed reduction starts
+    /// this reducer over again with `new_state`.
+    fn snapshot_state(&self, _state: &Box<Any + Send>) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Restore a state for the given seed that was previously serialized with
+    /// `snapshot_state`.
+    ///
+    /// If the state cannot be restored, then `None` should be returned, and
+    /// the reducer will start over with `new_state` instead.
+    fn restore_state(
+        &mut self,
+        _seed: &test_case::Interesting,
+        _snapshot: serde_json::Value,
+    ) -> error::Result<Option<Box<Any + Send>>> {
+        Ok(None)
+    }
 }
 
 /// Is a candidate interesting?
@@ -294,10 +320,80 @@ pub trait IsInteresting: Send {
     /// otherwise.
     fn is_interesting(&self, candidate: &path::Path) -> error::Result<bool>;
 
+    /// Like `is_interesting`, but if this predicate runs more than once to
+    /// tolerate flakiness, like `interesting::Retry` or
+    /// `interesting::Majority`, only run it once. This is what the flakiness
+    /// check measures.
+    ///
+    /// By default, this calls `is_interesting`.
+    fn is_interesting_once(&self, candidate: &path::Path) -> error::Result<bool> {
+        self.is_interesting(candidate)
+    }
+
+    /// Like `is_interesting`, but also describe how the candidate was judged:
+    /// the `Votes` of the predicate's runs, if it runs more than once to
+    /// tolerate flakiness, and, if `capture_output` is true, everything that
+    /// its processes printed.
+    ///
+    /// By default, this calls `is_interesting`, and there are no details.
+    fn judge(
+        &self,
+        candidate: &path::Path,
+        _capture_output: bool,
+    ) -> error::Result<interesting::Judgement> {
+        Ok(self.is_interesting(candidate)?.into())
+    }
+
+    /// Like `is_interesting`, but also return the `Votes` of the predicate's
+    /// runs, if it runs more than once to tolerate flakiness.
+    ///
+    /// By default, this calls `judge`.
+    fn is_interesting_with_votes(
+        &self,
+        candidate: &path::Path,
+    ) -> error::Result<(bool, Option<interesting::Votes>)> {
+        let judgement = self.judge(candidate, false)?;
+        Ok((judgement.interesting, judgement.votes))
+    }
+
+    /// Like `is_interesting`, but also capture and return everything that the
+    /// predicate's processes printed, for diagnosing a misbehaving predicate.
+    ///
+    /// By default, this calls `judge`.
+    fn is_interesting_with_output(
+        &self,
+        candidate: &path::Path,
+    ) -> error::Result<(bool, Option<interesting::Output>)> {
+        let judgement = self.judge(candidate, true)?;
+        Ok((judgement.interesting, judgement.output))
+    }
+
     /// Clone this `IsInteresting` predicate as an owned trait object.
     fn clone(&self) -> Box<IsInteresting>
     where
         Self: 'static;
+
+    /// Get a fingerprint identifying this predicate, for keying its results
+    /// in an on-disk cache that persists across runs. Any change to the
+    /// predicate that could change its results must also change its
+    /// fingerprint.
+    ///
+    /// By default, there is no fingerprint, and results are never cached.
+    fn fingerprint(&self) -> Option<String> {
+        None
+    }
+
+    /// Set environment variables for any processes this predicate spawns, in
+    /// addition to the ones they inherit from us. This must not change the
+    /// predicate's fingerprint.
+    ///
+    /// By default, this does nothing.
+    fn set_env(&mut self, _vars: &[(String, ffi::OsString)]) {}
+
+    /// Apply the given resource limits to any processes this predicate spawns.
+    ///
+    /// By default, this does nothing.
+    fn set_resource_limits(&mut self, _limits: &interesting::ResourceLimits) {}
 }
 
 /// An oracle observes the results of interesting-ness judgements of candidates
@@ -305,15 +401,39 @@ pub trait IsInteresting: Send {
 /// them. The resulting scores are ultimately used by the supervisor actor to
 /// prioritize and schedule work.
 pub trait Oracle: Send {
-    /// Tell the oracle that we found a new smallest interesting test case.
-    fn observe_smallest_interesting(&mut self, interesting: &test_case::Interesting);
+    /// Tell the oracle that we found a new smallest interesting test case, and
+    /// that judging it took `elapsed` wall time.
+    fn observe_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    );
 
     /// Tell the oracle that we found a new interesting test case, but that it
-    /// is not the smallest.
-    fn observe_not_smallest_interesting(&mut self, interesting: &test_case::Interesting);
+    /// is not the smallest, and that judging it took `elapsed` wall time.
+    fn observe_not_smallest_interesting(
+        &mut self,
+        interesting: &test_case::Interesting,
+        elapsed: time::Duration,
+    );
 
-    /// Tell the oracle that we found the given candidate unininteresting.
-    fn observe_not_interesting(&mut self, candidate: &test_case::Candidate);
+    /// Tell the oracle that we found the given candidate unininteresting, and
+    /// that judging it took `elapsed` wall time.
+    fn observe_not_interesting(
+        &mut self,
+        candidate: &test_case::Candidate,
+        elapsed: time::Duration,
+    );
+
+    /// Tell the oracle that the predicate timed out while judging the given
+    /// candidate, after `elapsed` wall time, so the candidate is not
+    /// interesting.
+    ///
+    /// By default, this is observed the same as any other uninteresting
+    /// candidate, charged with the full time it took to time out.
+    fn observe_timed_out(&mut self, candidate: &test_case::Candidate, elapsed: time::Duration) {
+        self.observe_not_interesting(candidate, elapsed);
+    }
 
     /// Tell the oracle that the reducer with the given name has been exhausted.
     fn observe_exhausted(&mut self, reducer_name: &str);
@@ -321,6 +441,24 @@ pub trait Oracle: Send {
     /// Ask the oracle's to score the given candidate, so we know how
     /// to prioritize testing it.
     fn predict(&mut self, candidate: &test_case::Candidate) -> score::Score;
+
+    /// Serialize this oracle's observations, so that they can be saved in a
+    /// checkpoint and restored with `restore` when the reduction is resumed.
+    ///
+    /// By default, nothing is saved.
+    fn snapshot(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Restore observations that were previously serialized with `snapshot`,
+    /// given the `smallest` interesting test case that we are resuming from.
+    fn restore(
+        &mut self,
+        _snapshot: serde_json::Value,
+        _smallest: &test_case::Interesting,
+    ) -> error::Result<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 extern crate preduce;
+extern crate serde_json;
+extern crate tempdir;
 
 use preduce::test_case::TestCaseMethods;
 use preduce::traits::Reducer;
@@ -189,6 +191,945 @@ test_preduce_runs! {
     }
 }
 
+#[test]
+fn checkpoint_and_resume() {
+    let dir = tempdir::TempDir::new("checkpoint_and_resume").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let checkpoint_dir = dir.path().join("checkpoint");
+    let log_file = dir.path().join("reduction.log");
+    let expected = "tests/expectations/lorem-ipsum.txt";
+
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--checkpoint-dir")
+        .arg(&checkpoint_dir)
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("--workers")
+        .arg("2")
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    for file in &["checkpoint.json", "smallest", "invocation.json"] {
+        assert!(
+            checkpoint_dir.join(file).is_file(),
+            "checkpoint should contain {}",
+            file
+        );
+    }
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg(expected)
+        .arg(checkpoint_dir.join("smallest"))
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "checkpointed smallest should match expectation");
+
+    // Clobber the test case, so we know that resuming restores it from the
+    // checkpoint.
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+    std::fs::remove_file(&log_file).expect("should remove log file");
+
+    // The reduction's settings can't be changed when resuming it.
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--resume")
+        .arg(&checkpoint_dir)
+        .arg("--workers")
+        .arg("3")
+        .stderr(std::process::Stdio::null())
+        .status()
+        .expect("should run preduce OK");
+    assert!(!status.success(), "resumed preduce should reject new settings");
+
+    // Resume from another directory, to check that the checkpointed settings
+    // don't depend on where we run from.
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--resume")
+        .arg(&checkpoint_dir)
+        .current_dir(dir.path())
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "resumed preduce should exit OK");
+    assert!(log_file.exists(), "resumed preduce should keep logging to the same file");
+    assert!(!dir.path().join("preduce.log").exists());
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg(expected)
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "resumed test case should match expectation");
+}
+
+#[test]
+fn preflight_trivially_true_predicate() {
+    let dir = tempdir::TempDir::new("preflight").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let output = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--command")
+        .arg("echo checked")
+        .arg(&test_case)
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .output()
+        .expect("should run preduce OK");
+    assert!(!output.status.success(), "preduce should fail the pre-flight checks");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("an empty test case is interesting"), "{}", stderr);
+    assert!(stderr.contains("checked"), "{}", stderr);
+
+    let contents = std::fs::read_to_string(&test_case).expect("should read test case");
+    let original = std::fs::read_to_string("tests/fixtures/lorem-ipsum.txt")
+        .expect("should read fixture");
+    assert_eq!(contents, original, "the test case should not be reduced");
+}
+
+#[test]
+fn command_predicate() {
+    let dir = tempdir::TempDir::new("command_predicate").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    // The equivalent of `tests/predicates/has-lorem.sh`, without the script.
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--command")
+        .arg("grep -i lorem {}")
+        .arg("--expect-exit-code")
+        .arg("0")
+        .arg("--expect-stdout")
+        .arg("(?i)lorem")
+        .arg(&test_case)
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+}
+
+#[test]
+fn cache_dir_reuses_results() {
+    use std::io::{Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir::TempDir::new("cache_dir_reuses_results").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let cache_dir = dir.path().join("cache");
+    let count = dir.path().join("count");
+
+    // A predicate that records each time it is invoked.
+    let predicate = dir.path().join("predicate.sh");
+    {
+        let mut file = std::fs::File::create(&predicate).expect("should create predicate");
+        writeln!(
+            &mut file,
+            "#!/usr/bin/env sh\necho x >> {}\ngrep -i lorem \"$1\"",
+            count.display()
+        ).unwrap();
+    }
+    let mut perms = std::fs::metadata(&predicate).unwrap().permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(&predicate, perms).unwrap();
+
+    let run = || -> usize {
+        std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+        let _ = std::fs::remove_file(&count);
+
+        let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+            .arg("--workers")
+            .arg("1")
+            .arg("--cache-dir")
+            .arg(&cache_dir)
+            .arg(&test_case)
+            .arg(&predicate)
+            .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+            .status()
+            .expect("should run preduce OK");
+        assert!(status.success(), "preduce should exit OK");
+
+        let status = Command::new("diff")
+            .arg("-U8")
+            .arg("tests/expectations/lorem-ipsum.txt")
+            .arg(&test_case)
+            .status()
+            .expect("should run diff OK");
+        assert!(status.success(), "diff should exit OK");
+
+        let mut invocations = String::new();
+        std::fs::File::open(&count)
+            .expect("predicate should have been invoked")
+            .read_to_string(&mut invocations)
+            .unwrap();
+        invocations.lines().count()
+    };
+
+    let first = run();
+    let second = run();
+    assert!(
+        second < first,
+        "the second run should reuse cached results ({} vs {} predicate invocations)",
+        second,
+        first
+    );
+}
+
+#[test]
+fn output_and_log_file() {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let dir = tempdir::TempDir::new("output_and_log_file").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let output = dir.path().join("reduced.txt");
+    let log_file = dir.path().join("reduction.log");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    // The initial test case may be read-only, since it is never overwritten.
+    let mut perms = std::fs::metadata(&test_case).unwrap().permissions();
+    perms.set_readonly(true);
+    std::fs::set_permissions(&test_case, perms).unwrap();
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--output")
+        .arg(&output)
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    let status = Command::new("diff")
+        .arg("tests/fixtures/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "the initial test case should be untouched");
+    assert!(!dir.path().join("lorem-ipsum.txt.orig").exists());
+
+    // Without the status view, progress summaries are not logged.
+    let mut log = String::new();
+    std::fs::File::open(&log_file)
+        .expect("should open log file")
+        .read_to_string(&mut log)
+        .unwrap();
+    assert!(!log.contains("Supervisor: smallest is"));
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&output)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+
+    // Now read the same test case from stdin. Run from inside the temp dir, so
+    // that a "-" mistaken for a file name doesn't land in the crate root.
+    std::fs::remove_file(&output).unwrap();
+    let predicate = std::fs::canonicalize("tests/predicates/has-lorem.sh").unwrap();
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .current_dir(dir.path())
+        .arg("--output")
+        .arg(&output)
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("-")
+        .arg(&predicate)
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .stdin(Stdio::from(std::fs::File::open(&test_case).unwrap()))
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&output)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+
+    // And from stdin to stdout, checkpointing so that the test case read from
+    // stdin is kept for resuming.
+    let checkpoint_dir = dir.path().join("checkpoint");
+    let result = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .current_dir(dir.path())
+        .arg("--output")
+        .arg("-")
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("--checkpoint-dir")
+        .arg(&checkpoint_dir)
+        .arg("-")
+        .arg(&predicate)
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .stdin(Stdio::from(std::fs::File::open(&test_case).unwrap()))
+        .output()
+        .expect("should run preduce OK");
+    assert!(result.status.success(), "preduce should exit OK");
+
+    let mut expected = vec![];
+    std::fs::File::open("tests/expectations/lorem-ipsum.txt")
+        .unwrap()
+        .read_to_end(&mut expected)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&result.stdout),
+        String::from_utf8_lossy(&expected),
+        "only the reduced test case should be written to stdout"
+    );
+
+    let mut invocation = String::new();
+    std::fs::File::open(checkpoint_dir.join("invocation.json"))
+        .unwrap()
+        .read_to_string(&mut invocation)
+        .unwrap();
+    let stdin_copy = checkpoint_dir.join("stdin");
+    assert!(invocation.contains(&*stdin_copy.to_string_lossy()));
+    let status = Command::new("diff")
+        .arg(&test_case)
+        .arg(&stdin_copy)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "the test case read from stdin should be kept");
+    assert!(!dir.path().join("-").exists());
+}
+
+#[test]
+fn config_file() {
+    use std::io::Write;
+
+    let dir = tempdir::TempDir::new("config_file").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let config = dir.path().join("preduce.toml");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let predicate = std::fs::canonicalize("tests/predicates/has-lorem.sh").unwrap();
+    let mut file = std::fs::File::create(&config).expect("should create config file");
+    write!(
+        &mut file,
+        r#"
+test_case = "lorem-ipsum.txt"
+workers = 2
+output = "reduced.txt"
+log_file = "reduction.log"
+log_format = "jsonl"
+
+[predicate]
+script = "{}"
+
+[[reducers]]
+path = "{}"
+weight = 2.0
+"#,
+        predicate.display(),
+        concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks")
+    ).unwrap();
+    drop(file);
+
+    // The command line takes precedence over the configuration file.
+    let log_file = dir.path().join("override.log");
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--config")
+        .arg(&config)
+        .arg("--log-file")
+        .arg(&log_file)
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    assert!(log_file.exists());
+    assert!(!dir.path().join("reduction.log").exists());
+    let log = std::fs::read_to_string(&log_file).unwrap();
+    assert!(log.lines().all(|l| l.starts_with("{")), "the log should be JSON lines");
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(dir.path().join("reduced.txt"))
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+
+    // Unknown keys are an error, rather than silently ignored.
+    writeln!(
+        &mut std::fs::OpenOptions::new().append(true).open(&config).unwrap(),
+        "wokers = 3"
+    ).unwrap();
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--config")
+        .arg(&config)
+        .status()
+        .expect("should run preduce OK");
+    assert!(!status.success(), "preduce should reject the invalid config");
+}
+
+#[test]
+fn jsonl_log_format() {
+    use std::io::{BufRead, BufReader};
+
+    let dir = tempdir::TempDir::new("jsonl_log_format").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let log_file = dir.path().join("preduce.jsonl");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("--log-format")
+        .arg("jsonl")
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    let log = BufReader::new(std::fs::File::open(&log_file).expect("should open log file"));
+    let mut smallest_sizes = vec![];
+    for line in log.lines() {
+        let line = line.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&line).expect("should be JSON");
+        assert!(json["event"].is_string(), "{}", line);
+        assert!(json["timestamp"].is_f64(), "{}", line);
+        if json["event"] == "new_smallest" {
+            let provenance = json["interesting"]["provenance"].as_str().unwrap();
+            assert!(provenance.ends_with("preduce-reducer-chunks"), "{}", line);
+            smallest_sizes.push(json["interesting"]["size"].as_u64().unwrap());
+        }
+    }
+
+    let final_size = std::fs::metadata(&test_case).unwrap().len();
+    assert_eq!(smallest_sizes.last(), Some(&final_size));
+}
+
+#[test]
+fn phases() {
+    use std::io::{BufRead, BufReader};
+
+    let dir = tempdir::TempDir::new("phases").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let log_file = dir.path().join("preduce.jsonl");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let chunks = concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks");
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("--log-format")
+        .arg("jsonl")
+        .arg("--phase")
+        .arg(format!("first:{}", chunks))
+        .arg("--phase")
+        .arg(format!("main:{}:fixpoint", chunks))
+        .arg("--phase")
+        .arg(format!("last:{}", chunks))
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    let log = BufReader::new(std::fs::File::open(&log_file).expect("should open log file"));
+    let phases: Vec<_> = log.lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(&line.unwrap()).unwrap())
+        .filter(|json| json["event"] == "starting_phase")
+        .map(|json| json["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(phases, vec!["first", "main", "last"]);
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+}
+
+#[test]
+fn builtin_reducers() {
+    use std::io::{BufRead, BufReader};
+
+    let dir = tempdir::TempDir::new("builtin_reducers").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    let log_file = dir.path().join("preduce.jsonl");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--log-file")
+        .arg(&log_file)
+        .arg("--log-format")
+        .arg("jsonl")
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .arg("chunks")
+        .arg("blank")
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    // The candidates' provenance is the built-in reducer's name, rather than a
+    // reducer script's path.
+    let log = BufReader::new(std::fs::File::open(&log_file).expect("should open log file"));
+    assert!(log.lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(&line.unwrap()).unwrap())
+        .filter(|json| json["event"] == "new_smallest")
+        .all(|json| {
+            json["interesting"]["provenance"] == "chunks" ||
+                json["interesting"]["provenance"] == "blank"
+        }));
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .arg("no-such-reducer")
+        .status()
+        .expect("should run preduce OK");
+    assert!(!status.success(), "preduce should reject unknown reducers");
+}
+
+#[test]
+fn oracle_strategies() {
+    for strategy in preduce::oracle::Strategy::NAMES {
+        let dir = tempdir::TempDir::new("oracle_strategies").expect("should create temp dir");
+        let test_case = dir.path().join("lorem-ipsum.txt");
+        std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+        let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+            .arg("--oracle")
+            .arg(strategy)
+            .arg(&test_case)
+            .arg("tests/predicates/has-lorem.sh")
+            .arg("chunks")
+            .arg("blank")
+            .status()
+            .expect("should run preduce OK");
+        assert!(status.success(), "preduce --oracle {} should exit OK", strategy);
+
+        let status = Command::new("diff")
+            .arg("-U8")
+            .arg("tests/expectations/lorem-ipsum.txt")
+            .arg(&test_case)
+            .status()
+            .expect("should run diff OK");
+        assert!(status.success(), "diff should exit OK with --oracle {}", strategy);
+    }
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--oracle")
+        .arg("clairvoyant")
+        .arg("tests/fixtures/lorem-ipsum.txt")
+        .arg("tests/predicates/has-lorem.sh")
+        .arg("chunks")
+        .status()
+        .expect("should run preduce OK");
+    assert!(!status.success(), "an unknown strategy should be rejected");
+}
+
+#[test]
+fn remote_workers() {
+    use std::io::{Read, Write};
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir::TempDir::new("remote_workers").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    // The remote workers' copy of the predicate records each time it is
+    // invoked, so we can tell that they did some of the work.
+    let count = dir.path().join("count");
+    std::fs::create_dir(dir.path().join("remote")).unwrap();
+    let remote_predicate = dir.path().join("remote").join("has-lorem.sh");
+    {
+        let mut file = std::fs::File::create(&remote_predicate).expect("should create predicate");
+        writeln!(
+            &mut file,
+            "#!/usr/bin/env sh\necho x >> {}\ngrep -i lorem \"$1\"",
+            count.display()
+        ).unwrap();
+    }
+    let mut perms = std::fs::metadata(&remote_predicate).unwrap().permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(&remote_predicate, perms).unwrap();
+
+    let addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    };
+
+    // The workers keep retrying until the coordinator is listening.
+    let mut workers: Vec<_> = (0..3)
+        .map(|_| {
+            Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-worker"))
+                .arg(&addr)
+                .arg(&remote_predicate)
+                .spawn()
+                .expect("should spawn preduce-worker OK")
+        })
+        .collect();
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--workers")
+        .arg("1")
+        .arg("--listen")
+        .arg(&addr)
+        .arg("--log-file")
+        .arg(dir.path().join("preduce.log"))
+        .arg(&test_case)
+        .arg("tests/predicates/has-lorem.sh")
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .status()
+        .expect("should run preduce OK");
+
+    for worker in &mut workers {
+        let _ = worker.kill();
+        let _ = worker.wait();
+    }
+    assert!(status.success(), "preduce should exit OK");
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+
+    let mut invocations = String::new();
+    std::fs::File::open(&count)
+        .expect("remote predicate should have been invoked")
+        .read_to_string(&mut invocations)
+        .unwrap();
+    assert!(invocations.lines().count() > 0);
+}
+
+#[test]
+fn run_returns_report() {
+    let dir = tempdir::TempDir::new("run_returns_report").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+    let orig_size = std::fs::metadata(&test_case).unwrap().len();
+
+    let predicate = preduce::interesting::Script::new("tests/predicates/has-lorem.sh")
+        .expect("should create predicate");
+    let reducer = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-chunks"
+    )).expect("should create reducer");
+
+    let report = preduce::Options::new(predicate, vec![Box::new(reducer)], &test_case)
+        .workers(1)
+        .run()
+        .expect("should run OK");
+
+    assert_eq!(report.termination, preduce::report::Termination::Fixpoint);
+    assert_eq!(report.final_path, test_case);
+    assert_eq!(report.original_size, orig_size);
+    assert_eq!(
+        report.final_size,
+        std::fs::metadata(&test_case).unwrap().len()
+    );
+    assert!(report.final_size < report.original_size);
+
+    let removed: u64 = report.reducers.values().map(|s| s.bytes_removed).sum();
+    assert_eq!(removed, report.original_size - report.final_size);
+    for (name, stats) in &report.reducers {
+        assert!(stats.interesting <= stats.tested, "{}: {:?}", name, stats);
+    }
+}
+
+#[test]
+fn bandit_oracle() {
+    let dir = tempdir::TempDir::new("bandit_oracle").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let predicate = preduce::interesting::Script::new("tests/predicates/has-lorem.sh")
+        .expect("should create predicate");
+    let chunks = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-chunks"
+    )).expect("should create reducer");
+    let blank = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-blank"
+    )).expect("should create reducer");
+
+    let report = preduce::Options::new(
+        predicate,
+        vec![Box::new(chunks), Box::new(blank)],
+        &test_case,
+    ).oracle_strategy(preduce::oracle::Strategy::Bandit)
+        .run()
+        .expect("should run OK");
+    assert_eq!(report.termination, preduce::report::Termination::Fixpoint);
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+}
+
+#[test]
+fn fair_scheduling() {
+    let dir = tempdir::TempDir::new("fair_scheduling").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let predicate = preduce::interesting::Script::new("tests/predicates/has-lorem.sh")
+        .expect("should create predicate");
+    let chunks = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-chunks"
+    )).expect("should create reducer");
+    let blank = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-blank"
+    )).expect("should create reducer");
+
+    let report = preduce::Options::new(
+        predicate,
+        vec![Box::new(chunks), Box::new(blank)],
+        &test_case,
+    ).workers(2)
+        .reducer_quota(1)
+        .priority_aging(4)
+        .exploration_share(0.25)
+        .run()
+        .expect("should run OK");
+    assert_eq!(report.termination, preduce::report::Termination::Fixpoint);
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+
+    // Every tested candidate waited in the queue first.
+    for (name, stats) in &report.reducers {
+        if stats.tested > 0 {
+            assert!(stats.peak_queued > 0, "{}: {:?}", name, stats);
+        }
+    }
+}
+
+#[test]
+fn on_event() {
+    use preduce::event::Event;
+    use std::sync::{Arc, Mutex};
+
+    let dir = tempdir::TempDir::new("on_event").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let predicate = preduce::interesting::Script::new("tests/predicates/has-lorem.sh")
+        .expect("should create predicate");
+    let reducer = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-chunks"
+    )).expect("should create reducer");
+
+    let events = Arc::new(Mutex::new(vec![]));
+    let events2 = events.clone();
+    let report = preduce::Options::new(predicate, vec![Box::new(reducer)], &test_case)
+        .workers(1)
+        .on_event(Box::new(move |event: &Event| {
+            events2.lock().unwrap().push(event.clone());
+        }))
+        .run()
+        .expect("should run OK");
+
+    let events = events.lock().unwrap();
+    let smallest_sizes: Vec<_> = events
+        .iter()
+        .filter_map(|e| match *e {
+            Event::NewSmallest { ref interesting, .. } => Some(interesting.size),
+            _ => None,
+        })
+        .collect();
+    assert!(!smallest_sizes.is_empty());
+    assert_eq!(*smallest_sizes.last().unwrap(), report.final_size);
+
+    assert!(events.contains(&Event::FinalReducedSize {
+        final_size: report.final_size,
+        original_size: report.original_size,
+    }));
+}
+
+#[test]
+fn flakiness_check() {
+    use preduce::event::Event;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let dir = tempdir::TempDir::new("flakiness_check").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    // Every other run finds every test case interesting.
+    let runs = Arc::new(AtomicUsize::new(0));
+    let predicate = move |_: &path::Path| Ok(runs.fetch_add(1, Ordering::SeqCst) % 2 == 0);
+    let reducer = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-chunks"
+    )).expect("should create reducer");
+
+    let events = Arc::new(Mutex::new(vec![]));
+    let events2 = events.clone();
+    preduce::Options::new(predicate, vec![Box::new(reducer)], &test_case)
+        .workers(1)
+        .flakiness_check(3)
+        .on_event(Box::new(move |event: &Event| {
+            events2.lock().unwrap().push(event.clone());
+        }))
+        .run()
+        .expect("should run OK");
+
+    let events = events.lock().unwrap();
+    assert!(events.contains(&Event::FlakyPredicate {
+        interesting: 2,
+        runs: 4,
+    }));
+}
+
+#[test]
+fn capture_output() {
+    use preduce::event::Event;
+    use std::sync::{Arc, Mutex};
+
+    let dir = tempdir::TempDir::new("capture_output").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+    let capture_dir = dir.path().join("outputs");
+
+    let predicate = preduce::interesting::Script::new("tests/predicates/has-lorem.sh")
+        .expect("should create predicate");
+    let reducer = preduce::reducers::Script::new(concat!(
+        env!("PREDUCE_TARGET_DIR"),
+        "/preduce-reducer-chunks"
+    )).expect("should create reducer");
+
+    let events = Arc::new(Mutex::new(vec![]));
+    let events2 = events.clone();
+    preduce::Options::new(predicate, vec![Box::new(reducer)], &test_case)
+        .workers(1)
+        .capture_output(&capture_dir, preduce::capture::Retention::All)
+        .on_event(Box::new(move |event: &Event| {
+            events2.lock().unwrap().push(event.clone());
+        }))
+        .run()
+        .expect("should run OK");
+
+    let events = events.lock().unwrap();
+    let outputs: Vec<_> = events
+        .iter()
+        .filter_map(|event| match *event {
+            Event::JudgedInteresting { ref output, .. } |
+            Event::JudgedNotInteresting { ref output, .. } => Some(output.clone()),
+            _ => None,
+        })
+        .collect();
+    assert!(!outputs.is_empty(), "should judge some candidates");
+
+    for output in outputs {
+        let output = output.expect("each judgement should link to its output");
+        assert!(output.starts_with(&capture_dir));
+        let contents = std::fs::read_to_string(&output).expect("should read captured output");
+        assert!(contents.contains("--- stderr ---"), "{}", contents);
+        assert!(contents.contains("grep -i lorem"), "{}", contents);
+    }
+}
+
+#[test]
+fn sandbox_aux_files() {
+    use std::io::Write;
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir::TempDir::new("sandbox_aux_files").expect("should create temp dir");
+    let test_case = dir.path().join("lorem-ipsum.txt");
+    std::fs::copy("tests/fixtures/lorem-ipsum.txt", &test_case).expect("should copy fixture");
+
+    let aux_file = dir.path().join("magic-word");
+    std::fs::File::create(&aux_file)
+        .and_then(|mut f| write!(&mut f, "lorem"))
+        .expect("should create aux file");
+
+    // A predicate that needs its aux file next to the candidate, and writes to
+    // a fixed output file name that would clobber other workers' outside of a
+    // sandbox.
+    let predicate = dir.path().join("predicate.sh");
+    {
+        let mut file = std::fs::File::create(&predicate).expect("should create predicate");
+        writeln!(
+            &mut file,
+            "#!/usr/bin/env sh
+             test \"$1\" = lorem-ipsum.txt || exit 2
+             test \"$(pwd -P)\" = \"$PREDUCE_SCRATCH_DIR\" || exit 2
+             test -n \"$PREDUCE_WORKER_ID\" || exit 2
+             cmp -s \"$PREDUCE_ORIGINAL\" {} || exit 2
+             grep -i \"$(cat magic-word)\" \"$1\" > out.txt
+             test -s out.txt",
+            std::fs::canonicalize("tests/fixtures/lorem-ipsum.txt")
+                .unwrap()
+                .display()
+        ).unwrap();
+    }
+    let mut perms = std::fs::metadata(&predicate).unwrap().permissions();
+    perms.set_mode(0o755);
+    std::fs::set_permissions(&predicate, perms).unwrap();
+
+    let status = Command::new(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce"))
+        .arg("--workers")
+        .arg("4")
+        .arg("--aux-files")
+        .arg(&aux_file)
+        .arg(&test_case)
+        .arg(&predicate)
+        .arg(concat!(env!("PREDUCE_TARGET_DIR"), "/preduce-reducer-chunks"))
+        .status()
+        .expect("should run preduce OK");
+    assert!(status.success(), "preduce should exit OK");
+
+    let status = Command::new("diff")
+        .arg("-U8")
+        .arg("tests/expectations/lorem-ipsum.txt")
+        .arg(&test_case)
+        .status()
+        .expect("should run diff OK");
+    assert!(status.success(), "diff should exit OK");
+}
+
 fn test_reducer<P, Q, I, R>(reducer: P, seed: Q, expecteds: I)
 where
     P: AsRef<path::Path>,
@@ -203,7 +1144,11 @@ where
         .expect("should run interesting test OK")
         .expect("should be interesting");
 
-    let mut reducer = preduce::reducers::Script::new(reducer).expect("should create reducer OK");
+    // Reducers that aren't paths to reducer scripts are built-in reducers.
+    let mut reducer = match reducer.as_ref().to_str().and_then(preduce::reducers::builtin) {
+        Some(builtin) => builtin,
+        None => Box::new(preduce::reducers::Script::new(reducer).expect("should create reducer OK")),
+    };
     let state = reducer
         .new_state(&seed)
         .expect("reducer should create new state");
@@ -386,6 +1331,56 @@ test_reducers! {
             "tests/expectations/includes-3",
         ]
     }
+    in_process_balanced_paren => {
+        "balanced-paren",
+        seeded with "tests/fixtures/parens.txt",
+        generates [
+            "tests/expectations/balanced-paren-0",
+            "tests/expectations/balanced-paren-1",
+            "tests/expectations/balanced-paren-2",
+            "tests/expectations/balanced-paren-3",
+            "tests/expectations/balanced-paren-4",
+            "tests/expectations/balanced-paren-5",
+            "tests/expectations/balanced-paren-6",
+            "tests/expectations/balanced-paren-7",
+            "tests/expectations/balanced-paren-8",
+            "tests/expectations/balanced-paren-9",
+        ]
+    }
+    in_process_blank => {
+        "blank",
+        seeded with "tests/fixtures/wow.cpp",
+        generates [
+            "tests/expectations/blank-0",
+            "tests/expectations/blank-1",
+            "tests/expectations/blank-2",
+        ]
+    }
+    in_process_chunks => {
+        "chunks",
+        seeded with "tests/fixtures/lines.txt",
+        generates [
+            "tests/expectations/chunks-0",
+            "tests/expectations/chunks-1",
+            "tests/expectations/chunks-2",
+            "tests/expectations/chunks-3",
+            "tests/expectations/chunks-4",
+            "tests/expectations/chunks-5",
+            "tests/expectations/chunks-6",
+            "tests/expectations/chunks-7",
+            "tests/expectations/chunks-8",
+        ]
+    }
+    in_process_includes => {
+        "includes",
+        seeded with "tests/fixtures/some-includes.cpp",
+        generates [
+            "tests/expectations/includes-0",
+            "tests/expectations/includes-1",
+            "tests/expectations/includes-2",
+            "tests/expectations/includes-3",
+        ]
+    }
 }
 
 // For whatever reason, we can't find `clex` on Travis CI.